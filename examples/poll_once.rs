@@ -0,0 +1,30 @@
+//! Poll Azure DevOps once with the settings of a config file and print every decision.
+//!
+//! ```bash
+//! cargo run --example poll_once -- config.toml
+//! ```
+
+use anyhow::Result;
+use fast_pr_reviewer::config::ConfigOverrides;
+use fast_pr_reviewer::{AppConfig, AzureDevOpsClient, Reviewer};
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let path = std::env::args().nth(1).unwrap_or_else(|| "config.toml".to_string());
+    let config = AppConfig::from_file(&path, &ConfigOverrides::default())?;
+    let client = AzureDevOpsClient::new(&config.organization, &config.project, &config.personal_access_token);
+
+    let reviewer = Reviewer::new(client, config)?;
+    reviewer.on_decision(|decision| {
+        println!("PR #{} in {} from {}: {} -> {} {}", decision.pr_id, decision.repository, decision.author,
+            decision.action, decision.outcome, decision.detail);
+    });
+    // Only PRs created after the engine started are approved, so look at the open ones explicitly
+    for pr in reviewer.client().get_active_pull_requests().await? {
+        reviewer.recheck(pr.pull_request_id).await;
+    }
+
+    let new_count = reviewer.run_once().await?;
+    println!("{} new pull requests", new_count);
+    Ok(())
+}
@@ -0,0 +1,37 @@
+//! Keep approving PRs from watched users until Ctrl+C, counting approvals through a decision callback.
+//!
+//! ```bash
+//! cargo run --example run_until_ctrl_c -- config.toml
+//! ```
+
+use anyhow::Result;
+use fast_pr_reviewer::config::ConfigOverrides;
+use fast_pr_reviewer::{AppConfig, AzureDevOpsClient, Reviewer};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt::init();
+
+    let path = std::env::args().nth(1).unwrap_or_else(|| "config.toml".to_string());
+    let config = AppConfig::from_file(&path, &ConfigOverrides::default())?;
+    let client = AzureDevOpsClient::new(&config.organization, &config.project, &config.personal_access_token)
+        .with_rate_limit(2, 1.0);
+
+    let mut reviewer = Reviewer::new(client, config)?;
+    let approvals = Arc::new(AtomicUsize::new(0));
+    let counter = approvals.clone();
+    reviewer.on_decision(move |decision| {
+        if decision.outcome == "approved" {
+            counter.fetch_add(1, Ordering::Relaxed);
+        }
+    });
+
+    reviewer.run(Duration::from_secs(30), async {
+        let _ = tokio::signal::ctrl_c().await;
+    }).await?;
+    println!("Approved {} pull requests", approvals.load(Ordering::Relaxed));
+    Ok(())
+}
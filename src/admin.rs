@@ -0,0 +1,374 @@
+use anyhow::{Context, Result};
+use axum::extract::{Path, Query, Request, State};
+use axum::http::{header, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{delete, get, post};
+use axum::{Json, Router};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use tokio::sync::{mpsc, oneshot};
+use tracing::{error, info};
+
+use fast_pr_reviewer::config::{AdminApiConfig, ListenAddress};
+use fast_pr_reviewer::decisions::Decision;
+use fast_pr_reviewer::ReviewerState;
+
+// Decisions returned by GET /decisions unless a limit is given
+const DEFAULT_DECISION_LIMIT: usize = 50;
+
+/// A request only the main loop can carry out, as it needs the client and the current config
+pub enum AdminRequest {
+    Approve { pr_id: i32, reply: oneshot::Sender<Result<()>> },
+    /// Replies with the config fields that changed
+    Reload { reply: oneshot::Sender<Result<Vec<String>>> },
+    /// Polling was resumed, so the main loop should poll right away
+    Resumed,
+}
+
+#[derive(Clone)]
+struct AdminState {
+    token: Arc<str>,
+    reviewer: Arc<ReviewerState>,
+    requests: mpsc::Sender<AdminRequest>,
+}
+
+/// Start serving the admin API in the background
+pub async fn start(
+    config: &AdminApiConfig,
+    reviewer: Arc<ReviewerState>,
+    requests: mpsc::Sender<AdminRequest>,
+) -> Result<()> {
+    let router = router(AdminState { token: config.token.as_str().into(), reviewer, requests });
+
+    match config.listen_address()? {
+        ListenAddress::Tcp(address) => {
+            let listener = TcpListener::bind(address).await
+                .with_context(|| format!("Failed to listen on {}", address))?;
+            info!("🛠 Admin API listening on http://{}", address);
+            tokio::spawn(async move {
+                if let Err(e) = axum::serve(listener, router).await {
+                    error!("❌ Admin API stopped: {}", e);
+                }
+            });
+        }
+        #[cfg(unix)]
+        ListenAddress::Unix(path) => {
+            let listener = bind_unix_socket(&path)?;
+            info!("🛠 Admin API listening on {:?}", path);
+            tokio::spawn(async move {
+                if let Err(e) = axum::serve(listener, router).await {
+                    error!("❌ Admin API stopped: {}", e);
+                }
+            });
+        }
+        #[cfg(not(unix))]
+        ListenAddress::Unix(_) => unreachable!("unix sockets are rejected when validating the config"),
+    }
+
+    Ok(())
+}
+
+fn router(state: AdminState) -> Router {
+    Router::new()
+        .route("/pause", post(pause))
+        .route("/resume", post(resume))
+        .route("/prs", get(list_prs))
+        .route("/prs/{pr_id}/approve", post(approve))
+        .route("/prs/{pr_id}/recheck", post(recheck))
+        .route("/prs/{pr_id}/seen", delete(forget))
+        .route("/reload", post(reload))
+        .route("/decisions", get(recent_decisions))
+        .route_layer(middleware::from_fn_with_state(state.clone(), authorize))
+        .with_state(state)
+}
+
+/// An error response with a JSON body
+struct AdminError(StatusCode, String);
+
+impl IntoResponse for AdminError {
+    fn into_response(self) -> Response {
+        (self.0, Json(json!({ "error": self.1 }))).into_response()
+    }
+}
+
+fn message(text: impl Into<String>) -> Json<serde_json::Value> {
+    Json(json!({ "message": text.into() }))
+}
+
+async fn authorize(State(state): State<AdminState>, request: Request, next: Next) -> Response {
+    let given = request.headers().get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    match given {
+        Some(token) if token_matches(token, &state.token) => next.run(request).await,
+        _ => AdminError(StatusCode::UNAUTHORIZED, "missing or invalid bearer token".to_string()).into_response(),
+    }
+}
+
+/// Compare without stopping at the first difference, so response times don't reveal the token
+fn token_matches(given: &str, expected: &str) -> bool {
+    given.len() == expected.len()
+        && given.bytes().zip(expected.bytes()).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+async fn pause(State(state): State<AdminState>) -> Json<serde_json::Value> {
+    state.reviewer.set_paused(true).await;
+    info!("⏸ Polling paused through the admin API");
+    Json(json!({ "paused": true }))
+}
+
+async fn resume(State(state): State<AdminState>) -> Json<serde_json::Value> {
+    state.reviewer.set_paused(false).await;
+    info!("▶ Polling resumed through the admin API");
+    let _ = state.requests.try_send(AdminRequest::Resumed);
+    Json(json!({ "paused": false }))
+}
+
+/// A tracked PR and everything the bot currently knows about it
+#[derive(Serialize)]
+struct TrackedPrState {
+    pr_id: i32,
+    repository: String,
+    author: String,
+    title: String,
+    created: Option<DateTime<Utc>>,
+    seen: bool,
+    /// Why a rule condition holds the PR back
+    blocked: Option<String>,
+    /// Waiting for the approval window to open
+    queued: bool,
+    /// When a delayed approval is due
+    approval_due: Option<DateTime<Utc>>,
+    /// Approved and waiting to be completed by the bot
+    completing: bool,
+    last_decision: Option<Decision>,
+}
+
+async fn list_prs(State(state): State<AdminState>) -> Json<Vec<TrackedPrState>> {
+    let reviewer = &state.reviewer;
+    let recent = reviewer.decisions().recent();
+    let mut prs = Vec::new();
+    for tracked in reviewer.tracked_prs().await {
+        let approval_due = reviewer.approval_queue().lock().await.entries().iter()
+            .find(|entry| entry.pr_id == tracked.pr_id)
+            .map(|entry| entry.due_at);
+        prs.push(TrackedPrState {
+            pr_id: tracked.pr_id,
+            seen: reviewer.is_seen(tracked.pr_id).await,
+            blocked: reviewer.blocked_reason(tracked.pr_id).await,
+            queued: reviewer.is_queued(tracked.pr_id).await,
+            approval_due,
+            completing: reviewer.is_completing(tracked.pr_id).await,
+            last_decision: recent.iter().rev().find(|decision| decision.pr_id == tracked.pr_id).cloned(),
+            repository: tracked.repository,
+            author: tracked.author,
+            title: tracked.title,
+            created: tracked.created,
+        });
+    }
+    prs.sort_unstable_by_key(|pr| pr.pr_id);
+    Json(prs)
+}
+
+async fn approve(State(state): State<AdminState>, Path(pr_id): Path<i32>) -> Result<Json<serde_json::Value>, AdminError> {
+    info!("🛠 Approving PR #{} through the admin API", pr_id);
+    let (reply, result) = oneshot::channel();
+    send(&state, AdminRequest::Approve { pr_id, reply }).await?;
+
+    match result.await {
+        Ok(Ok(())) => Ok(message(format!("PR #{} approved", pr_id))),
+        Ok(Err(e)) => Err(AdminError(StatusCode::BAD_GATEWAY, format!("Failed to approve PR #{}: {:#}", pr_id, e))),
+        Err(_) => Err(shutting_down()),
+    }
+}
+
+async fn recheck(State(state): State<AdminState>, Path(pr_id): Path<i32>) -> Json<serde_json::Value> {
+    state.reviewer.recheck(pr_id).await;
+    info!("🛠 PR #{} will be re-evaluated, as requested through the admin API", pr_id);
+    message(format!("PR #{} will be re-evaluated on the next poll", pr_id))
+}
+
+async fn forget(State(state): State<AdminState>, Path(pr_id): Path<i32>) -> Result<Json<serde_json::Value>, AdminError> {
+    if !state.reviewer.forget(pr_id).await {
+        return Err(AdminError(StatusCode::NOT_FOUND, format!("PR #{} has not been seen", pr_id)));
+    }
+    info!("🛠 Cleared PR #{} from the seen PRs through the admin API", pr_id);
+    Ok(message(format!("PR #{} cleared from the seen PRs", pr_id)))
+}
+
+async fn reload(State(state): State<AdminState>) -> Result<Json<serde_json::Value>, AdminError> {
+    info!("🛠 Reloading the configuration through the admin API");
+    let (reply, result) = oneshot::channel();
+    send(&state, AdminRequest::Reload { reply }).await?;
+
+    match result.await {
+        Ok(Ok(changed)) => Ok(Json(json!({ "changed": changed }))),
+        Ok(Err(e)) => Err(AdminError(StatusCode::UNPROCESSABLE_ENTITY, format!("{:#}", e))),
+        Err(_) => Err(shutting_down()),
+    }
+}
+
+#[derive(Deserialize)]
+struct DecisionQuery {
+    limit: Option<usize>,
+    pr_id: Option<i32>,
+}
+
+async fn recent_decisions(State(state): State<AdminState>, Query(query): Query<DecisionQuery>) -> Json<Vec<Decision>> {
+    let mut decisions: Vec<Decision> = state.reviewer.decisions().recent().into_iter()
+        .filter(|decision| query.pr_id.is_none_or(|pr_id| decision.pr_id == pr_id))
+        .collect();
+    let limit = query.limit.unwrap_or(DEFAULT_DECISION_LIMIT);
+    decisions.drain(..decisions.len().saturating_sub(limit));
+    Json(decisions)
+}
+
+/// Listen on a unix socket only the bot's user can connect to. The socket is created inside a private
+/// directory and then moved into place, so it is never reachable with looser permissions
+#[cfg(unix)]
+fn bind_unix_socket(path: &std::path::Path) -> Result<tokio::net::UnixListener> {
+    use std::fs::{self, DirBuilder, Permissions};
+    use std::os::unix::fs::{DirBuilderExt, FileTypeExt, PermissionsExt};
+
+    // A socket left behind by an earlier run would make binding fail; anything else at the path is not ours to delete
+    match fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.file_type().is_socket() => {
+            fs::remove_file(path).with_context(|| format!("Failed to remove stale socket {:?}", path))?;
+        }
+        Ok(_) => return Err(anyhow::anyhow!("{:?} already exists and is not a socket", path)),
+        Err(_) => (),
+    }
+
+    let file_name = path.file_name().with_context(|| format!("{:?} is not a socket path", path))?;
+    let private_dir = path.with_file_name(format!(".{}.{}", file_name.to_string_lossy(), std::process::id()));
+    DirBuilder::new().mode(0o700).create(&private_dir)
+        .with_context(|| format!("Failed to create {:?}", private_dir))?;
+    let staged = private_dir.join("admin.sock");
+
+    let result = tokio::net::UnixListener::bind(&staged)
+        .with_context(|| format!("Failed to listen on {:?}", path))
+        .and_then(|listener| {
+            fs::set_permissions(&staged, Permissions::from_mode(0o600))
+                .with_context(|| format!("Failed to restrict permissions of {:?}", path))?;
+            fs::rename(&staged, path).with_context(|| format!("Failed to move the socket to {:?}", path))?;
+            Ok(listener)
+        });
+    let _ = fs::remove_file(&staged);
+    let _ = fs::remove_dir(&private_dir);
+    result
+}
+
+async fn send(state: &AdminState, request: AdminRequest) -> Result<(), AdminError> {
+    state.requests.send(request).await.map_err(|_| shutting_down())
+}
+
+fn shutting_down() -> AdminError {
+    AdminError(StatusCode::SERVICE_UNAVAILABLE, "the bot is shutting down".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fast_pr_reviewer::cassette::{MatchOn, Player};
+    use fast_pr_reviewer::{AppConfig, AzureDevOpsClient, Reviewer};
+
+    /// A reviewer that approved PR #4242 in a recorded session
+    async fn reviewer_with_tracked_pr() -> Reviewer {
+        let player = Player::load("tests/cassettes/approve_new_pr.jsonl", &[MatchOn::Method, MatchOn::Path, MatchOn::Query]).unwrap();
+        let client = AzureDevOpsClient::with_base_url("https://dev.azure.com/contoso/web", "admin-test-pat-0123").with_player(player);
+        let queue_path = std::env::temp_dir().join(format!("fpr-admin-test-queue-{}.json", std::process::id()));
+        let config: AppConfig = toml::from_str(&format!(r#"
+            organization = "contoso"
+            project = "web"
+            reviewer_id = "bot-id"
+            watched_users = ["Jane Doe"]
+            approval_queue = {:?}
+        "#, queue_path)).unwrap();
+        let reviewer = Reviewer::new(client, config).unwrap();
+        reviewer.recheck(4242).await;
+        reviewer.run_once().await.unwrap();
+        reviewer
+    }
+
+    async fn serve_test_api(reviewer: Arc<ReviewerState>) -> (String, mpsc::Receiver<AdminRequest>) {
+        let (requests, received) = mpsc::channel(8);
+        let state = AdminState { token: "admin-test-token".into(), reviewer, requests };
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, router(state)).await.unwrap() });
+        (format!("http://{}", address), received)
+    }
+
+    #[tokio::test]
+    async fn admin_api_requires_token_and_manages_reviewer_state() {
+        let reviewer = reviewer_with_tracked_pr().await;
+        let (base_url, mut requests) = serve_test_api(reviewer.state().clone()).await;
+        let client = reqwest::Client::new();
+        let pr_id = 4242;
+
+        let unauthorized = client.get(format!("{}/prs", base_url)).bearer_auth("wrong-token").send().await.unwrap();
+        assert_eq!(unauthorized.status(), StatusCode::UNAUTHORIZED);
+
+        let prs: serde_json::Value = client.get(format!("{}/prs", base_url)).bearer_auth("admin-test-token")
+            .send().await.unwrap().json().await.unwrap();
+        let pr = prs.as_array().unwrap().iter().find(|pr| pr["pr_id"] == pr_id).unwrap();
+        assert_eq!(pr["seen"], true);
+        assert_eq!(pr["last_decision"]["outcome"], "approved");
+
+        let cleared = client.delete(format!("{}/prs/{}/seen", base_url, pr_id)).bearer_auth("admin-test-token")
+            .send().await.unwrap();
+        assert_eq!(cleared.status(), StatusCode::OK);
+        assert!(!reviewer.state().is_seen(pr_id).await);
+
+        let paused = client.post(format!("{}/pause", base_url)).bearer_auth("admin-test-token").send().await.unwrap();
+        assert_eq!(paused.status(), StatusCode::OK);
+        assert!(reviewer.is_paused().await);
+
+        // Approvals are carried out by the main loop
+        let approval = tokio::spawn(client.post(format!("{}/prs/{}/approve", base_url, pr_id))
+            .bearer_auth("admin-test-token").send());
+        match requests.recv().await.unwrap() {
+            AdminRequest::Approve { pr_id: requested, reply } => {
+                assert_eq!(requested, pr_id);
+                reply.send(Ok(())).unwrap();
+            }
+            _ => panic!("expected an approve request"),
+        }
+        assert_eq!(approval.await.unwrap().unwrap().status(), StatusCode::OK);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn unix_socket_replaces_only_stale_sockets() {
+        use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+
+        let dir = std::env::temp_dir().join(format!("fpr-admin-socket-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("admin.sock");
+
+        // A regular file is left alone
+        std::fs::write(&path, "not a socket").unwrap();
+        assert!(bind_unix_socket(&path).unwrap_err().to_string().contains("not a socket"));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "not a socket");
+        std::fs::remove_file(&path).unwrap();
+
+        // A socket left behind by an earlier run is replaced
+        drop(bind_unix_socket(&path).unwrap());
+        let listener = bind_unix_socket(&path).unwrap();
+        let metadata = std::fs::symlink_metadata(&path).unwrap();
+        assert!(metadata.file_type().is_socket());
+        assert_eq!(metadata.permissions().mode() & 0o777, 0o600);
+        assert!(tokio::net::UnixStream::connect(&path).await.is_ok());
+        drop(listener);
+
+        // No private directory is left behind
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
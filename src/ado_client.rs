@@ -1,44 +1,145 @@
 use anyhow::{Context, Result};
 use base64::{engine::general_purpose, Engine as _};
-use log::{debug, info, warn};
-use reqwest::{Client, header, StatusCode};
+use tracing::{debug, error, info, warn};
+use reqwest::{Client, RequestBuilder, Response, header, StatusCode};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Mutex;
 use std::time::Duration;
 use tokio::time::sleep;
 use rand::Rng;
+use url::Url;
 
-use crate::models::{PullRequest, PullRequestList, ReviewRequest, Reviewer, ReviewerList};
+use crate::cassette::{Player, RecordedRequest, RecordedResponse, Recorder};
+use crate::config::PatSource;
+use crate::rate_limiter::{RateLimiter, RateLimiterStatus, DEFAULT_BURST, DEFAULT_REQUESTS_PER_SECOND};
+use crate::redact;
+use crate::models::{
+    AddReviewerRequest, CommentRequest, CommentThread, CommentThreadList, CommentThreadRequest, ConnectionData, FileChange, Identity, IdentityList,
+    IterationChanges, IterationList, PermissionCheck, PolicyEvaluation, PolicyEvaluationList, PullRequest, PullRequestList, PullRequestUpdate, Repository, RepositoryList, ResourceRef,
+    ResourceRefList, ReviewRequest, Reviewer, ReviewerList, TeamProject, WorkItem, WorkItemList,
+};
+
+/// Error returned when Azure DevOps answers with a non-success status
+#[derive(Debug)]
+pub struct ApiError {
+    pub status: StatusCode,
+    pub body: String,
+}
+
+impl ApiError {
+    async fn from_response(response: reqwest::Response) -> Self {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_else(|_| String::from("Unable to read response body"));
+        // Responses can echo request details back, including credentials
+        Self { status, body: redact::redact(&body) }
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "API request failed with status {}: {}", self.status, self.body)
+    }
+}
+
+impl std::error::Error for ApiError {}
 
 /// Azure DevOps API client
 pub struct AzureDevOpsClient {
     client: Client,
     base_url: String,
-    auth_header: String,
+    // Organization-level URLs for APIs that aren't scoped to the project
+    organization_url: String,
+    identity_url: String,
+    project: String,
+    auth_header: Mutex<String>,
+    // Re-read after a 401 or a sign-in redirect so a rotated PAT is picked up without a restart
+    pat_source: Option<PatSource>,
     max_retries: u32,
     initial_retry_delay_ms: u64,
+    rate_limiter: RateLimiter,
+    // Last page of the active PR list at each offset with its ETag, so unchanged pages aren't downloaded again
+    pr_list_cache: Mutex<HashMap<usize, CachedResponse>>,
+    cassette: Option<CassetteMode>,
+}
+
+/// Whether requests are recorded to, or answered from, a cassette
+enum CassetteMode {
+    Record(Recorder),
+    Replay(Player),
+}
+
+/// A response body kept for conditional requests
+struct CachedResponse {
+    etag: String,
+    body: String,
 }
 
 const API_VERSION: &str = "7.1";
 
+// Vote values: 10 = approve, 5 = approve with suggestions, 0 = no vote, -5 = waiting for author, -10 = reject
+pub const VOTE_APPROVE: i32 = 10;
+pub const VOTE_WAIT_FOR_AUTHOR: i32 = -5;
+pub const VOTE_REJECT: i32 = -10;
+const POLICY_API_VERSION: &str = "7.1-preview.1";
+
+// Active PRs are listed this many at a time
+const PR_PAGE_SIZE: usize = 100;
+
+// The work items API accepts at most this many IDs per request
+const MAX_WORK_ITEMS_PER_REQUEST: usize = 200;
+
+// Git Repositories security namespace and its "Contribute to pull requests" permission bit
+const GIT_SECURITY_NAMESPACE: &str = "2e9eb7ed-3c0a-47d4-87c1-0ffdd275fd87";
+const PULL_REQUEST_CONTRIBUTE: u32 = 16384;
+
 impl AzureDevOpsClient {
     pub fn new(organization: &str, project: &str, pat: &str) -> Self {
         // Modified to handle custom URL structures
         // The URL structure from the error logs suggests your organization might be using a
         // custom domain or on-premise Azure DevOps Server
-        let base_url = if organization.contains(".") {
+        let (base_url, organization_url, identity_url) = if organization.starts_with("http://") || organization.starts_with("https://") {
+            // A full URL, e.g. an Azure DevOps Server collection or a local mock server
+            let url = organization.trim_end_matches('/').to_string();
+            (format!("{}/{}", url, project), url.clone(), identity_url_for(&url))
+        } else if organization.contains(".") {
             // Custom domain approach
-            format!("https://{}", organization)
+            let url = format!("https://{}", organization.trim_end_matches('/'));
+            (format!("{}/{}", url, project), url.clone(), url)
         } else {
-            // Standard Azure DevOps Services
-            format!("https://dev.azure.com/{}/{}", organization, project)
+            // Standard Azure DevOps Services; identities live on a separate host
+            (format!("https://dev.azure.com/{}/{}", organization, project),
+             format!("https://dev.azure.com/{}", organization),
+             format!("https://vssps.dev.azure.com/{}", organization))
         };
 
-        // Log the base URL for debugging
-        info!("Using ADO base URL: {}", base_url);
         info!("Organization: {}, Project: {}", organization, project);
 
+        let mut client = Self::with_base_url(&base_url, pat);
+        client.organization_url = organization_url;
+        client.identity_url = identity_url;
+        client.project = project.to_string();
+        client
+    }
+
+    /// Create a client against an explicit project URL such as "https://dev.azure.com/contoso/web", or the
+    /// root of a mock server, which leaves the project unknown
+    pub fn with_base_url(base_url: &str, pat: &str) -> Self {
+        let base_url = base_url.trim_end_matches('/').to_string();
+        // The last path segment names the project, and the organization is what comes before it
+        let (organization_url, project) = match base_url.rsplit_once('/') {
+            Some((organization, project)) if Url::parse(&base_url).is_ok_and(|url| url.path() != "/") => {
+                (organization.to_string(), project.to_string())
+            }
+            _ => (base_url.clone(), String::new()),
+        };
+
+        // Log the base URL for debugging
+        debug!("Using ADO base URL: {}", base_url);
+
         // Create auth header using PAT (Personal Access Token)
-        let auth_token = general_purpose::STANDARD.encode(format!(":{}", pat));
-        let auth_header = format!("Basic {}", auth_token);
+        redact::register_pat(pat);
+        let auth_header = basic_auth_header(pat);
 
         // Create HTTP client with default headers
         let mut headers = header::HeaderMap::new();
@@ -59,10 +160,131 @@ impl AzureDevOpsClient {
 
         Self {
             client,
+            identity_url: identity_url_for(&organization_url),
+            organization_url,
+            project,
             base_url,
-            auth_header,
+            auth_header: Mutex::new(auth_header),
+            pat_source: None,
             max_retries: 5,  // Default max retries
             initial_retry_delay_ms: 1000,  // Start with 1 second delay
+            rate_limiter: RateLimiter::new(DEFAULT_BURST, DEFAULT_REQUESTS_PER_SECOND),
+            pr_list_cache: Mutex::default(),
+            cassette: None,
+        }
+    }
+
+    /// Replace the default client-side rate limit
+    pub fn with_rate_limit(mut self, burst: u32, requests_per_second: f64) -> Self {
+        self.rate_limiter = RateLimiter::new(burst, requests_per_second);
+        self
+    }
+
+    /// Current state of the client-side rate limiter
+    pub fn rate_limit_status(&self) -> RateLimiterStatus {
+        self.rate_limiter.status()
+    }
+
+    /// Re-read the PAT from this source when Azure DevOps rejects the current one
+    pub fn with_pat_source(mut self, source: PatSource) -> Self {
+        self.pat_source = Some(source);
+        self
+    }
+
+    /// Save every request and response to a cassette file
+    pub fn with_recorder(mut self, recorder: Recorder) -> Self {
+        self.cassette = Some(CassetteMode::Record(recorder));
+        self
+    }
+
+    /// Answer requests from a recorded cassette instead of the network
+    pub fn with_player(mut self, player: Player) -> Self {
+        self.cassette = Some(CassetteMode::Replay(player));
+        self
+    }
+
+    /// Send an authenticated request, re-reading the PAT once if it was rejected
+    async fn send(&self, request: RequestBuilder) -> Result<Response> {
+        let retry = request.try_clone();
+        let mut response = self.send_once(request).await?;
+
+        // Instead of a 401, Azure DevOps sometimes answers a bad PAT with a 203 and its sign-in page
+        if matches!(response.status(), StatusCode::UNAUTHORIZED | StatusCode::NON_AUTHORITATIVE_INFORMATION)
+            && let Some(retry) = retry
+            && self.refresh_pat().await
+        {
+            info!("🔑 Picked up a new PAT after a {}, retrying the request", response.status().as_u16());
+            response = self.send_once(retry).await?;
+        }
+
+        if response.status() == StatusCode::NON_AUTHORITATIVE_INFORMATION {
+            return Err(anyhow::anyhow!("Azure DevOps redirected to its sign-in page (203), the PAT is invalid or expired"));
+        }
+
+        Ok(response)
+    }
+
+    /// Send an authenticated request once the rate limiter allows it, and learn from its throttling headers
+    async fn send_once(&self, request: RequestBuilder) -> Result<Response> {
+        self.rate_limiter.acquire().await;
+
+        let auth_header = self.auth_header.lock().unwrap().clone();
+        let request = request
+            .header(header::AUTHORIZATION, auth_header)
+            .build()?;
+
+        let response = match &self.cassette {
+            None => self.client.execute(request).await?,
+            Some(CassetteMode::Record(recorder)) => {
+                let recorded = recorded_request(&request);
+                let response = self.client.execute(request).await?;
+                let status = response.status();
+                let headers = response.headers().clone();
+                let body = response.bytes().await?;
+                recorder.record(recorded, RecordedResponse::new(status.as_u16(), &headers, &body));
+
+                let mut replay = http::Response::builder().status(status);
+                if let Some(replay_headers) = replay.headers_mut() {
+                    *replay_headers = headers;
+                }
+                Response::from(replay.body(body)?)
+            }
+            Some(CassetteMode::Replay(player)) => {
+                let recorded = player.play(&recorded_request(&request))?;
+                let mut replay = http::Response::builder().status(recorded.status);
+                for (name, value) in &recorded.headers {
+                    replay = replay.header(name, value);
+                }
+                Response::from(replay.body(recorded.body)?)
+            }
+        };
+
+        self.rate_limiter.update_from_headers(response.headers());
+        Ok(response)
+    }
+
+    /// Read the PAT again from its source; returns whether it changed
+    async fn refresh_pat(&self) -> bool {
+        let Some(source) = &self.pat_source else {
+            return false;
+        };
+
+        match source.read_async().await {
+            Ok(pat) => {
+                redact::register_pat(&pat);
+                let auth_header = basic_auth_header(&pat);
+                let mut current = self.auth_header.lock().unwrap();
+                if *current == auth_header {
+                    warn!("⚠ Azure DevOps rejected the PAT and {} still has the same one", source);
+                    return false;
+                }
+                *current = auth_header;
+                true
+            }
+            Err(e) => {
+                error!("❌ Failed to re-read the PAT: {:#}", e);
+                false
+            }
         }
     }
 
@@ -89,7 +311,10 @@ impl AzureDevOpsClient {
                     }
                     
                     // Check if the error is retryable (rate limiting, server errors)
-                    let should_retry = if let Some(reqwest_err) = e.downcast_ref::<reqwest::Error>() {
+                    let should_retry = if let Some(api_error) = e.downcast_ref::<ApiError>() {
+                        // The rate limiter has already paused for any Retry-After the server sent
+                        api_error.status == StatusCode::TOO_MANY_REQUESTS || api_error.status.is_server_error()
+                    } else if let Some(reqwest_err) = e.downcast_ref::<reqwest::Error>() {
                         if let Some(status) = reqwest_err.status() {
                             match status {
                                 // Rate limiting
@@ -112,12 +337,12 @@ impl AzureDevOpsClient {
                         return Err(e);
                     }
                     
-                    // Add jitter to prevent all clients retrying at the same time
-                    let mut rng = rand::rng();
-                    let jitter = rng.random_range(1..=100) as u64;
+                    // Add jitter to prevent all clients retrying at the same time; the RNG isn't held across the
+                    // sleep, so requests can run on any worker thread
+                    let jitter = rand::rng().random_range(1..=100) as u64;
                     let backoff_delay = delay + jitter;
                     
-                    warn!("{} failed (attempt {}/{}), retrying in {}ms", 
+                    warn!(operation, attempt, "{} failed (attempt {}/{}), retrying in {}ms", 
                         operation, attempt, self.max_retries, backoff_delay);
                     
                     // Wait before retrying
@@ -130,39 +355,90 @@ impl AzureDevOpsClient {
         }
     }
 
-    /// Get all active pull requests
+    /// Get all active pull requests, newest first, a page at a time
     pub async fn get_active_pull_requests(&self) -> Result<Vec<PullRequest>> {
+        debug!("Fetching active pull requests");
+
+        let mut prs: Vec<PullRequest> = Vec::new();
+        let mut skip = 0;
+        loop {
+            let page = self.get_active_pull_request_page(skip).await?;
+            let last_page = page.len() < PR_PAGE_SIZE;
+            skip += page.len();
+
+            // A PR opened while paging pushes older ones onto the next page, where they'd show up twice
+            for pr in page {
+                if !prs.iter().any(|listed| listed.pull_request_id == pr.pull_request_id) {
+                    prs.push(pr);
+                }
+            }
+            if last_page {
+                return Ok(prs);
+            }
+        }
+    }
+
+    async fn get_active_pull_request_page(&self, skip: usize) -> Result<Vec<PullRequest>> {
         let url = format!(
-            "{}/_apis/git/pullrequests?api-version={}&status=active&$top=10&$orderby=creationDate desc",
-            self.base_url, API_VERSION
+            "{}/_apis/git/pullrequests?api-version={}&status=active&$top={}&$skip={}&$orderby=creationDate desc",
+            self.base_url, API_VERSION, PR_PAGE_SIZE, skip
         );
 
-        debug!("Fetching active pull requests");
-        info!("Request URL: {}", url);
+        debug!("Request URL: {}", url);
 
         self.execute_with_retry("Get active pull requests", || async {
-            let response = self.client
-                .get(&url)
-                .header(header::AUTHORIZATION, &self.auth_header)
-                .send()
+            // Ask ADO to skip the body if the page hasn't changed since the last poll
+            let cached_etag = self.pr_list_cache.lock().unwrap().get(&skip).map(|cached| cached.etag.clone());
+            let mut request = self.client.get(&url);
+            if let Some(etag) = &cached_etag {
+                request = request.header(header::IF_NONE_MATCH, etag);
+            }
+
+            let response = self.send(request)
                 .await
                 .context("Failed to send request to Azure DevOps API")?;
 
+            if response.status() == StatusCode::NOT_MODIFIED
+                && let Some(cached) = self.pr_list_cache.lock().unwrap().get(&skip)
+            {
+                debug!("Pull request list unchanged since the last poll");
+                let pr_list: PullRequestList = serde_json::from_str(&cached.body)
+                    .context("Failed to parse cached pull request response")?;
+                return Ok(pr_list.value);
+            }
+
             if !response.status().is_success() {
-                let status = response.status();
-                let text = response.text().await.unwrap_or_else(|_| String::from("Unable to read response body"));
-                return Err(anyhow::anyhow!("API request failed with status {}: {}", status, text));
+                return Err(ApiError::from_response(response).await.into());
             }
 
-            let pr_list: PullRequestList = response.json().await
+            let etag = response.headers().get(header::ETAG)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string);
+            let body = response.text().await
+                .context("Failed to read pull request response")?;
+            let pr_list: PullRequestList = serde_json::from_str(&body)
                 .context("Failed to parse pull request response")?;
 
+            let mut cache = self.pr_list_cache.lock().unwrap();
+            match etag {
+                Some(etag) => cache.insert(skip, CachedResponse { etag, body }),
+                None => cache.remove(&skip),
+            };
+
             Ok(pr_list.value)
         }).await
     }
 
-    /// Approve a pull request
-    pub async fn approve_pull_request(&self, pull_request: &PullRequest, reviewer_id: &str) -> Result<()> {
+    /// Approve a pull request; `required` makes the bot a required reviewer, and one that already is stays required
+    pub async fn approve_pull_request(&self, pull_request: &PullRequest, reviewer_id: &str, required: bool) -> Result<()> {
+        self.vote_on_pull_request(pull_request, reviewer_id, VOTE_APPROVE, "Auto-approved by FastPRReviewer", required).await?;
+
+        info!(pr_id = pull_request.pull_request_id, vote = VOTE_APPROVE, "Successfully approved PR #{}", pull_request.pull_request_id);
+        Ok(())
+    }
+
+    /// Cast a vote on a pull request; `required` makes the bot a required reviewer, and one that already is stays required
+    pub async fn vote_on_pull_request(&self, pull_request: &PullRequest, reviewer_id: &str, vote: i32, comment: &str, required: bool) -> Result<()> {
         // Submit the vote using the provided reviewer ID
         let vote_url = format!(
             "{}/_apis/git/repositories/{}/pullRequests/{}/reviewers/{}?api-version={}",
@@ -170,78 +446,73 @@ impl AzureDevOpsClient {
             reviewer_id, API_VERSION
         );
 
-        debug!("Approving pull request #{} in repository {}", pull_request.pull_request_id, pull_request.repository.name);
-        info!("Approval URL: {}", vote_url);
+        info!(pr_id = pull_request.pull_request_id, repo = %pull_request.repository.name, vote,
+            "Voting {} on pull request #{} in repository {}", vote, pull_request.pull_request_id, pull_request.repository.name);
+        debug!("Vote URL: {}", vote_url);
+
+        // A vote without isRequired makes the reviewer optional, which would demote a bot required by branch policy
+        let is_required = required || self.get_reviewer(pull_request, reviewer_id).await?
+            .is_some_and(|reviewer| reviewer.is_required);
 
-        // Vote values: 10 = approve, 5 = approve with suggestions, 0 = no vote, -5 = waiting for author, -10 = reject
         let review_request = ReviewRequest {
-            vote: 10,  // Approve
-            comment: "Auto-approved by FastPRReviewer".to_string(),
+            vote,
+            comment: redact::redact(comment),
+            is_required,
         };
 
-        self.execute_with_retry(&format!("Approve pull request #{}", pull_request.pull_request_id), || async {
-            let response = self.client
-                .put(&vote_url)
-                .header(header::AUTHORIZATION, &self.auth_header)
-                .json(&review_request)
-                .send()
+        self.execute_with_retry(&format!("Vote on pull request #{}", pull_request.pull_request_id), || async {
+            let response = self.send(self.client.put(&vote_url).json(&review_request))
                 .await
-                .context("Failed to send approval request")?;
+                .context("Failed to send vote request")?;
 
             if !response.status().is_success() {
-                let status = response.status();
-                let text = response.text().await.unwrap_or_else(|_| String::from("Unable to read response body"));
-                return Err(anyhow::anyhow!("API request failed with status {}: {}", status, text));
+                return Err(ApiError::from_response(response).await.into());
             }
 
-            info!("Successfully approved PR #{}", pull_request.pull_request_id);
             Ok(())
         }).await
     }
 
     /// Check if we've already approved this PR
     pub async fn check_approval_status(&self, pull_request: &PullRequest, reviewer_id: &str) -> Result<bool> {
-        // Check if this reviewer ID has already approved the PR
+        debug!("Checking approval status for PR #{} in repository {}", 
+            pull_request.pull_request_id, pull_request.repository.name);
+
+        // Check if the vote is positive (approval)
+        if self.get_reviewer(pull_request, reviewer_id).await?.is_some_and(|reviewer| reviewer.vote > 0) {
+            debug!("PR #{} is already approved by the reviewer", pull_request.pull_request_id);
+            return Ok(true);
+        }
+
+        debug!("PR #{} is not approved by the reviewer", pull_request.pull_request_id);
+        Ok(false)
+    }
+
+    /// Get one reviewer of a pull request, or None if they aren't a reviewer
+    pub async fn get_reviewer(&self, pull_request: &PullRequest, reviewer_id: &str) -> Result<Option<Reviewer>> {
         let url = format!(
             "{}/_apis/git/repositories/{}/pullRequests/{}/reviewers/{}?api-version={}",
             self.base_url, pull_request.repository.id, pull_request.pull_request_id, reviewer_id, API_VERSION
         );
 
-        debug!("Checking approval status for PR #{} in repository {}", 
-            pull_request.pull_request_id, pull_request.repository.name);
-        info!("Check status URL: {}", url);
+        debug!("Reviewer URL: {}", url);
 
-        self.execute_with_retry(&format!("Check approval status for PR #{}", pull_request.pull_request_id), || async {
-            let response = self.client
-                .get(&url)
-                .header(header::AUTHORIZATION, &self.auth_header)
-                .send()
+        self.execute_with_retry(&format!("Get reviewer of PR #{}", pull_request.pull_request_id), || async {
+            let response = self.send(self.client.get(&url))
                 .await
-                .context("Failed to send request to check approval status")?;
+                .context("Failed to send request to get reviewer")?;
             
             if response.status() == StatusCode::NOT_FOUND {
                 // If the reviewer doesn't exist, it means we haven't reviewed yet
-                return Ok(false);
+                return Ok(None);
             } else if !response.status().is_success() {
-                let status = response.status();
-                let text = response.text().await.unwrap_or_else(|_| String::from("Unable to read response body"));
-                return Err(anyhow::anyhow!("API request failed with status {}: {}", status, text));
+                return Err(ApiError::from_response(response).await.into());
             }
 
-            // Parse the individual reviewer response
-            let reviewer: serde_json::Value = response.json().await
+            let reviewer: Reviewer = response.json().await
                 .context("Failed to parse reviewer response")?;
-            
-            // Check if the vote is positive (approval)
-            if let Some(vote) = reviewer["vote"].as_i64() {
-                if vote > 0 {
-                    debug!("PR #{} is already approved by the reviewer", pull_request.pull_request_id);
-                    return Ok(true);
-                }
-            }
 
-            debug!("PR #{} is not approved by the reviewer", pull_request.pull_request_id);
-            Ok(false)
+            Ok(Some(reviewer))
         }).await
     }
 
@@ -253,20 +524,15 @@ impl AzureDevOpsClient {
         );
 
         debug!("Fetching reviewers for PR #{} in repository {}", pull_request.pull_request_id, pull_request.repository.name);
-        info!("Reviewers URL: {}", url);
+        debug!("Reviewers URL: {}", url);
 
         self.execute_with_retry(&format!("Get reviewers for PR #{}", pull_request.pull_request_id), || async {
-            let response = self.client
-                .get(&url)
-                .header(header::AUTHORIZATION, &self.auth_header)
-                .send()
+            let response = self.send(self.client.get(&url))
                 .await
                 .context("Failed to send request to get reviewers")?;
 
             if !response.status().is_success() {
-                let status = response.status();
-                let text = response.text().await.unwrap_or_else(|_| String::from("Unable to read response body"));
-                return Err(anyhow::anyhow!("API request failed with status {}: {}", status, text));
+                return Err(ApiError::from_response(response).await.into());
             }
 
             let reviewer_list: ReviewerList = response.json().await
@@ -276,6 +542,113 @@ impl AzureDevOpsClient {
         }).await
     }
 
+    /// Get the files changed by the latest iteration (push) of a pull request, compared to its target branch
+    pub async fn get_changed_files(&self, pull_request: &PullRequest) -> Result<Vec<FileChange>> {
+        let iterations_url = format!(
+            "{}/_apis/git/repositories/{}/pullRequests/{}/iterations?api-version={}",
+            self.base_url, pull_request.repository.id, pull_request.pull_request_id, API_VERSION
+        );
+
+        debug!("Fetching changed files for PR #{}", pull_request.pull_request_id);
+        debug!("Iterations URL: {}", iterations_url);
+
+        let latest = self.execute_with_retry(&format!("Get iterations for PR #{}", pull_request.pull_request_id), || async {
+            let response = self.send(self.client.get(&iterations_url))
+                .await
+                .context("Failed to send request to get iterations")?;
+
+            if !response.status().is_success() {
+                return Err(ApiError::from_response(response).await.into());
+            }
+
+            let iterations: IterationList = response.json().await
+                .context("Failed to parse iterations response")?;
+
+            Ok(iterations.value.iter().map(|iteration| iteration.id).max())
+        }).await?;
+
+        let Some(latest) = latest else {
+            return Ok(Vec::new());
+        };
+
+        let changes_url = format!(
+            "{}/_apis/git/repositories/{}/pullRequests/{}/iterations/{}/changes?$compareTo=0&api-version={}",
+            self.base_url, pull_request.repository.id, pull_request.pull_request_id, latest, API_VERSION
+        );
+        debug!("Changes URL: {}", changes_url);
+
+        self.execute_with_retry(&format!("Get changes for PR #{}", pull_request.pull_request_id), || async {
+            let response = self.send(self.client.get(&changes_url))
+                .await
+                .context("Failed to send request to get iteration changes")?;
+
+            if !response.status().is_success() {
+                return Err(ApiError::from_response(response).await.into());
+            }
+
+            let changes: IterationChanges = response.json().await
+                .context("Failed to parse iteration changes response")?;
+
+            Ok(changes.change_entries.into_iter()
+                .filter_map(|entry| entry.item.path.map(|path| FileChange { path, change_type: entry.change_type }))
+                .collect())
+        }).await
+    }
+
+    /// Add a reviewer to a pull request without casting a vote
+    pub async fn add_reviewer(&self, pull_request: &PullRequest, reviewer_id: &str, is_required: bool) -> Result<Reviewer> {
+        let url = format!(
+            "{}/_apis/git/repositories/{}/pullRequests/{}/reviewers/{}?api-version={}",
+            self.base_url, pull_request.repository.id, pull_request.pull_request_id, reviewer_id, API_VERSION
+        );
+
+        debug!("Adding reviewer {} to PR #{} (required: {})", reviewer_id, pull_request.pull_request_id, is_required);
+        debug!("Add reviewer URL: {}", url);
+
+        let add_request = AddReviewerRequest {
+            vote: 0,  // No vote
+            is_required,
+        };
+
+        self.execute_with_retry(&format!("Add reviewer to PR #{}", pull_request.pull_request_id), || async {
+            let response = self.send(self.client.put(&url).json(&add_request))
+                .await
+                .context("Failed to send request to add reviewer")?;
+
+            if !response.status().is_success() {
+                return Err(ApiError::from_response(response).await.into());
+            }
+
+            let reviewer: Reviewer = response.json().await
+                .context("Failed to parse added reviewer response")?;
+
+            Ok(reviewer)
+        }).await
+    }
+
+    /// Remove a reviewer from a pull request
+    pub async fn remove_reviewer(&self, pull_request: &PullRequest, reviewer_id: &str) -> Result<()> {
+        let url = format!(
+            "{}/_apis/git/repositories/{}/pullRequests/{}/reviewers/{}?api-version={}",
+            self.base_url, pull_request.repository.id, pull_request.pull_request_id, reviewer_id, API_VERSION
+        );
+
+        debug!("Removing reviewer {} from PR #{}", reviewer_id, pull_request.pull_request_id);
+        debug!("Remove reviewer URL: {}", url);
+
+        self.execute_with_retry(&format!("Remove reviewer from PR #{}", pull_request.pull_request_id), || async {
+            let response = self.send(self.client.delete(&url))
+                .await
+                .context("Failed to send request to remove reviewer")?;
+
+            if !response.status().is_success() {
+                return Err(ApiError::from_response(response).await.into());
+            }
+
+            Ok(())
+        }).await
+    }
+
     /// Get a specific pull request by ID
     pub async fn get_pull_request_by_id(&self, pull_request_id: i32) -> Result<PullRequest> {
         // Because we don't know the repository ID in advance, we need a URL that doesn't require it
@@ -285,20 +658,15 @@ impl AzureDevOpsClient {
         );
 
         debug!("Fetching pull request #{}", pull_request_id);
-        info!("Request URL: {}", url);
+        debug!("Request URL: {}", url);
 
         self.execute_with_retry(&format!("Get pull request #{}", pull_request_id), || async {
-            let response = self.client
-                .get(&url)
-                .header(header::AUTHORIZATION, &self.auth_header)
-                .send()
+            let response = self.send(self.client.get(&url))
                 .await
                 .context("Failed to send request to get pull request")?;
 
             if !response.status().is_success() {
-                let status = response.status();
-                let text = response.text().await.unwrap_or_else(|_| String::from("Unable to read response body"));
-                return Err(anyhow::anyhow!("API request failed with status {}: {}", status, text));
+                return Err(ApiError::from_response(response).await.into());
             }
 
             let pull_request: PullRequest = response.json().await
@@ -307,4 +675,767 @@ impl AzureDevOpsClient {
             Ok(pull_request)
         }).await
     }
-}
\ No newline at end of file
+
+    /// Update a pull request (auto-complete, completion options, status, ...)
+    pub async fn update_pull_request(&self, pull_request: &PullRequest, update: &PullRequestUpdate) -> Result<PullRequest> {
+        let url = format!(
+            "{}/_apis/git/repositories/{}/pullRequests/{}?api-version={}",
+            self.base_url, pull_request.repository.id, pull_request.pull_request_id, API_VERSION
+        );
+
+        debug!("Updating pull request #{} in repository {}", pull_request.pull_request_id, pull_request.repository.name);
+        debug!("Update URL: {}", url);
+
+        self.execute_with_retry(&format!("Update pull request #{}", pull_request.pull_request_id), || async {
+            let response = self.send(self.client.patch(&url).json(update))
+                .await
+                .context("Failed to send pull request update")?;
+
+            if !response.status().is_success() {
+                return Err(ApiError::from_response(response).await.into());
+            }
+
+            let pull_request: PullRequest = response.json().await
+                .context("Failed to parse updated pull request response")?;
+
+            Ok(pull_request)
+        }).await
+    }
+
+    /// Get the policy evaluations for a pull request
+    pub async fn get_policy_evaluations(&self, pull_request: &PullRequest) -> Result<Vec<PolicyEvaluation>> {
+        let project_id = pull_request.repository.project.as_ref()
+            .map(|project| project.id.as_str())
+            .context("Pull request response did not include the project ID")?;
+
+        let url = format!(
+            "{}/_apis/policy/evaluations?artifactId=vstfs:///CodeReview/CodeReviewId/{}/{}&api-version={}",
+            self.base_url, project_id, pull_request.pull_request_id, POLICY_API_VERSION
+        );
+
+        debug!("Fetching policy evaluations for PR #{}", pull_request.pull_request_id);
+        debug!("Policy evaluations URL: {}", url);
+
+        self.execute_with_retry(&format!("Get policy evaluations for PR #{}", pull_request.pull_request_id), || async {
+            let response = self.send(self.client.get(&url))
+                .await
+                .context("Failed to send request to get policy evaluations")?;
+
+            if !response.status().is_success() {
+                return Err(ApiError::from_response(response).await.into());
+            }
+
+            let evaluations: PolicyEvaluationList = response.json().await
+                .context("Failed to parse policy evaluations response")?;
+
+            Ok(evaluations.value)
+        }).await
+    }
+
+    /// Get the work items linked to a pull request
+    pub async fn get_pull_request_work_items(&self, pull_request: &PullRequest) -> Result<Vec<ResourceRef>> {
+        let url = format!(
+            "{}/_apis/git/repositories/{}/pullRequests/{}/workitems?api-version={}",
+            self.base_url, pull_request.repository.id, pull_request.pull_request_id, API_VERSION
+        );
+
+        debug!("Fetching work items linked to PR #{}", pull_request.pull_request_id);
+        debug!("Work items URL: {}", url);
+
+        self.execute_with_retry(&format!("Get work items for PR #{}", pull_request.pull_request_id), || async {
+            let response = self.send(self.client.get(&url))
+                .await
+                .context("Failed to send request to get pull request work items")?;
+
+            if !response.status().is_success() {
+                return Err(ApiError::from_response(response).await.into());
+            }
+
+            let work_items: ResourceRefList = response.json().await
+                .context("Failed to parse pull request work items response")?;
+
+            Ok(work_items.value)
+        }).await
+    }
+
+    /// Get the type and state of work items from the work item tracking API, in batches of the
+    /// most IDs one request may ask for
+    pub async fn get_work_items(&self, ids: &[i32]) -> Result<Vec<WorkItem>> {
+        // No IDs means no requests; the API rejects an empty ID list
+        let mut work_items = Vec::with_capacity(ids.len());
+        for batch in ids.chunks(MAX_WORK_ITEMS_PER_REQUEST) {
+            work_items.extend(self.get_work_item_batch(batch).await?);
+        }
+        Ok(work_items)
+    }
+
+    async fn get_work_item_batch(&self, ids: &[i32]) -> Result<Vec<WorkItem>> {
+        let ids = ids.iter().map(|id| id.to_string()).collect::<Vec<_>>().join(",");
+        let url = format!(
+            "{}/_apis/wit/workitems?ids={}&fields=System.WorkItemType,System.State&api-version={}",
+            self.base_url, ids, API_VERSION
+        );
+
+        debug!("Fetching work items {}", ids);
+        debug!("Work item tracking URL: {}", url);
+
+        self.execute_with_retry("Get work items", || async {
+            let response = self.send(self.client.get(&url))
+                .await
+                .context("Failed to send request to get work items")?;
+
+            if !response.status().is_success() {
+                return Err(ApiError::from_response(response).await.into());
+            }
+
+            let work_items: WorkItemList = response.json().await
+                .context("Failed to parse work items response")?;
+
+            Ok(work_items.value)
+        }).await
+    }
+
+    /// Get all comment threads on a pull request
+    pub async fn get_threads(&self, pull_request: &PullRequest) -> Result<Vec<CommentThread>> {
+        let url = format!(
+            "{}/_apis/git/repositories/{}/pullRequests/{}/threads?api-version={}",
+            self.base_url, pull_request.repository.id, pull_request.pull_request_id, API_VERSION
+        );
+
+        debug!("Fetching comment threads for PR #{}", pull_request.pull_request_id);
+        debug!("Threads URL: {}", url);
+
+        self.execute_with_retry(&format!("Get threads for PR #{}", pull_request.pull_request_id), || async {
+            let response = self.send(self.client.get(&url))
+                .await
+                .context("Failed to send request to get comment threads")?;
+
+            if !response.status().is_success() {
+                return Err(ApiError::from_response(response).await.into());
+            }
+
+            let threads: CommentThreadList = response.json().await
+                .context("Failed to parse comment threads response")?;
+
+            Ok(threads.value)
+        }).await
+    }
+
+    /// Start a new comment thread on a pull request
+    pub async fn create_thread(&self, pull_request: &PullRequest, content: &str) -> Result<()> {
+        let url = format!(
+            "{}/_apis/git/repositories/{}/pullRequests/{}/threads?api-version={}",
+            self.base_url, pull_request.repository.id, pull_request.pull_request_id, API_VERSION
+        );
+
+        debug!("Creating comment thread on PR #{}", pull_request.pull_request_id);
+        debug!("Threads URL: {}", url);
+
+        let thread_request = CommentThreadRequest::new(&redact::redact(content));
+
+        self.execute_with_retry(&format!("Create thread on PR #{}", pull_request.pull_request_id), || async {
+            let response = self.send(self.client.post(&url).json(&thread_request))
+                .await
+                .context("Failed to send request to create comment thread")?;
+
+            if !response.status().is_success() {
+                return Err(ApiError::from_response(response).await.into());
+            }
+
+            Ok(())
+        }).await
+    }
+
+    /// Reply to a comment in an existing thread
+    pub async fn reply_to_thread(&self, pull_request: &PullRequest, thread_id: i32, parent_comment_id: i32, content: &str) -> Result<()> {
+        let url = format!(
+            "{}/_apis/git/repositories/{}/pullRequests/{}/threads/{}/comments?api-version={}",
+            self.base_url, pull_request.repository.id, pull_request.pull_request_id, thread_id, API_VERSION
+        );
+
+        debug!("Replying in thread {} on PR #{}", thread_id, pull_request.pull_request_id);
+        debug!("Reply URL: {}", url);
+
+        let reply_request = CommentRequest {
+            parent_comment_id,
+            content: redact::redact(content),
+            comment_type: "text".to_string(),
+        };
+
+        self.execute_with_retry(&format!("Reply in thread {} on PR #{}", thread_id, pull_request.pull_request_id), || async {
+            let response = self.send(self.client.post(&url).json(&reply_request))
+                .await
+                .context("Failed to send request to reply in thread")?;
+
+            if !response.status().is_success() {
+                return Err(ApiError::from_response(response).await.into());
+            }
+
+            Ok(())
+        }).await
+    }
+
+    /// Get the identity the PAT authenticates as
+    pub async fn get_connection_data(&self) -> Result<ConnectionData> {
+        let url = format!("{}/_apis/connectionData", self.organization_url);
+
+        debug!("Fetching connection data");
+
+        self.execute_with_retry("Get connection data", || async {
+            let response = self.send(self.client.get(&url))
+                .await
+                .context("Failed to send request to get connection data")?;
+
+            if !response.status().is_success() {
+                return Err(ApiError::from_response(response).await.into());
+            }
+
+            // An invalid PAT gets a sign-in page instead of JSON
+            let connection: ConnectionData = response.json().await
+                .context("Failed to parse connection data response; the PAT may be invalid")?;
+
+            Ok(connection)
+        }).await
+    }
+
+    /// Get the configured project
+    pub async fn get_project(&self) -> Result<TeamProject> {
+        // Without a project the URL would list every project of the organization instead
+        if self.project.is_empty() {
+            return Err(anyhow::anyhow!("No project configured"));
+        }
+        let url = format!("{}/_apis/projects/{}?api-version={}", self.organization_url, self.project, API_VERSION);
+
+        debug!("Fetching project {}", self.project);
+
+        self.execute_with_retry("Get project", || async {
+            let response = self.send(self.client.get(&url))
+                .await
+                .context("Failed to send request to get project")?;
+
+            if !response.status().is_success() {
+                return Err(ApiError::from_response(response).await.into());
+            }
+
+            let project: TeamProject = response.json().await
+                .context("Failed to parse project response")?;
+
+            Ok(project)
+        }).await
+    }
+
+    /// List the Git repositories of the project
+    pub async fn get_repositories(&self) -> Result<Vec<Repository>> {
+        let url = format!("{}/_apis/git/repositories?api-version={}", self.base_url, API_VERSION);
+
+        debug!("Fetching repositories");
+
+        self.execute_with_retry("Get repositories", || async {
+            let response = self.send(self.client.get(&url))
+                .await
+                .context("Failed to send request to get repositories")?;
+
+            if !response.status().is_success() {
+                return Err(ApiError::from_response(response).await.into());
+            }
+
+            let repositories: RepositoryList = response.json().await
+                .context("Failed to parse repositories response")?;
+
+            Ok(repositories.value)
+        }).await
+    }
+
+    /// Check that the PAT may vote on PRs in a repository, without changing anything: asks for the
+    /// caller's "Contribute to pull requests" permission, which is refused with 401/403 when the PAT
+    /// lacks the Code scope
+    pub async fn can_vote(&self, project_id: &str, repository_id: &str) -> Result<bool> {
+        let url = format!(
+            "{}/_apis/permissions/{}/{}?tokens=repoV2/{}/{}&alwaysAllow=false&api-version={}",
+            self.organization_url, GIT_SECURITY_NAMESPACE, PULL_REQUEST_CONTRIBUTE, project_id, repository_id, API_VERSION
+        );
+
+        self.execute_with_retry("Check vote permission", || async {
+            let response = self.send(self.client.get(&url))
+                .await
+                .context("Failed to send request to check vote permission")?;
+
+            match response.status() {
+                StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => Ok(false),
+                status if status.is_success() => {
+                    let permissions: PermissionCheck = response.json().await
+                        .context("Failed to parse permission check response")?;
+                    Ok(!permissions.value.is_empty() && permissions.value.iter().all(|allowed| *allowed))
+                }
+                _ => Err(ApiError::from_response(response).await.into()),
+            }
+        }).await
+    }
+
+    /// Find identities whose display name or account matches the search term
+    pub async fn search_identities(&self, name: &str) -> Result<Vec<Identity>> {
+        let url = format!("{}/_apis/identities", self.identity_url);
+
+        debug!("Searching identities for '{}'", name);
+
+        self.execute_with_retry(&format!("Search identities for '{}'", name), || async {
+            let request = self.client.get(&url)
+                .query(&[("searchFilter", "General"), ("filterValue", name), ("api-version", API_VERSION)]);
+            let response = self.send(request)
+                .await
+                .context("Failed to send request to search identities")?;
+
+            if !response.status().is_success() {
+                return Err(ApiError::from_response(response).await.into());
+            }
+
+            let identities: IdentityList = response.json().await
+                .context("Failed to parse identities response")?;
+
+            Ok(identities.value.into_iter().flatten().collect())
+        }).await
+    }
+
+    /// Look up an identity by ID, returning None if it doesn't exist
+    pub async fn get_identity(&self, id: &str) -> Result<Option<Identity>> {
+        let url = format!("{}/_apis/identities", self.identity_url);
+
+        self.execute_with_retry(&format!("Get identity {}", id), || async {
+            let request = self.client.get(&url)
+                .query(&[("identityIds", id), ("api-version", API_VERSION)]);
+            let response = self.send(request)
+                .await
+                .context("Failed to send request to get identity")?;
+
+            if !response.status().is_success() {
+                return Err(ApiError::from_response(response).await.into());
+            }
+
+            let identities: IdentityList = response.json().await
+                .context("Failed to parse identity response")?;
+
+            Ok(identities.value.into_iter().flatten().next())
+        }).await
+    }
+}
+
+/// Where identities live for an organization given as a full URL: Azure DevOps Services keeps them
+/// on a separate host, Azure DevOps Server and mock servers serve them from the same URL
+fn identity_url_for(organization_url: &str) -> String {
+    if let Ok(mut url) = Url::parse(organization_url)
+        && url.host_str() == Some("dev.azure.com")
+        && url.set_host(Some("vssps.dev.azure.com")).is_ok()
+    {
+        return url.as_str().trim_end_matches('/').to_string();
+    }
+    organization_url.to_string()
+}
+
+/// A request as written to a cassette; its headers, including the auth header, are left out
+fn recorded_request(request: &reqwest::Request) -> RecordedRequest {
+    RecordedRequest {
+        method: request.method().to_string(),
+        url: request.url().to_string(),
+        body: request.body()
+            .and_then(|body| body.as_bytes())
+            .map(|body| String::from_utf8_lossy(body).into_owned()),
+    }
+}
+
+fn basic_auth_header(pat: &str) -> String {
+    format!("Basic {}", general_purpose::STANDARD.encode(format!(":{}", pat)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decisions::{Decision, DecisionLog};
+    use std::io::Write;
+    use std::sync::Arc;
+    use std::time::Instant;
+    use wiremock::matchers::{header, method, path, path_regex};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn empty_pr_list() -> serde_json::Value {
+        serde_json::json!({ "value": [] })
+    }
+
+    #[tokio::test]
+    async fn rate_limiter_backs_off_on_throttling_headers() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/_apis/git/pullrequests"))
+            .respond_with(ResponseTemplate::new(200)
+                .insert_header("X-RateLimit-Resource", "Core")
+                .insert_header("X-RateLimit-Limit", "200")
+                .insert_header("X-RateLimit-Remaining", "10")
+                .insert_header("Retry-After", "1")
+                .set_body_json(empty_pr_list()))
+            .mount(&server)
+            .await;
+
+        let client = AzureDevOpsClient::with_base_url(&server.uri(), "pat");
+        client.get_active_pull_requests().await.unwrap();
+
+        let status = client.rate_limit_status();
+        assert_eq!(status.resource.as_deref(), Some("Core"));
+        assert_eq!(status.remaining, Some(10));
+        assert!(status.paused_for.is_some());
+        assert!(status.requests_per_second < DEFAULT_REQUESTS_PER_SECOND);
+
+        // The next request has to wait out the Retry-After pause
+        let started = Instant::now();
+        client.get_active_pull_requests().await.unwrap();
+        assert!(started.elapsed() >= Duration::from_millis(900));
+    }
+
+    #[tokio::test]
+    async fn throttled_request_is_retried_after_pause() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/_apis/git/pullrequests"))
+            .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "1"))
+            .up_to_n_times(1)
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/_apis/git/pullrequests"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(empty_pr_list()))
+            .mount(&server)
+            .await;
+
+        let client = AzureDevOpsClient::with_base_url(&server.uri(), "pat");
+        let started = Instant::now();
+        let prs = client.get_active_pull_requests().await.unwrap();
+
+        assert!(prs.is_empty());
+        assert!(started.elapsed() >= Duration::from_millis(900));
+        assert_eq!(server.received_requests().await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn unchanged_pull_request_list_is_served_from_cache() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/_apis/git/pullrequests"))
+            .and(header("If-None-Match", "\"v1\""))
+            .respond_with(ResponseTemplate::new(304))
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/_apis/git/pullrequests"))
+            .respond_with(ResponseTemplate::new(200)
+                .insert_header("ETag", "\"v1\"")
+                .set_body_json(empty_pr_list()))
+            .mount(&server)
+            .await;
+
+        let client = AzureDevOpsClient::with_base_url(&server.uri(), "pat");
+        client.get_active_pull_requests().await.unwrap();
+        let prs = client.get_active_pull_requests().await.unwrap();
+
+        assert!(prs.is_empty());
+        let requests = server.received_requests().await.unwrap();
+        assert_eq!(requests.len(), 2);
+        assert!(!requests[0].headers.contains_key("If-None-Match"));
+        assert_eq!(requests[1].headers.get("If-None-Match").unwrap(), "\"v1\"");
+    }
+
+    #[tokio::test]
+    async fn every_page_of_active_pull_requests_is_listed() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/_apis/git/pullrequests"))
+            .respond_with(|request: &wiremock::Request| {
+                let skip: usize = request.url.query_pairs()
+                    .find(|(name, _)| name == "$skip")
+                    .map(|(_, skip)| skip.parse().unwrap())
+                    .unwrap();
+                // 250 PRs, with one opened while the second page is fetched
+                let first = if skip == 0 { 0 } else { skip - 1 };
+                let value: Vec<_> = (first..250.min(first + PR_PAGE_SIZE))
+                    .map(|n| serde_json::json!({
+                        "pullRequestId": 1000 - n as i32,
+                        "title": "Test",
+                        "createdBy": { "id": "author", "displayName": "Author" },
+                        "creationDate": "2026-01-01T00:00:00Z",
+                        "repository": { "id": "repo-id", "name": "repo" },
+                    }))
+                    .collect();
+                ResponseTemplate::new(200).set_body_json(serde_json::json!({ "value": value }))
+            })
+            .mount(&server)
+            .await;
+
+        let client = AzureDevOpsClient::with_base_url(&server.uri(), "pat");
+        let prs = client.get_active_pull_requests().await.unwrap();
+
+        assert_eq!(prs.iter().map(|pr| pr.pull_request_id).collect::<Vec<_>>(), (751..=1000).rev().collect::<Vec<_>>());
+        assert_eq!(server.received_requests().await.unwrap().len(), 3);
+    }
+
+    /// Log output shared between the test and the subscriber
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn token_never_appears_in_any_output() {
+        let token = "known-secret-token-0123456789";
+        let auth_header = basic_auth_header(token);
+
+        // A server that echoes the credentials back in every error
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/_apis/git/pullrequests"))
+            .respond_with(ResponseTemplate::new(400)
+                .set_body_string(format!("bad request from {} with {}", token, auth_header)))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path_regex("/threads$"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+
+        let logs = SharedBuffer::default();
+        let writer = logs.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_max_level(tracing::Level::DEBUG)
+            .with_writer(move || redact::Redacting(writer.clone()))
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let client = AzureDevOpsClient::with_base_url(&server.uri(), token);
+        let error = client.get_active_pull_requests().await.unwrap_err();
+        tracing::error!("❌ Request failed: {:?} (token {}, header {})", error, token, auth_header);
+
+        // Error text ends up in the decision log and in comments posted on the PR
+        let pr: PullRequest = serde_json::from_value(serde_json::json!({
+            "pullRequestId": 1,
+            "title": "Test",
+            "createdBy": { "id": "author", "displayName": "Author" },
+            "creationDate": "2026-01-01T00:00:00Z",
+            "repository": { "id": "repo-id", "name": "repo" },
+        })).unwrap();
+        let decision_file = std::env::temp_dir().join(format!("fpr-redact-test-{}.jsonl", std::process::id()));
+        let decisions = DecisionLog::new(Some(decision_file.clone()));
+        decisions.record(Decision::new(&pr, None, "approve", "failed", format!("{} {}", error, token)));
+        client.create_thread(&pr, &format!("Failed with {}", token)).await.unwrap();
+
+        let log_output = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+        let outputs = [
+            ("error", format!("{:?}", error)),
+            ("logs", log_output),
+            ("decision log", std::fs::read_to_string(&decision_file).unwrap()),
+            ("recent decisions", format!("{:?}", decisions.recent())),
+            ("comment payload", server.received_requests().await.unwrap().iter()
+                .filter(|request| request.method.as_str() == "POST")
+                .map(|request| String::from_utf8_lossy(&request.body).into_owned())
+                .collect()),
+        ];
+        let _ = std::fs::remove_file(&decision_file);
+
+        for (name, output) in outputs {
+            assert!(output.contains("[REDACTED]"), "{} was not redacted: {}", name, output);
+            assert!(!output.contains(token), "token leaked into {}: {}", name, output);
+            assert!(!output.contains(&auth_header[6..]), "auth header leaked into {}: {}", name, output);
+        }
+    }
+
+    #[tokio::test]
+    async fn vote_permission_is_checked_without_writing() {
+        use wiremock::matchers::query_param;
+
+        let server = MockServer::start().await;
+        let permission_path = format!("/_apis/permissions/{}/{}", GIT_SECURITY_NAMESPACE, PULL_REQUEST_CONTRIBUTE);
+        for (repository, response) in [
+            ("allowed", ResponseTemplate::new(200).set_body_json(serde_json::json!({ "count": 1, "value": [true] }))),
+            ("denied", ResponseTemplate::new(200).set_body_json(serde_json::json!({ "count": 1, "value": [false] }))),
+            ("unauthorized", ResponseTemplate::new(401)),
+            ("forbidden", ResponseTemplate::new(403)),
+            ("missing", ResponseTemplate::new(404)),
+        ] {
+            Mock::given(method("GET"))
+                .and(path(permission_path.as_str()))
+                .and(query_param("tokens", format!("repoV2/project/{}", repository)))
+                .respond_with(response)
+                .mount(&server)
+                .await;
+        }
+
+        let client = AzureDevOpsClient::with_base_url(&server.uri(), "pat");
+        assert!(client.can_vote("project", "allowed").await.unwrap());
+        assert!(!client.can_vote("project", "denied").await.unwrap());
+        assert!(!client.can_vote("project", "unauthorized").await.unwrap());
+        assert!(!client.can_vote("project", "forbidden").await.unwrap());
+        assert!(client.can_vote("project", "missing").await.is_err());
+
+        let requests = server.received_requests().await.unwrap();
+        assert!(requests.iter().all(|request| request.method == wiremock::http::Method::GET), "{:?}", requests);
+    }
+
+    #[tokio::test]
+    async fn work_items_are_fetched_in_batches() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/_apis/wit/workitems"))
+            .respond_with(|request: &wiremock::Request| {
+                let ids = request.url.query_pairs()
+                    .find(|(name, _)| name == "ids")
+                    .map(|(_, ids)| ids.split(',').map(|id| id.parse::<i32>().unwrap()).collect::<Vec<_>>())
+                    .unwrap();
+                let value: Vec<_> = ids.iter()
+                    .map(|id| serde_json::json!({ "id": id, "fields": { "System.WorkItemType": "Bug", "System.State": "Active" } }))
+                    .collect();
+                ResponseTemplate::new(200).set_body_json(serde_json::json!({ "count": value.len(), "value": value }))
+            })
+            .mount(&server)
+            .await;
+
+        let client = AzureDevOpsClient::with_base_url(&server.uri(), "pat");
+        assert!(client.get_work_items(&[]).await.unwrap().is_empty());
+        assert!(server.received_requests().await.unwrap().is_empty());
+
+        let ids: Vec<i32> = (1..=450).collect();
+        let work_items = client.get_work_items(&ids).await.unwrap();
+        assert_eq!(work_items.iter().map(|item| item.id).collect::<Vec<_>>(), ids);
+        assert_eq!(server.received_requests().await.unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn votes_keep_a_required_reviewer_required() {
+        let server = MockServer::start().await;
+        for (pr_id, reviewer) in [
+            (1, ResponseTemplate::new(200).set_body_json(serde_json::json!({ "id": "bot-id", "displayName": "Bot", "vote": 0, "isRequired": true }))),
+            (2, ResponseTemplate::new(200).set_body_json(serde_json::json!({ "id": "bot-id", "displayName": "Bot", "vote": 0 }))),
+            (3, ResponseTemplate::new(404)),
+        ] {
+            Mock::given(method("GET"))
+                .and(path(format!("/_apis/git/repositories/repo-id/pullRequests/{}/reviewers/bot-id", pr_id)))
+                .respond_with(reviewer)
+                .mount(&server)
+                .await;
+        }
+        Mock::given(method("PUT"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+
+        let client = AzureDevOpsClient::with_base_url(&server.uri(), "pat");
+        let pr = |pr_id: i32| -> PullRequest {
+            serde_json::from_value(serde_json::json!({
+                "pullRequestId": pr_id,
+                "title": "Test",
+                "createdBy": { "id": "author", "displayName": "Author" },
+                "creationDate": "2026-01-01T00:00:00Z",
+                "repository": { "id": "repo-id", "name": "repo" },
+            })).unwrap()
+        };
+        // Required by branch policy, optional, not a reviewer yet, and optional but a rule asks for required
+        client.approve_pull_request(&pr(1), "bot-id", false).await.unwrap();
+        client.vote_on_pull_request(&pr(2), "bot-id", VOTE_WAIT_FOR_AUTHOR, "Wait", false).await.unwrap();
+        client.approve_pull_request(&pr(3), "bot-id", false).await.unwrap();
+        client.approve_pull_request(&pr(2), "bot-id", true).await.unwrap();
+
+        let required: Vec<Option<bool>> = server.received_requests().await.unwrap().iter()
+            .filter(|request| request.method == wiremock::http::Method::PUT)
+            .map(|request| serde_json::from_slice::<serde_json::Value>(&request.body).unwrap()["isRequired"].as_bool())
+            .collect();
+        assert_eq!(required, [Some(true), None, None, Some(true)]);
+    }
+
+    #[test]
+    fn organization_forms_map_to_project_organization_and_identity_urls() {
+        let client = AzureDevOpsClient::new("contoso", "web", "pat");
+        assert_eq!(client.organization_url, "https://dev.azure.com/contoso");
+        assert_eq!(client.identity_url, "https://vssps.dev.azure.com/contoso");
+
+        let client = AzureDevOpsClient::new("https://dev.azure.com/contoso/", "web", "pat");
+        assert_eq!(client.base_url, "https://dev.azure.com/contoso/web");
+        assert_eq!(client.organization_url, "https://dev.azure.com/contoso");
+        assert_eq!(client.identity_url, "https://vssps.dev.azure.com/contoso");
+
+        // Azure DevOps Server and mock servers serve identities themselves
+        let client = AzureDevOpsClient::new("https://tfs.contoso.com/DefaultCollection", "web", "pat");
+        assert_eq!(client.base_url, "https://tfs.contoso.com/DefaultCollection/web");
+        assert_eq!(client.identity_url, "https://tfs.contoso.com/DefaultCollection");
+        let client = AzureDevOpsClient::new("tfs.contoso.com", "web", "pat");
+        assert_eq!(client.base_url, "https://tfs.contoso.com/web");
+        assert_eq!(client.organization_url, "https://tfs.contoso.com");
+        let client = AzureDevOpsClient::new("http://127.0.0.1:8080", "web", "pat");
+        assert_eq!(client.identity_url, "http://127.0.0.1:8080");
+
+        // A project URL names the project in its last segment
+        let client = AzureDevOpsClient::with_base_url("https://dev.azure.com/contoso/web/", "pat");
+        assert_eq!(client.base_url, "https://dev.azure.com/contoso/web");
+        assert_eq!(client.organization_url, "https://dev.azure.com/contoso");
+        assert_eq!(client.identity_url, "https://vssps.dev.azure.com/contoso");
+        assert_eq!(client.project, "web");
+        let client = AzureDevOpsClient::with_base_url("http://127.0.0.1:8080", "pat");
+        assert_eq!(client.organization_url, "http://127.0.0.1:8080");
+        assert_eq!(client.project, "");
+    }
+
+    #[tokio::test]
+    async fn project_is_fetched_by_name_and_never_listed() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/_apis/projects/web"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "id": "project-1", "name": "web" })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = AzureDevOpsClient::with_base_url(&format!("{}/web", server.uri()), "pat");
+        assert_eq!(client.get_project().await.unwrap().id, "project-1");
+
+        // Without a project there is nothing to fetch
+        let client = AzureDevOpsClient::with_base_url(&server.uri(), "pat");
+        assert!(client.get_project().await.unwrap_err().to_string().contains("No project"));
+    }
+
+    #[tokio::test]
+    async fn sign_in_page_is_an_auth_failure_that_rereads_the_pat() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/_apis/git/pullrequests"))
+            .and(header("Authorization", basic_auth_header("rotated-pat-0123").as_str()))
+            .respond_with(ResponseTemplate::new(200).set_body_json(empty_pr_list()))
+            .mount(&server)
+            .await;
+        // Any other PAT gets the sign-in page instead of a 401
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(203).set_body_string("<html>Sign in</html>"))
+            .mount(&server)
+            .await;
+
+        let pat_file = std::env::temp_dir().join(format!("fpr-sign-in-test-pat-{}", std::process::id()));
+        std::fs::write(&pat_file, "expired-pat-0123").unwrap();
+        let client = AzureDevOpsClient::with_base_url(&server.uri(), "expired-pat-0123")
+            .with_pat_source(PatSource::File(pat_file.clone()));
+
+        // The same PAT again: reported as a sign-in redirect rather than parsed, and not retried
+        let error = client.get_active_pull_requests().await.unwrap_err();
+        assert!(format!("{:#}", error).contains("sign-in page"), "{:#}", error);
+        assert_eq!(server.received_requests().await.unwrap().len(), 1);
+
+        // A rotated PAT is picked up and the request retried with it
+        std::fs::write(&pat_file, "rotated-pat-0123\n").unwrap();
+        assert!(client.get_active_pull_requests().await.unwrap().is_empty());
+        assert_eq!(server.received_requests().await.unwrap().len(), 3);
+        std::fs::remove_file(&pat_file).ok();
+    }
+}
@@ -0,0 +1,215 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use tracing::warn;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::ado_client::AzureDevOpsClient;
+use crate::models::PullRequest;
+
+// Reviewer vote meaning the PR was rejected
+const REJECTED_VOTE: i32 = -10;
+
+/// Result of checking a scheduled approval again when its timer fires
+pub enum Revalidation {
+    /// Nothing changed; go ahead and approve
+    Ready(PullRequest),
+    /// New commits were pushed since the approval was scheduled
+    PushedTo(PullRequest),
+    /// The PR was abandoned, completed or rejected in the meantime
+    Cancelled(PullRequest, String),
+}
+
+/// An approval waiting for its timer to fire
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ScheduledApproval {
+    pub pr_id: i32,
+    pub repository: String,
+    pub author: String,
+    pub title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rule: Option<String>,
+    pub scheduled_at: DateTime<Utc>,
+    pub due_at: DateTime<Utc>,
+    /// Source commit when the approval was scheduled; a different one means the PR was pushed to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_commit: Option<String>,
+}
+
+impl ScheduledApproval {
+    pub fn new(pr: &PullRequest, rule: Option<&str>, due_at: DateTime<Utc>) -> Self {
+        Self {
+            pr_id: pr.pull_request_id,
+            repository: pr.repository.name.clone(),
+            author: pr.created_by.display_name.clone(),
+            title: pr.title.clone(),
+            rule: rule.map(str::to_string),
+            scheduled_at: Utc::now(),
+            due_at,
+            source_commit: pr.last_merge_source_commit.as_ref().map(|commit| commit.commit_id.clone()),
+        }
+    }
+}
+
+/// Approvals scheduled for later, saved to disk on every change so they survive restarts
+pub struct ApprovalQueue {
+    path: PathBuf,
+    entries: Vec<ScheduledApproval>,
+    /// Changed since the last successful save
+    dirty: bool,
+}
+
+impl ApprovalQueue {
+    /// Load the queue from disk, starting empty if the file doesn't exist yet
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let entries = if path.exists() {
+            let queue_str = fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read approval queue: {:?}", path))?;
+            serde_json::from_str(&queue_str)
+                .with_context(|| format!("Failed to parse approval queue: {:?}", path))?
+        } else {
+            Vec::new()
+        };
+
+        Ok(Self { path, entries, dirty: false })
+    }
+
+    /// Schedule an approval, replacing any earlier one for the same PR
+    pub fn schedule(&mut self, approval: ScheduledApproval) {
+        self.entries.retain(|entry| entry.pr_id != approval.pr_id);
+        self.entries.push(approval);
+        self.entries.sort_by_key(|entry| entry.due_at);
+        self.dirty = true;
+        self.save();
+    }
+
+    /// Every approval whose timer has fired; they stay queued until `finish` is called, so a crash
+    /// before they are handled doesn't lose them
+    pub fn due(&self, now: DateTime<Utc>) -> Vec<ScheduledApproval> {
+        self.entries.iter().filter(|entry| entry.due_at <= now).cloned().collect()
+    }
+
+    /// Drop a due approval once it was approved or cancelled; a newer approval scheduled for
+    /// the same PR in the meantime is kept
+    pub fn finish(&mut self, approval: &ScheduledApproval) {
+        let before = self.entries.len();
+        self.entries.retain(|entry| entry.pr_id != approval.pr_id || entry.scheduled_at != approval.scheduled_at);
+        if self.entries.len() != before {
+            self.dirty = true;
+            self.save();
+        }
+    }
+
+    /// Cancel the scheduled approval of a PR; returns whether there was one
+    pub fn cancel(&mut self, pr_id: i32) -> bool {
+        let before = self.entries.len();
+        self.entries.retain(|entry| entry.pr_id != pr_id);
+        if self.entries.len() == before {
+            return false;
+        }
+        self.dirty = true;
+        self.save();
+        true
+    }
+
+    /// Scheduled approvals, soonest first
+    pub fn entries(&self) -> &[ScheduledApproval] {
+        &self.entries
+    }
+
+    /// Write the queue to disk if it changed since the last save; saving is best effort, as the
+    /// in-memory queue stays authoritative for this run and a failed save is retried on the next one
+    pub fn save(&mut self) {
+        if !self.dirty {
+            return;
+        }
+
+        let result = serde_json::to_string_pretty(&self.entries)
+            .context("Failed to serialize approval queue")
+            .and_then(|queue_str| fs::write(&self.path, queue_str).map_err(Into::into));
+
+        match result {
+            Ok(()) => self.dirty = false,
+            Err(e) => warn!("⚠ Failed to save approval queue to {:?}: {}", self.path, e),
+        }
+    }
+}
+
+/// Fetch the PR again and make sure it is still worth approving
+pub async fn revalidate(client: &AzureDevOpsClient, approval: &ScheduledApproval) -> Result<Revalidation> {
+    let pr = client.get_pull_request_by_id(approval.pr_id).await?;
+
+    if let Some(status) = pr.status.as_deref().filter(|status| *status != "active") {
+        let reason = format!("PR is {}", status);
+        return Ok(Revalidation::Cancelled(pr, reason));
+    }
+
+    let source_commit = pr.last_merge_source_commit.as_ref().map(|commit| commit.commit_id.as_str());
+    if approval.source_commit.is_some() && source_commit != approval.source_commit.as_deref() {
+        return Ok(Revalidation::PushedTo(pr));
+    }
+
+    let reviewers = client.get_reviewers(&pr).await?;
+    if let Some(rejecting) = reviewers.iter().find(|r| r.vote == REJECTED_VOTE) {
+        let reason = format!("rejected by {}", rejecting.display_name);
+        return Ok(Revalidation::Cancelled(pr, reason));
+    }
+
+    Ok(Revalidation::Ready(pr))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn approval(pr_id: i32, due_at: DateTime<Utc>) -> ScheduledApproval {
+        ScheduledApproval {
+            pr_id,
+            repository: "web".to_string(),
+            author: "Alice".to_string(),
+            title: format!("PR {}", pr_id),
+            rule: None,
+            scheduled_at: Utc::now(),
+            due_at,
+            source_commit: None,
+        }
+    }
+
+    #[test]
+    fn due_approvals_stay_queued_until_finished() {
+        let path = std::env::temp_dir().join(format!("fpr-approval-queue-test-{}.json", std::process::id()));
+        fs::remove_file(&path).ok();
+        let now = Utc::now();
+
+        // Loading and saving an untouched empty queue leaves no file behind
+        let mut queue = ApprovalQueue::load(&path).unwrap();
+        queue.save();
+        assert!(!path.exists());
+
+        queue.schedule(approval(2, now + Duration::hours(1)));
+        queue.schedule(approval(1, now - Duration::minutes(1)));
+        assert_eq!(queue.entries().iter().map(|entry| entry.pr_id).collect::<Vec<_>>(), vec![1, 2]);
+
+        let due = queue.due(now);
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].pr_id, 1);
+
+        // Until it is finished, the due approval survives a restart
+        assert_eq!(ApprovalQueue::load(&path).unwrap().due(now).len(), 1);
+
+        // A newer approval of the same PR is not dropped when the old one finishes
+        let rescheduled = ScheduledApproval { scheduled_at: due[0].scheduled_at + Duration::seconds(1), ..approval(1, now + Duration::hours(2)) };
+        queue.schedule(rescheduled);
+        queue.finish(&due[0]);
+        assert_eq!(queue.entries().len(), 2);
+
+        queue.finish(&queue.entries()[1].clone());
+        assert!(queue.cancel(2));
+        assert!(!queue.cancel(2));
+        assert!(ApprovalQueue::load(&path).unwrap().entries().is_empty());
+        fs::remove_file(&path).ok();
+    }
+}
@@ -0,0 +1,246 @@
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use reqwest::header::HeaderMap;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tracing::{debug, warn};
+use url::Url;
+
+use crate::redact;
+
+// Response headers never written to a cassette, as they can carry credentials
+const SCRUBBED_HEADERS: [&str; 3] = ["set-cookie", "authorization", "www-authenticate"];
+
+/// Every request the client made with the response it got, in order
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Cassette {
+    pub interactions: Vec<Interaction>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Interaction {
+    pub request: RecordedRequest,
+    pub response: RecordedResponse,
+}
+
+/// A request as sent, without its headers so the auth header never reaches the cassette
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedResponse {
+    pub status: u16,
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    #[serde(default)]
+    pub body: String,
+}
+
+impl RecordedResponse {
+    /// Keep the status, the headers worth replaying and the body, with secrets redacted
+    pub fn new(status: u16, headers: &HeaderMap, body: &[u8]) -> Self {
+        let headers = headers.iter()
+            .filter(|(name, _)| !SCRUBBED_HEADERS.contains(&name.as_str()))
+            .filter_map(|(name, value)| Some((name.to_string(), redact::redact(value.to_str().ok()?))))
+            .collect();
+        Self { status, headers, body: redact::redact(&String::from_utf8_lossy(body)) }
+    }
+}
+
+/// What a replayed request has to share with a recorded one to be answered by it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum MatchOn {
+    Method,
+    Path,
+    Query,
+    /// Answer requests with the recorded responses in order
+    Ordered,
+}
+
+/// Appends every interaction to a cassette file as a JSON line as it happens, so a crash still leaves
+/// a usable recording
+pub struct Recorder {
+    path: PathBuf,
+    file: Mutex<File>,
+}
+
+impl Recorder {
+    /// Start a new cassette, replacing any file already at `path`
+    pub fn new(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let file = File::create(&path).with_context(|| format!("Failed to create cassette {:?}", path))?;
+        Ok(Self { path, file: Mutex::new(file) })
+    }
+
+    pub fn record(&self, request: RecordedRequest, response: RecordedResponse) {
+        let interaction = Interaction {
+            request: RecordedRequest { body: request.body.map(|body| redact::redact(&body)), ..request },
+            response,
+        };
+
+        let result = serde_json::to_string(&interaction)
+            .context("Failed to serialize cassette interaction")
+            .and_then(|line| writeln!(self.file.lock().unwrap(), "{}", line).map_err(Into::into));
+        if let Err(e) = result {
+            warn!("⚠ Failed to write cassette {:?}: {}", self.path, e);
+        }
+    }
+}
+
+/// Serves recorded responses instead of going to the network
+pub struct Player {
+    interactions: Vec<Interaction>,
+    match_on: Vec<MatchOn>,
+    // Which interactions were played, and where ordered playback is
+    played: Mutex<(Vec<bool>, usize)>,
+}
+
+impl Player {
+    /// Load a cassette of JSON lines, one interaction each, or a single `{"interactions": [...]}` document
+    pub fn load(path: impl AsRef<Path>, match_on: &[MatchOn]) -> Result<Self> {
+        let path = path.as_ref();
+        let json = fs::read_to_string(path).with_context(|| format!("Failed to read cassette {:?}", path))?;
+        let cassette = match serde_json::from_str::<Cassette>(&json) {
+            Ok(cassette) => cassette,
+            Err(_) => Cassette {
+                interactions: json.lines()
+                    .enumerate()
+                    .filter(|(_, line)| !line.trim().is_empty())
+                    .map(|(index, line)| serde_json::from_str(line)
+                        .with_context(|| format!("Failed to parse line {} of cassette {:?}", index + 1, path)))
+                    .collect::<Result<_>>()?,
+            },
+        };
+        Ok(Self::new(cassette, match_on))
+    }
+
+    pub fn new(cassette: Cassette, match_on: &[MatchOn]) -> Self {
+        let played = vec![false; cassette.interactions.len()];
+        Self { interactions: cassette.interactions, match_on: match_on.to_vec(), played: Mutex::new((played, 0)) }
+    }
+
+    /// The recorded response for a request
+    pub fn play(&self, request: &RecordedRequest) -> Result<RecordedResponse> {
+        let mut played = self.played.lock().unwrap();
+        let (played, next) = &mut *played;
+
+        let index = if self.match_on.contains(&MatchOn::Ordered) {
+            let index = *next;
+            let interaction = self.interactions.get(index)
+                .with_context(|| format!("Cassette has no interaction left for {} {}", request.method, request.url))?;
+            if !self.matches(&interaction.request, request)? {
+                return Err(anyhow::anyhow!("Request {} {} doesn't match interaction {} of the cassette ({} {})",
+                    request.method, request.url, index + 1, interaction.request.method, interaction.request.url));
+            }
+            *next += 1;
+            index
+        } else {
+            // Unplayed interactions first; once all matches are used up, the last one keeps answering, e.g. on every poll
+            let mut matching = Vec::new();
+            for (index, interaction) in self.interactions.iter().enumerate() {
+                if self.matches(&interaction.request, request)? {
+                    matching.push(index);
+                }
+            }
+            matching.iter().copied().find(|&index| !played[index])
+                .or(matching.last().copied())
+                .with_context(|| format!("Cassette has no interaction for {} {}", request.method, request.url))?
+        };
+
+        played[index] = true;
+        debug!("Replaying interaction {} for {} {}", index + 1, request.method, request.url);
+        Ok(self.interactions[index].response.clone())
+    }
+
+    fn matches(&self, recorded: &RecordedRequest, request: &RecordedRequest) -> Result<bool> {
+        let recorded_url = Url::parse(&recorded.url).with_context(|| format!("Invalid URL in cassette: {}", recorded.url))?;
+        let url = Url::parse(&request.url).with_context(|| format!("Invalid request URL: {}", request.url))?;
+        // Query parameters are compared regardless of their order
+        let query = |url: &Url| url.query_pairs().into_owned().collect::<BTreeMap<String, String>>();
+
+        Ok(self.match_on.iter().all(|field| match field {
+            MatchOn::Method => recorded.method.eq_ignore_ascii_case(&request.method),
+            MatchOn::Path => recorded_url.path() == url.path(),
+            MatchOn::Query => query(&recorded_url) == query(&url),
+            MatchOn::Ordered => true,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn interaction(method: &str, url: &str, body: &str) -> Interaction {
+        Interaction {
+            request: RecordedRequest { method: method.to_string(), url: url.to_string(), body: None },
+            response: RecordedResponse { status: 200, headers: BTreeMap::new(), body: body.to_string() },
+        }
+    }
+
+    fn request(method: &str, url: &str) -> RecordedRequest {
+        RecordedRequest { method: method.to_string(), url: url.to_string(), body: None }
+    }
+
+    #[test]
+    fn replay_matches_on_the_configured_fields() {
+        let cassette = || Cassette {
+            interactions: vec![
+                interaction("GET", "https://ado.test/prs?status=active&top=10", "first poll"),
+                interaction("GET", "https://ado.test/prs?top=10&status=active", "second poll"),
+                interaction("PUT", "https://ado.test/prs/1/reviewers/me", "voted"),
+            ],
+        };
+
+        let player = Player::new(cassette(), &[MatchOn::Method, MatchOn::Path, MatchOn::Query]);
+        assert_eq!(player.play(&request("PUT", "https://other.host/prs/1/reviewers/me")).unwrap().body, "voted");
+        assert_eq!(player.play(&request("GET", "https://ado.test/prs?status=active&top=10")).unwrap().body, "first poll");
+        assert_eq!(player.play(&request("GET", "https://ado.test/prs?status=active&top=10")).unwrap().body, "second poll");
+        // The last match keeps answering once every match was played
+        assert_eq!(player.play(&request("GET", "https://ado.test/prs?status=active&top=10")).unwrap().body, "second poll");
+        assert!(player.play(&request("GET", "https://ado.test/prs?status=completed")).is_err());
+
+        let ordered = Player::new(cassette(), &[MatchOn::Ordered, MatchOn::Method]);
+        assert_eq!(ordered.play(&request("GET", "https://ado.test/anything")).unwrap().body, "first poll");
+        assert!(ordered.play(&request("PUT", "https://ado.test/anything")).is_err());
+        assert_eq!(ordered.play(&request("GET", "https://ado.test/anything")).unwrap().body, "second poll");
+    }
+
+    #[test]
+    fn recorded_lines_play_back_and_documents_still_load() {
+        let path = std::env::temp_dir().join(format!("fpr-cassette-test-{}.jsonl", std::process::id()));
+        fs::write(&path, "left over from an earlier recording\n").unwrap();
+
+        let recorder = Recorder::new(&path).unwrap();
+        for (method, url, body) in [("GET", "https://ado.test/prs", "polled"), ("PUT", "https://ado.test/prs/1/reviewers/me", "voted")] {
+            let interaction = interaction(method, url, body);
+            recorder.record(interaction.request, interaction.response);
+        }
+        drop(recorder);
+        assert_eq!(fs::read_to_string(&path).unwrap().lines().count(), 2);
+
+        let player = Player::load(&path, &[MatchOn::Ordered]).unwrap();
+        assert_eq!(player.play(&request("GET", "https://ado.test/prs")).unwrap().body, "polled");
+        assert_eq!(player.play(&request("PUT", "https://ado.test/prs/1/reviewers/me")).unwrap().body, "voted");
+
+        // Cassettes recorded as a single document still play
+        let document = Cassette { interactions: vec![interaction("GET", "https://ado.test/prs", "from a document")] };
+        fs::write(&path, serde_json::to_string_pretty(&document).unwrap()).unwrap();
+        let player = Player::load(&path, &[MatchOn::Method, MatchOn::Path]).unwrap();
+        assert_eq!(player.play(&request("GET", "https://ado.test/prs")).unwrap().body, "from a document");
+
+        fs::write(&path, "{\"request\": 1}\n").unwrap();
+        let error = Player::load(&path, &[MatchOn::Method]).err().unwrap();
+        assert!(error.to_string().contains("line 1"), "{}", error);
+        fs::remove_file(&path).ok();
+    }
+}
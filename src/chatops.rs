@@ -0,0 +1,324 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use futures::stream::{self, StreamExt};
+use tracing::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use crate::ado_client::AzureDevOpsClient;
+use crate::config::{parse_duration, AppConfig};
+use crate::control;
+use crate::models::{Comment, CommentThread, PullRequest};
+use crate::ReviewerState;
+
+// Number of past decisions included in a `why` reply
+const WHY_DECISION_COUNT: usize = 5;
+
+/// Settings for controlling the bot through PR comments
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ChatOpsConfig {
+    /// Prefix every command starts with
+    #[serde(default = "default_prefix")]
+    pub prefix: String,
+    /// Identity IDs or unique names (e.g. "jane@contoso.com") allowed to run commands. Display names aren't
+    /// accepted: they aren't unique and people can change their own
+    #[serde(default)]
+    pub allowed_users: Vec<String>,
+    /// File where handled commands are kept so they survive restarts
+    #[serde(default = "default_state_file")]
+    pub state_file: String,
+    /// How often the comments of the active PRs are read, e.g. "1m"
+    #[serde(default = "default_scan_interval")]
+    pub scan_interval: String,
+}
+
+fn default_prefix() -> String {
+    "/fastreview".to_string()
+}
+
+fn default_state_file() -> String {
+    "chatops_state.json".to_string()
+}
+
+fn default_scan_interval() -> String {
+    "1m".to_string()
+}
+
+/// Commands already handled, saved so a restart neither runs them again nor drops the ones posted
+/// while the bot was down
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct HandledCommands {
+    /// When chat-ops was first enabled; older comments are never treated as commands
+    since: Option<DateTime<Utc>>,
+    /// Handled commands on active PRs, as (PR ID, thread ID, comment ID)
+    handled: BTreeSet<(i32, i32, i32)>,
+    #[serde(skip)]
+    path: Option<PathBuf>,
+    /// When the threads were last read
+    #[serde(skip)]
+    last_scan: Option<Instant>,
+}
+
+impl HandledCommands {
+    /// Load the state file the first time, or again when the config names another one
+    fn open(&mut self, path: &str, started: DateTime<Utc>) {
+        if self.path.as_deref() == Some(Path::new(path)) {
+            return;
+        }
+
+        let loaded = if Path::new(path).exists() {
+            fs::read_to_string(path)
+                .context("Failed to read file")
+                .and_then(|state| serde_json::from_str(&state).context("Failed to parse file"))
+        } else {
+            Ok(Self::default())
+        };
+        *self = loaded.unwrap_or_else(|e| {
+            warn!("⚠ Ignoring chat-ops state {}: {:#}", path, e);
+            Self::default()
+        });
+        self.since.get_or_insert(started);
+        self.path = Some(PathBuf::from(path));
+    }
+
+    /// Mark a command as handled; returns false if it already was
+    fn insert(&mut self, key: (i32, i32, i32)) -> bool {
+        if !self.handled.insert(key) {
+            return false;
+        }
+        self.save();
+        true
+    }
+
+    /// Forget PRs that are no longer active; `prs` is the full list of active PRs, so a PR missing from it
+    /// was completed or abandoned
+    fn retain_active(&mut self, prs: &[PullRequest]) {
+        let before = self.handled.len();
+        self.handled.retain(|(pr_id, _, _)| prs.iter().any(|pr| pr.pull_request_id == *pr_id));
+        if self.handled.len() != before {
+            self.save();
+        }
+    }
+
+    /// Write the state to disk; best effort, like the approval queue
+    fn save(&self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let result = serde_json::to_string_pretty(self)
+            .context("Failed to serialize chat-ops state")
+            .and_then(|state| fs::write(path, state).map_err(Into::into));
+        if let Err(e) = result {
+            warn!("⚠ Failed to save chat-ops state to {:?}: {}", path, e);
+        }
+    }
+}
+
+/// A command found in a PR comment
+#[derive(Debug, PartialEq, Eq)]
+enum Command {
+    Approve,
+    Skip,
+    Why,
+    Recheck,
+    Unknown(String),
+}
+
+impl ChatOpsConfig {
+    pub fn validate(&self) -> Result<()> {
+        parse_duration(&self.scan_interval).context("Invalid chatops.scan_interval")?;
+        Ok(())
+    }
+
+    fn is_allowed(&self, comment: &Comment) -> bool {
+        let author = &comment.author;
+        self.allowed_users.iter().any(|user| {
+            *user == author.id || author.unique_name.as_deref().is_some_and(|unique_name| user.eq_ignore_ascii_case(unique_name))
+        })
+    }
+
+    /// Parse the command on the first line of a comment, if there is one
+    fn parse_command(&self, content: &str) -> Option<Command> {
+        let mut words = content.lines().next()?.split_whitespace();
+        if words.next()? != self.prefix {
+            return None;
+        }
+
+        Some(match words.next().unwrap_or("") {
+            "approve" => Command::Approve,
+            "skip" => Command::Skip,
+            "why" => Command::Why,
+            "recheck" => Command::Recheck,
+            other => Command::Unknown(other.to_string()),
+        })
+    }
+}
+
+/// Scan the threads of every active PR for new commands and run them, at most once per `scan_interval`.
+/// Posting a comment doesn't change the PR itself, so the threads have to be read to find new ones, and
+/// reading them on every poll would take requests away from approvals
+pub async fn process_commands(
+    client: &AzureDevOpsClient,
+    config: &AppConfig,
+    prs: &[PullRequest],
+    reviewer_id: &str,
+    state: &ReviewerState,
+) {
+    let Some(chatops) = &config.chatops else {
+        return;
+    };
+
+    let mut handled = state.handled_comments.lock().await;
+    handled.open(&chatops.state_file, state.started);
+    handled.retain_active(prs);
+    let interval = parse_duration(&chatops.scan_interval).unwrap_or_default();
+    if handled.last_scan.is_some_and(|last_scan| last_scan.elapsed() < interval) {
+        return;
+    }
+    handled.last_scan = Some(Instant::now());
+    debug!("Looking for chat-ops commands on {} PRs", prs.len());
+
+    // Fetch threads concurrently, but run the commands one at a time in PR order
+    let results: Vec<_> = stream::iter(prs)
+        .map(|pr| async move { (pr, client.get_threads(pr).await) })
+        .buffered(config.max_concurrency)
+        .collect()
+        .await;
+
+    for (pr, result) in results {
+        let threads = match result {
+            Ok(threads) => threads,
+            Err(e) => {
+                warn!("⚠ Failed to fetch threads for PR #{}: {}", pr.pull_request_id, e);
+                continue;
+            }
+        };
+
+        for thread in threads.iter().filter(|thread| !thread.is_deleted) {
+            for comment in &thread.comments {
+                if let Some(command) = new_command(chatops, &mut handled, pr, thread, comment, reviewer_id) {
+                    let reply = if chatops.is_allowed(comment) {
+                        run_command(client, pr, comment, command, reviewer_id, state).await
+                    } else {
+                        warn!("⛔ {} is not allowed to run commands (PR #{})", comment.author.display_name, pr.pull_request_id);
+                        format!("Sorry {}, you are not allowed to run FastPRReviewer commands.", comment.author.display_name)
+                    };
+                    if let Err(e) = client.reply_to_thread(pr, thread.id, comment.id, &reply).await {
+                        warn!("⚠ Failed to reply to command on PR #{}: {}", pr.pull_request_id, e);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Return the command in a comment if it hasn't been handled yet, marking it as handled
+fn new_command(
+    chatops: &ChatOpsConfig,
+    handled: &mut HandledCommands,
+    pr: &PullRequest,
+    thread: &CommentThread,
+    comment: &Comment,
+    reviewer_id: &str,
+) -> Option<Command> {
+    // Never react to our own replies
+    if comment.author.id == reviewer_id {
+        return None;
+    }
+
+    let command = chatops.parse_command(comment.content.as_deref()?)?;
+
+    // Commands written before chat-ops was enabled are history, not requests
+    let published = comment.published_date.as_deref()
+        .and_then(|date| DateTime::parse_from_rfc3339(date).ok())
+        .map(|date| date.with_timezone(&Utc));
+    if published.is_none_or(|date| handled.since.is_none_or(|since| date < since)) {
+        return None;
+    }
+
+    // Marked before running, so a crash can't make a command run twice
+    if !handled.insert((pr.pull_request_id, thread.id, comment.id)) {
+        return None;
+    }
+
+    debug!("Found command {:?} from {} on PR #{}", command, comment.author.display_name, pr.pull_request_id);
+    Some(command)
+}
+
+/// Run an authorised command and return the reply to post in its thread
+async fn run_command(
+    client: &AzureDevOpsClient,
+    pr: &PullRequest,
+    comment: &Comment,
+    command: Command,
+    reviewer_id: &str,
+    state: &ReviewerState,
+) -> String {
+    let author = &comment.author.display_name;
+    info!("💬 Running command {:?} from {} on PR #{}", command, author, pr.pull_request_id);
+    let pr_id = pr.pull_request_id;
+
+    match command {
+        Command::Approve => match control::force_approve(client, state, pr, reviewer_id, author).await {
+            Ok(()) => "✅ Approved.".to_string(),
+            Err(e) => format!("❌ Failed to approve: {}", e),
+        },
+        Command::Skip => {
+            control::skip(state, pr, author).await;
+            "⏭ This pull request will be skipped.".to_string()
+        }
+        Command::Why => {
+            let recent: Vec<_> = state.decisions.recent().into_iter()
+                .filter(|decision| decision.pr_id == pr_id)
+                .collect();
+            if recent.is_empty() {
+                return "No decisions have been made about this pull request yet.".to_string();
+            }
+
+            let lines: Vec<String> = recent.iter().rev().take(WHY_DECISION_COUNT)
+                .map(|d| format!("- {}{}: {} -> {}{}", d.timestamp.format("%Y-%m-%d %H:%M:%S UTC"),
+                    d.rule.as_deref().map(|rule| format!(" (rule '{}')", rule)).unwrap_or_default(),
+                    d.action, d.outcome, if d.detail.is_empty() { String::new() } else { format!(" ({})", d.detail) }))
+                .collect();
+            format!("Recent decisions for this pull request:\n{}", lines.join("\n"))
+        }
+        Command::Recheck => {
+            state.recheck(pr_id).await;
+            "🔄 This pull request will be re-evaluated on the next poll.".to_string()
+        }
+        Command::Unknown(name) => {
+            format!("Unknown command '{}'. Available commands: approve, skip, why, recheck.", name)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn comment(id: &str, display_name: &str, unique_name: Option<&str>) -> Comment {
+        serde_json::from_value(serde_json::json!({
+            "id": 1,
+            "author": { "id": id, "displayName": display_name, "uniqueName": unique_name },
+            "content": "/fastreview approve",
+        })).unwrap()
+    }
+
+    #[test]
+    fn only_identity_ids_and_unique_names_are_allowed() {
+        let chatops = ChatOpsConfig {
+            prefix: default_prefix(),
+            allowed_users: vec!["jane-id".to_string(), "sam@contoso.com".to_string(), "Jane Doe".to_string()],
+            state_file: default_state_file(),
+            scan_interval: default_scan_interval(),
+        };
+
+        assert!(chatops.is_allowed(&comment("jane-id", "Jane Doe", None)));
+        assert!(chatops.is_allowed(&comment("sam-id", "Sam Lead", Some("Sam@Contoso.com"))));
+        // Anyone can call themselves Jane Doe
+        assert!(!chatops.is_allowed(&comment("mallory-id", "Jane Doe", Some("mallory@contoso.com"))));
+    }
+}
@@ -0,0 +1,222 @@
+use anyhow::Result;
+use tracing::debug;
+use reqwest::StatusCode;
+
+use crate::ado_client::{ApiError, AzureDevOpsClient};
+use crate::models::{GitCommitRef, PullRequest, PullRequestUpdate};
+use crate::rules::CompleteAction;
+
+// Votes of 5 (approved with suggestions) and 10 (approved) count as approvals
+const MIN_APPROVING_VOTE: i32 = 5;
+const REJECTED_VOTE: i32 = -10;
+
+// Policy evaluation states that don't block completion
+const PASSING_POLICY_STATES: [&str; 2] = ["approved", "notApplicable"];
+
+/// Outcome of one attempt to complete a pull request directly
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CompletionOutcome {
+    /// The PR was completed by the bot
+    Completed,
+    /// The PR isn't ready yet; keep checking on later polls
+    Waiting(String),
+    /// The source branch conflicts with the target; the author has to fix it
+    Conflicts,
+    /// The PR moved on while we were checking it; retry with the new commit
+    StaleCommit,
+    /// The PR is no longer active (completed or abandoned by someone else)
+    Closed(String),
+}
+
+impl CompletionOutcome {
+    /// Short label used in the decision log
+    pub fn label(&self) -> &'static str {
+        match self {
+            CompletionOutcome::Completed => "completed",
+            CompletionOutcome::Waiting(_) => "waiting",
+            CompletionOutcome::Conflicts => "conflicts",
+            CompletionOutcome::StaleCommit => "stale_commit",
+            CompletionOutcome::Closed(_) => "closed",
+        }
+    }
+
+    pub fn detail(&self) -> &str {
+        match self {
+            CompletionOutcome::Waiting(reason) | CompletionOutcome::Closed(reason) => reason,
+            _ => "",
+        }
+    }
+}
+
+/// Check policies, merge status and votes, and complete the PR if everything passes
+pub async fn try_complete(
+    client: &AzureDevOpsClient,
+    pull_request_id: i32,
+    action: &CompleteAction,
+) -> Result<(PullRequest, CompletionOutcome)> {
+    // Always work from a fresh copy so the merge commit is current
+    let pr = client.get_pull_request_by_id(pull_request_id).await?;
+
+    let status = pr.status.clone().unwrap_or_else(|| "active".to_string());
+    if status != "active" {
+        return Ok((pr, CompletionOutcome::Closed(format!("PR is {}", status))));
+    }
+
+    match pr.merge_status.as_deref() {
+        Some("succeeded") => (),
+        Some("conflicts") => return Ok((pr, CompletionOutcome::Conflicts)),
+        other => {
+            let reason = format!("merge status is {}", other.unwrap_or("unknown"));
+            return Ok((pr, CompletionOutcome::Waiting(reason)));
+        }
+    }
+
+    let reviewers = client.get_reviewers(&pr).await?;
+    if let Some(rejecting) = reviewers.iter().find(|r| r.vote == REJECTED_VOTE) {
+        let reason = format!("rejected by {}", rejecting.display_name);
+        return Ok((pr, CompletionOutcome::Waiting(reason)));
+    }
+
+    let approvals = reviewers.iter().filter(|r| r.vote >= MIN_APPROVING_VOTE).count();
+    if approvals < action.min_approvals {
+        let reason = format!("{}/{} approvals", approvals, action.min_approvals);
+        return Ok((pr, CompletionOutcome::Waiting(reason)));
+    }
+
+    let evaluations = client.get_policy_evaluations(&pr).await?;
+    if let Some(pending) = evaluations.iter()
+        .filter(|e| e.configuration.is_blocking)
+        .find(|e| !PASSING_POLICY_STATES.contains(&e.status.as_str()))
+    {
+        let reason = format!("policy '{}' is {}", pending.configuration.policy_type.display_name, pending.status);
+        return Ok((pr, CompletionOutcome::Waiting(reason)));
+    }
+
+    let Some(commit) = &pr.last_merge_source_commit else {
+        return Ok((pr, CompletionOutcome::Waiting("no merge source commit yet".to_string())));
+    };
+
+    debug!("Completing PR #{} at commit {}", pr.pull_request_id, commit.commit_id);
+
+    let update = PullRequestUpdate {
+        status: Some("completed".to_string()),
+        last_merge_source_commit: Some(GitCommitRef { commit_id: commit.commit_id.clone() }),
+        completion_options: Some(action.options.to_completion_options(&pr)),
+        ..Default::default()
+    };
+
+    match client.update_pull_request(&pr, &update).await {
+        Ok(_) => Ok((pr, CompletionOutcome::Completed)),
+        Err(e) => match e.downcast_ref::<ApiError>() {
+            // ADO rejects the PATCH with 409 when lastMergeSourceCommit is no longer the head
+            Some(api_error) if api_error.status == StatusCode::CONFLICT => Ok((pr, CompletionOutcome::StaleCommit)),
+            _ => Err(e),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const PR_ID: i32 = 7;
+
+    fn pr(merge_status: &str) -> Value {
+        json!({
+            "pullRequestId": PR_ID,
+            "title": "Bump serde",
+            "createdBy": { "id": "jane", "displayName": "Jane Doe" },
+            "creationDate": "2026-01-01T00:00:00Z",
+            "repository": { "id": "repo-id", "name": "repo", "project": { "id": "project-id" } },
+            "status": "active",
+            "mergeStatus": merge_status,
+            "lastMergeSourceCommit": { "commitId": "abc123" },
+        })
+    }
+
+    fn reviewer(name: &str, vote: i32) -> Value {
+        json!({ "id": name, "displayName": name, "vote": vote })
+    }
+
+    fn policy(status: &str, is_blocking: bool) -> Value {
+        json!({ "status": status, "configuration": { "isBlocking": is_blocking, "type": { "displayName": "Build" } } })
+    }
+
+    /// Try to complete a PR against a server answering with these responses; returns the outcome and
+    /// the update sent for the PR, if any
+    async fn complete(pr: Value, reviewers: Vec<Value>, policies: Vec<Value>, update_status: u16) -> (CompletionOutcome, Option<Value>) {
+        let server = MockServer::start().await;
+        let pr_path = format!("/_apis/git/repositories/repo-id/pullRequests/{}", PR_ID);
+        Mock::given(method("GET"))
+            .and(path(format!("/_apis/git/pullrequests/{}", PR_ID)))
+            .respond_with(ResponseTemplate::new(200).set_body_json(&pr))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(format!("{}/reviewers", pr_path)))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "value": reviewers })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/_apis/policy/evaluations"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "value": policies })))
+            .mount(&server)
+            .await;
+        Mock::given(method("PATCH"))
+            .and(path(pr_path))
+            .respond_with(ResponseTemplate::new(update_status).set_body_json(&pr))
+            .mount(&server)
+            .await;
+
+        let client = AzureDevOpsClient::with_base_url(&server.uri(), "pat");
+        let action = CompleteAction { min_approvals: 2, options: Default::default() };
+        let (_, outcome) = try_complete(&client, PR_ID, &action).await.unwrap();
+        let update = server.received_requests().await.unwrap().iter()
+            .find(|request| request.method.as_str() == "PATCH")
+            .map(|request| serde_json::from_slice(&request.body).unwrap());
+        (outcome, update)
+    }
+
+    #[tokio::test]
+    async fn completes_once_votes_policies_and_merge_pass() {
+        let approved = vec![reviewer("bot", 10), reviewer("lead", 5)];
+        let passing = vec![policy("approved", true), policy("rejected", false)];
+        let (outcome, update) = complete(pr("succeeded"), approved.clone(), passing.clone(), 200).await;
+        assert_eq!(outcome, CompletionOutcome::Completed);
+        let update = update.unwrap();
+        assert_eq!(update["status"], "completed");
+        assert_eq!(update["lastMergeSourceCommit"]["commitId"], "abc123");
+
+        assert_eq!(complete(pr("conflicts"), approved.clone(), passing.clone(), 200).await, (CompletionOutcome::Conflicts, None));
+        assert_eq!(complete(pr("queued"), approved.clone(), passing.clone(), 200).await,
+            (CompletionOutcome::Waiting("merge status is queued".to_string()), None));
+
+        let rejected = vec![reviewer("bot", 10), reviewer("lead", 10), reviewer("Sam", -10)];
+        assert_eq!(complete(pr("succeeded"), rejected, passing.clone(), 200).await,
+            (CompletionOutcome::Waiting("rejected by Sam".to_string()), None));
+
+        let too_few = vec![reviewer("bot", 10), reviewer("lead", -5)];
+        assert_eq!(complete(pr("succeeded"), too_few, passing.clone(), 200).await,
+            (CompletionOutcome::Waiting("1/2 approvals".to_string()), None));
+
+        let blocking = vec![policy("approved", true), policy("running", true)];
+        assert_eq!(complete(pr("succeeded"), approved.clone(), blocking, 200).await,
+            (CompletionOutcome::Waiting("policy 'Build' is running".to_string()), None));
+
+        let mut abandoned = pr("succeeded");
+        abandoned["status"] = json!("abandoned");
+        assert_eq!(complete(abandoned, approved, passing, 200).await,
+            (CompletionOutcome::Closed("PR is abandoned".to_string()), None));
+    }
+
+    #[tokio::test]
+    async fn conflict_while_completing_means_the_commit_is_stale() {
+        let approved = vec![reviewer("bot", 10), reviewer("lead", 10)];
+        let (outcome, update) = complete(pr("succeeded"), approved, Vec::new(), 409).await;
+        assert_eq!(outcome, CompletionOutcome::StaleCommit);
+        assert!(update.is_some());
+    }
+}
@@ -0,0 +1,202 @@
+use anyhow::Result;
+use tracing::debug;
+
+use crate::ado_client::AzureDevOpsClient;
+use crate::models::PullRequest;
+use crate::rules::{Rule, WorkItemCondition};
+
+/// Result of checking a rule's conditions against a PR
+#[derive(Debug)]
+pub enum ConditionResult {
+    Passed,
+    /// A condition failed; when `comment` is set the bot votes wait-for-author and leaves it on the PR
+    Failed { reason: String, comment: Option<String> },
+}
+
+/// Check every condition of a rule, stopping at the first one that fails
+pub async fn check_conditions(
+    client: &AzureDevOpsClient,
+    rule: &Rule,
+    pr: &PullRequest,
+    reviewer_id: &str,
+) -> Result<ConditionResult> {
+    if let Some(condition) = &rule.conditions.work_items {
+        let result = check_work_items(client, condition, pr).await?;
+        if let ConditionResult::Failed { .. } = result {
+            return Ok(result);
+        }
+    }
+
+    if let Some(max_active_threads) = rule.conditions.max_active_threads {
+        let active = count_active_threads(client, pr, reviewer_id).await?;
+        if active > max_active_threads {
+            return Ok(ConditionResult::Failed {
+                reason: format!("{} active comment threads (max {})", active, max_active_threads),
+                comment: None,
+            });
+        }
+    }
+
+    Ok(ConditionResult::Passed)
+}
+
+/// Count active threads started by people, ignoring system threads and our own
+async fn count_active_threads(client: &AzureDevOpsClient, pr: &PullRequest, reviewer_id: &str) -> Result<usize> {
+    let threads = client.get_threads(pr).await?;
+
+    let active = threads.iter()
+        .filter(|thread| !thread.is_deleted && thread.status.as_deref() == Some("active"))
+        .filter(|thread| match thread.comments.first() {
+            Some(first) => first.comment_type.as_deref() != Some("system") && first.author.id != reviewer_id,
+            None => false,
+        })
+        .count();
+
+    debug!("PR #{} has {} active threads from reviewers", pr.pull_request_id, active);
+    Ok(active)
+}
+
+async fn check_work_items(client: &AzureDevOpsClient, condition: &WorkItemCondition, pr: &PullRequest) -> Result<ConditionResult> {
+    let linked = client.get_pull_request_work_items(pr).await?;
+    debug!("PR #{} has {} linked work items", pr.pull_request_id, linked.len());
+
+    let matching = if linked.is_empty() {
+        0
+    } else if condition.types.is_empty() && condition.states.is_empty() {
+        linked.len()
+    } else {
+        // Only hit the work item tracking API when we have to filter
+        let ids: Vec<i32> = linked.iter().filter_map(|r| r.id.parse().ok()).collect();
+        client.get_work_items(&ids).await?
+            .iter()
+            .inspect(|item| debug!("Work item #{} is a {} in state {}", item.id, item.fields.work_item_type, item.fields.state))
+            .filter(|item| condition.types.is_empty() || condition.types.contains(&item.fields.work_item_type))
+            .filter(|item| condition.states.is_empty() || condition.states.contains(&item.fields.state))
+            .count()
+    };
+
+    if matching > 0 {
+        return Ok(ConditionResult::Passed);
+    }
+
+    let reason = if linked.is_empty() {
+        "no linked work items".to_string()
+    } else {
+        format!("none of the {} linked work items match the required type or state", linked.len())
+    };
+
+    Ok(ConditionResult::Failed { reason, comment: Some(condition.comment.clone()) })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const PR_PATH: &str = "/_apis/git/repositories/repo-id/pullRequests/7";
+
+    fn pr() -> PullRequest {
+        serde_json::from_value(json!({
+            "pullRequestId": 7,
+            "title": "Bump serde",
+            "createdBy": { "id": "jane", "displayName": "Jane Doe" },
+            "creationDate": "2026-01-01T00:00:00Z",
+            "repository": { "id": "repo-id", "name": "repo" },
+        })).unwrap()
+    }
+
+    fn work_item(id: i32, work_item_type: &str, state: &str) -> Value {
+        json!({ "id": id, "fields": { "System.WorkItemType": work_item_type, "System.State": state } })
+    }
+
+    fn thread(status: &str, author_id: &str) -> Value {
+        json!({ "id": 1, "status": status, "comments": [{ "id": 1, "author": { "id": author_id, "displayName": author_id } }] })
+    }
+
+    fn system_thread(status: &str) -> Value {
+        let mut thread = thread(status, "ado");
+        thread["comments"][0]["commentType"] = json!("system");
+        thread
+    }
+
+    /// Check `rule` against a PR with these linked work items and comment threads
+    async fn check(rule: &str, work_items: Vec<Value>, threads: Vec<Value>) -> ConditionResult {
+        let server = MockServer::start().await;
+        let linked: Vec<Value> = work_items.iter().map(|item| json!({ "id": item["id"].to_string() })).collect();
+        Mock::given(method("GET"))
+            .and(path(format!("{}/workitems", PR_PATH)))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "value": linked })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/_apis/wit/workitems"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "value": work_items })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(format!("{}/threads", PR_PATH)))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "value": threads })))
+            .mount(&server)
+            .await;
+
+        let rule: Rule = toml::from_str(&format!("name = \"test\"\n{}", rule)).unwrap();
+        let client = AzureDevOpsClient::with_base_url(&server.uri(), "pat");
+        check_conditions(&client, &rule, &pr(), "bot-id").await.unwrap()
+    }
+
+    fn failure(result: ConditionResult) -> (String, Option<String>) {
+        match result {
+            ConditionResult::Failed { reason, comment } => (reason, comment),
+            ConditionResult::Passed => panic!("the conditions passed"),
+        }
+    }
+
+    #[tokio::test]
+    async fn work_items_of_the_required_type_and_state_must_be_linked() {
+        let any = "[conditions.work_items]\ncomment = \"Link a work item\"";
+        assert!(matches!(check(any, vec![work_item(1, "Task", "Closed")], Vec::new()).await, ConditionResult::Passed));
+        assert_eq!(failure(check(any, Vec::new(), Vec::new()).await),
+            ("no linked work items".to_string(), Some("Link a work item".to_string())));
+
+        let stories = "[conditions.work_items]\ntypes = [\"User Story\", \"Bug\"]\nstates = [\"Active\"]";
+        let linked = vec![work_item(1, "Task", "Active"), work_item(2, "Bug", "Active")];
+        assert!(matches!(check(stories, linked, Vec::new()).await, ConditionResult::Passed));
+        let (reason, comment) = failure(check(stories, vec![work_item(1, "Task", "Active"), work_item(2, "Bug", "Closed")], Vec::new()).await);
+        assert_eq!(reason, "none of the 2 linked work items match the required type or state");
+        assert!(comment.is_some_and(|comment| comment.contains("link a work item")));
+    }
+
+    #[tokio::test]
+    async fn active_threads_above_the_maximum_block_approval() {
+        let one_allowed = "[conditions]\nmax_active_threads = 1";
+        assert!(matches!(check(one_allowed, Vec::new(), vec![thread("active", "sam")]).await, ConditionResult::Passed));
+
+        let threads = vec![thread("active", "sam"), thread("active", "lee"), thread("fixed", "kim")];
+        assert_eq!(failure(check(one_allowed, Vec::new(), threads).await),
+            ("2 active comment threads (max 1)".to_string(), None));
+    }
+
+    #[tokio::test]
+    async fn only_active_threads_started_by_people_count() {
+        let mut deleted = thread("active", "sam");
+        deleted["isDeleted"] = json!(true);
+        let threads = vec![
+            thread("active", "sam"),
+            thread("active", "lee"),
+            // Started by ADO, by the bot, deleted, resolved or without comments
+            system_thread("active"),
+            thread("active", "bot-id"),
+            deleted,
+            thread("fixed", "kim"),
+            thread("closed", "kim"),
+            json!({ "id": 9, "status": "active", "comments": [] }),
+        ];
+
+        let two_allowed = "[conditions]\nmax_active_threads = 2";
+        assert!(matches!(check(two_allowed, Vec::new(), threads.clone()).await, ConditionResult::Passed));
+        let none_allowed = "[conditions]\nmax_active_threads = 0";
+        assert_eq!(failure(check(none_allowed, Vec::new(), threads).await).0, "2 active comment threads (max 0)");
+    }
+}
@@ -1,36 +1,323 @@
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::net::SocketAddr;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::env;
+use std::time::{Duration, SystemTime};
+use tracing::warn;
+
+use crate::chatops::ChatOpsConfig;
+use crate::polling::{AdaptiveInterval, PollingConfig};
+use crate::rate_limiter::{DEFAULT_BURST, DEFAULT_REQUESTS_PER_SECOND};
+use crate::redact;
+use crate::rules::Rule;
+use crate::schedule::{Schedule, ScheduleConfig};
+use crate::scripting::{self, ScriptingConfig};
+
+// Listen addresses starting with this are unix socket paths
+const UNIX_PREFIX: &str = "unix:";
+
+// Environment variables starting with this override config fields, e.g. FPR_PROJECT
+const ENV_PREFIX: &str = "FPR_";
+
+// Top-level fields whose environment values are always taken literally rather than parsed as TOML
+const STRING_FIELDS: [&str; 7] = [
+    "organization", "project", "personal_access_token", "pat_file", "reviewer_id", "approval_queue", "decision_log",
+];
+
+// How long a PAT command may run when the PAT is re-read
+const PAT_COMMAND_TIMEOUT: Duration = Duration::from_secs(30);
+
+// The PAT settings replace each other, so a higher layer setting one drops the others from lower layers
+const PAT_FIELDS: [&str; 3] = ["personal_access_token", "pat_file", "pat_command"];
+
+
+// Value shown in place of secrets
+const REDACTED: &str = "********";
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct AppConfig {
     pub organization: String,
     pub project: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub personal_access_token: String,
+    /// File holding the PAT, e.g. a mounted secret
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pat_file: Option<String>,
+    /// Command whose output is the PAT, e.g. ["secret-tool", "lookup", "service", "ado"]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pat_command: Option<Vec<String>>,
     #[serde(default)]
     pub watched_users: Vec<String>,
     #[serde(default)]
     pub reviewer_id: Option<String>,
+    /// File where delayed approvals are kept so they survive restarts
+    #[serde(default = "default_approval_queue")]
+    pub approval_queue: String,
+    /// Number of PRs processed at the same time
+    #[serde(default = "default_max_concurrency")]
+    pub max_concurrency: usize,
+    /// Optional path of a JSON lines file that every decision is appended to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decision_log: Option<String>,
+    /// Backs off the polling interval while idle when present
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub polling: Option<PollingConfig>,
+    /// How the bot stops on SIGTERM or Ctrl+C; the defaults apply when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shutdown: Option<ShutdownConfig>,
+    /// Client-side limit on requests to Azure DevOps
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rate_limit: Option<RateLimitConfig>,
+    /// Restricts approvals to working hours when present
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schedule: Option<ScheduleConfig>,
+    /// Regular expressions for other secrets that must never appear in logs, errors or comments
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub redact_patterns: Vec<String>,
+    /// Enables slash commands in PR comments when present
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chatops: Option<ChatOpsConfig>,
+    /// Serves the admin HTTP API when present
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub admin_api: Option<AdminApiConfig>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<Rule>,
+    /// Limits on the rules' approval policy scripts; the defaults apply when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scripting: Option<ScriptingConfig>,
+    /// Where each setting came from, keyed by dotted path
+    #[serde(skip)]
+    pub sources: BTreeMap<String, ConfigSource>,
+}
+
+/// Where a setting came from, from lowest to highest precedence
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigSource {
+    Default,
+    File,
+    Env(String),
+    Cli(String),
+    /// The PAT, read from its file or command
+    PatSource(String),
+}
+
+impl ConfigSource {
+    fn precedence(&self) -> u8 {
+        match self {
+            ConfigSource::Default => 0,
+            ConfigSource::File | ConfigSource::PatSource(_) => 1,
+            ConfigSource::Env(_) => 2,
+            ConfigSource::Cli(_) => 3,
+        }
+    }
+}
+
+impl std::fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigSource::Default => write!(f, "default"),
+            ConfigSource::File => write!(f, "config file"),
+            ConfigSource::Env(name) => write!(f, "environment {}", name),
+            ConfigSource::Cli(flag) => write!(f, "command line {}", flag),
+            ConfigSource::PatSource(source) => write!(f, "{}", source),
+        }
+    }
+}
+
+/// Settings given on the command line, which take precedence over the environment and the file
+#[derive(Debug, Default, Clone)]
+pub struct ConfigOverrides {
+    values: Vec<(String, String, toml::Value)>,
+}
+
+impl ConfigOverrides {
+    /// Override the field `key` with a value given through `flag`
+    pub fn set(&mut self, key: &str, flag: &str, value: impl Into<toml::Value>) {
+        self.values.push((key.to_string(), flag.to_string(), value.into()));
+    }
+}
+
+/// A setting of the effective config, as shown by `config show`
+pub struct EffectiveValue {
+    pub path: String,
+    pub value: String,
+    pub source: ConfigSource,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RateLimitConfig {
+    /// Requests that may be sent back to back before the steady rate applies
+    #[serde(default = "default_burst")]
+    pub burst: u32,
+    #[serde(default = "default_requests_per_second")]
+    pub requests_per_second: f64,
+}
+
+fn default_burst() -> u32 {
+    DEFAULT_BURST
+}
+
+fn default_requests_per_second() -> f64 {
+    DEFAULT_REQUESTS_PER_SECOND
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ShutdownConfig {
+    /// How long in-flight approvals may take to finish after a stop signal, e.g. "30s"
+    #[serde(default = "default_shutdown_timeout")]
+    pub timeout: String,
+    /// Exit code after a graceful shutdown
+    #[serde(default)]
+    pub exit_code: i32,
+    /// Exit code when a poll was still running after `timeout` and the bot stopped anyway
+    #[serde(default = "default_timeout_exit_code")]
+    pub timeout_exit_code: i32,
+}
+
+impl Default for ShutdownConfig {
+    fn default() -> Self {
+        Self {
+            timeout: default_shutdown_timeout(),
+            exit_code: 0,
+            timeout_exit_code: default_timeout_exit_code(),
+        }
+    }
+}
+
+fn default_shutdown_timeout() -> String {
+    "30s".to_string()
+}
+
+fn default_timeout_exit_code() -> i32 {
+    1
+}
+
+/// Settings for the admin HTTP API
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AdminApiConfig {
+    /// A localhost address such as "127.0.0.1:8787", or a unix socket such as "unix:/run/fast-pr-reviewer/admin.sock"
+    pub listen: String,
+    /// Bearer token every request must carry
+    pub token: String,
+}
+
+/// Where the admin API listens
+#[derive(Debug)]
+pub enum ListenAddress {
+    Tcp(SocketAddr),
+    Unix(PathBuf),
+}
+
+impl AdminApiConfig {
+    /// Check the listen address and token
+    pub fn validate(&self) -> Result<()> {
+        if self.token.trim().is_empty() {
+            return Err(anyhow::anyhow!("admin_api.token cannot be empty"));
+        }
+        self.listen_address()?;
+        Ok(())
+    }
+
+    pub fn listen_address(&self) -> Result<ListenAddress> {
+        if let Some(path) = self.listen.strip_prefix(UNIX_PREFIX) {
+            if !cfg!(unix) {
+                return Err(anyhow::anyhow!("admin_api.listen can only be a unix socket on Unix"));
+            }
+            return Ok(ListenAddress::Unix(PathBuf::from(path)));
+        }
+
+        let address: SocketAddr = self.listen.parse()
+            .with_context(|| format!("Invalid admin_api.listen '{}', expected e.g. 127.0.0.1:8787 or unix:/path/to/socket", self.listen))?;
+        // Anyone who can reach the API can approve PRs, so it never leaves the machine
+        if !address.ip().is_loopback() {
+            return Err(anyhow::anyhow!("admin_api.listen must be a localhost address, not {}", address.ip()));
+        }
+        Ok(ListenAddress::Tcp(address))
+    }
 }
 
 impl AppConfig {
-    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
-        let config_str = fs::read_to_string(&path)
-            .with_context(|| format!("Failed to read config file: {:?}", path.as_ref()))?;
+    /// Load the config file, then apply FPR_* environment variables and finally the command line overrides
+    pub fn from_file<P: AsRef<Path>>(path: P, overrides: &ConfigOverrides) -> Result<Self> {
+        Self::from_file_with_env(path.as_ref(), overrides, &env::vars().collect())
+    }
 
-        let mut config: AppConfig = toml::from_str(&config_str)
-            .with_context(|| format!("Failed to parse config file: {:?}", path.as_ref()))?;
+    /// `from_file` reading variables from `env` instead of the process environment
+    fn from_file_with_env(path: &Path, overrides: &ConfigOverrides, env: &BTreeMap<String, String>) -> Result<Self> {
+        let mut config = Self::parse(path, overrides, env)?;
+        if let Some(source) = config.pat_source()? {
+            config.personal_access_token = source.read()?;
+        }
+        Self::validated(config)
+    }
+
+    /// Like `from_file`, but reads the PAT without blocking the runtime and gives up on a PAT command
+    /// after PAT_COMMAND_TIMEOUT
+    pub async fn from_file_async<P: AsRef<Path>>(path: P, overrides: &ConfigOverrides) -> Result<Self> {
+        let mut config = Self::parse(path.as_ref(), overrides, &env::vars().collect())?;
+        if let Some(source) = config.pat_source()? {
+            config.personal_access_token = source.read_async().await?;
+        }
+        Self::validated(config)
+    }
+
+    /// The layered config before its PAT is read from a file or command
+    fn parse(path: &Path, overrides: &ConfigOverrides, env: &BTreeMap<String, String>) -> Result<Self> {
+        let config_str = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file: {:?}", path))?;
+
+        let mut value: toml::Value = toml::from_str(&config_str)
+            .with_context(|| format!("Failed to parse config file: {:?}", path))?;
+        let mut sources: BTreeMap<String, ConfigSource> = value.as_table()
+            .map(|table| table.keys().map(|key| (key.clone(), ConfigSource::File)).collect())
+            .unwrap_or_default();
         
-        // Process environment variables in the PAT value
-        if config.personal_access_token.starts_with("${") && config.personal_access_token.ends_with("}") {
-            // Extract the environment variable name
-            let env_var_name = &config.personal_access_token[2..config.personal_access_token.len()-1];
-            
-            // Get the value from environment variable
-            config.personal_access_token = env::var(env_var_name)
-                .with_context(|| format!("Environment variable {} not set", env_var_name))?;
+        let env_overrides = env.iter()
+            .filter(|(name, _)| name.starts_with(ENV_PREFIX) && name.len() > ENV_PREFIX.len());
+        let fields = config_fields();
+        for (name, raw) in env_overrides {
+            // FPR_RATE_LIMIT__BURST sets rate_limit.burst
+            let key = name[ENV_PREFIX.len()..].to_lowercase().replace("__", ".");
+            if !key.split('.').next().is_some_and(|field| fields.contains(&field)) {
+                warn!("⚠ Ignoring environment variable {}: {} is not a setting", name, key);
+                continue;
+            }
+            let parsed = parse_env_value(&key, raw);
+            set_value(&mut value, &mut sources, &key, parsed, ConfigSource::Env(name.clone()))?;
+        }
+        for (key, flag, override_value) in &overrides.values {
+            set_value(&mut value, &mut sources, key, override_value.clone(), ConfigSource::Cli(flag.clone()))?;
+        }
+        
+        // Expand ${VAR} and ${VAR:-default} in every string of the file
+        interpolate_value(&mut value, "", env)?;
+        
+        let mut config: AppConfig = value.try_into()
+            .with_context(|| format!("Failed to parse config file: {:?}", path))?;
+        
+        // The PAT is read from its file or command when it isn't given inline
+        if let Some(source) = config.pat_source()? {
+            if !config.personal_access_token.is_empty() {
+                return Err(anyhow::anyhow!("Set only one of personal_access_token, pat_file and pat_command"));
+            }
+            sources.insert("personal_access_token".to_string(), ConfigSource::PatSource(source.to_string()));
+        }
+        config.sources = sources;
+        Ok(config)
+    }
+
+    /// Register the config's secrets for redaction and check its settings
+    fn validated(config: Self) -> Result<Self> {
+        // Keep secrets out of everything the bot writes from here on
+        redact::register_pat(&config.personal_access_token);
+        for pattern in &config.redact_patterns {
+            redact::register_pattern(pattern)?;
+        }
+        if let Some(admin_api) = &config.admin_api {
+            redact::register_secret(&admin_api.token);
         }
         
         // Validate configuration
@@ -43,15 +330,122 @@ impl AppConfig {
         }
         
         if config.personal_access_token.is_empty() {
-            return Err(anyhow::anyhow!("Personal access token cannot be empty"));
+            return Err(anyhow::anyhow!("Personal access token cannot be empty; set personal_access_token, pat_file or pat_command"));
+        }
+        
+        if let Some(schedule) = &config.schedule {
+            Schedule::from_config(schedule).context("Invalid schedule")?;
+        }
+        
+        if let Some(polling) = &config.polling {
+            AdaptiveInterval::new(Duration::ZERO, Some(polling))?;
+        }
+        
+        if let Some(shutdown) = &config.shutdown {
+            parse_duration(&shutdown.timeout).context("Invalid shutdown.timeout")?;
+        }
+        
+        if config.max_concurrency == 0 {
+            return Err(anyhow::anyhow!("max_concurrency must be at least 1"));
+        }
+        
+        if let Some(rate_limit) = &config.rate_limit
+            && !(rate_limit.requests_per_second.is_finite() && rate_limit.requests_per_second > 0.0)
+        {
+            return Err(anyhow::anyhow!("rate_limit.requests_per_second must be a number greater than zero"));
+        }
+        
+        if let Some(admin_api) = &config.admin_api {
+            admin_api.validate()?;
+        }
+        
+        if let Some(chatops) = &config.chatops {
+            chatops.validate()?;
+        }
+        
+        if let Some(scripting) = &config.scripting {
+            scripting.validate()?;
+        }
+        for rule in &config.rules {
+            if let Some(delay) = &rule.approve_after {
+                delay.sample().with_context(|| format!("Invalid approve_after in rule '{}'", rule.name))?;
+            }
+            if let Some(hook) = &rule.hook {
+                hook.validate().with_context(|| format!("Invalid hook in rule '{}'", rule.name))?;
+            }
+            for script in &rule.scripts {
+                scripting::compile(Path::new(script)).with_context(|| format!("Invalid script in rule '{}'", rule.name))?;
+            }
         }
         
         
         Ok(config)
     }
     
-    pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
-        let config_str = toml::to_string(self)
+    /// Every setting of the config with where it came from, secrets redacted
+    pub fn effective_values(&self) -> Result<Vec<EffectiveValue>> {
+        let value = toml::Value::try_from(self).context("Failed to serialize config")?;
+        let mut values = Vec::new();
+        flatten_value(&value, "", &mut values);
+
+        Ok(values.into_iter()
+            .map(|(path, value)| {
+                // A PAT command's arguments may carry credentials of their own
+                let is_secret = path.rsplit('.').next()
+                    .is_some_and(|key| key == "pat_command" || ["token", "secret", "password"].iter().any(|word| key.contains(word)));
+                let is_set = match &value {
+                    toml::Value::String(value) => !value.is_empty(),
+                    toml::Value::Array(items) => !items.is_empty(),
+                    _ => false,
+                };
+                let value = if is_secret && is_set {
+                    toml::Value::String(REDACTED.to_string())
+                } else {
+                    value
+                };
+                let source = self.source_of(&path);
+                EffectiveValue { path, value: value.to_string(), source }
+            })
+            .collect())
+    }
+    
+    /// Source of a dotted path, from the closest enclosing setting that was given explicitly
+    fn source_of(&self, path: &str) -> ConfigSource {
+        let mut prefix = path;
+        loop {
+            if let Some(source) = self.sources.get(prefix) {
+                return source.clone();
+            }
+            match prefix.rfind(['.', '[']) {
+                Some(index) => prefix = &prefix[..index],
+                None => return ConfigSource::Default,
+            }
+        }
+    }
+    
+    /// Where the PAT is re-read from when it is rotated, if it isn't inline
+    pub fn pat_source(&self) -> Result<Option<PatSource>> {
+        match (&self.pat_file, &self.pat_command) {
+            (Some(_), Some(_)) => Err(anyhow::anyhow!("Set only one of pat_file and pat_command")),
+            (Some(file), None) => Ok(Some(PatSource::File(PathBuf::from(file)))),
+            (None, Some(command)) if command.is_empty() => Err(anyhow::anyhow!("pat_command cannot be empty")),
+            (None, Some(command)) => Ok(Some(PatSource::Command(command.clone()))),
+            (None, None) => Ok(None),
+        }
+    }
+    
+    /// Store the reviewer ID in the config file, leaving the rest of the file as written
+    pub fn save_reviewer_id<P: AsRef<Path>>(path: P, reviewer_id: &str) -> Result<()> {
+        let config_str = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read config file: {:?}", path.as_ref()))?;
+        
+        // Edit the raw file so ${VAR} references and PAT sources are kept instead of the secrets they resolve to
+        let mut value: toml::Value = toml::from_str(&config_str)
+            .with_context(|| format!("Failed to parse config file: {:?}", path.as_ref()))?;
+        let table = value.as_table_mut().context("Config file is not a TOML table")?;
+        table.insert("reviewer_id".to_string(), toml::Value::String(reviewer_id.to_string()));
+        
+        let config_str = toml::to_string(&value)
             .context("Failed to serialize config")?;
             
         fs::write(&path, config_str)
@@ -59,4 +453,498 @@ impl AppConfig {
             
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+/// Somewhere the PAT can be read from again
+#[derive(Debug, Clone)]
+pub enum PatSource {
+    File(PathBuf),
+    Command(Vec<String>),
+}
+
+impl PatSource {
+    /// Read the PAT, trimmed; errors never include the token itself. This blocks, so the running
+    /// bot uses `read_async` instead
+    pub fn read(&self) -> Result<String> {
+        let token = match self {
+            PatSource::File(path) => fs::read_to_string(path)
+                .with_context(|| format!("Failed to read PAT file {:?}", path))?,
+            PatSource::Command(command) => {
+                let output = std::process::Command::new(&command[0])
+                    .args(&command[1..])
+                    .stdin(std::process::Stdio::null())
+                    .stderr(std::process::Stdio::inherit())
+                    .output()
+                    .with_context(|| format!("Failed to run PAT command '{}'", command[0]))?;
+                self.command_output(output)?
+            }
+        };
+
+        self.trimmed(token)
+    }
+
+    /// Read the PAT without blocking the runtime; a PAT command is killed after PAT_COMMAND_TIMEOUT
+    pub async fn read_async(&self) -> Result<String> {
+        let token = match self {
+            PatSource::File(path) => tokio::fs::read_to_string(path).await
+                .with_context(|| format!("Failed to read PAT file {:?}", path))?,
+            PatSource::Command(command) => {
+                let output = tokio::process::Command::new(&command[0])
+                    .args(&command[1..])
+                    .stdin(std::process::Stdio::null())
+                    .stderr(std::process::Stdio::inherit())
+                    .kill_on_drop(true)
+                    .output();
+                let output = tokio::time::timeout(PAT_COMMAND_TIMEOUT, output).await
+                    .map_err(|_| anyhow::anyhow!("PAT command '{}' timed out after {:?}", command[0], PAT_COMMAND_TIMEOUT))?
+                    .with_context(|| format!("Failed to run PAT command '{}'", command[0]))?;
+                self.command_output(output)?
+            }
+        };
+
+        self.trimmed(token)
+    }
+
+    fn command_output(&self, output: std::process::Output) -> Result<String> {
+        if !output.status.success() {
+            return Err(anyhow::anyhow!("{} failed with {}", self, output.status));
+        }
+        String::from_utf8(output.stdout)
+            .map_err(|_| anyhow::anyhow!("{} did not print valid UTF-8", self))
+    }
+
+    fn trimmed(&self, token: String) -> Result<String> {
+        let token = token.trim().to_string();
+        if token.is_empty() {
+            return Err(anyhow::anyhow!("{} gave an empty PAT", self));
+        }
+        Ok(token)
+    }
+}
+
+impl std::fmt::Display for PatSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PatSource::File(path) => write!(f, "PAT file {:?}", path),
+            PatSource::Command(command) => write!(f, "PAT command '{}'", command[0]),
+        }
+    }
+}
+
+/// Parse an environment value as TOML (numbers, booleans, arrays, inline tables), falling back to a plain string
+fn parse_env_value(key: &str, raw: &str) -> toml::Value {
+    if STRING_FIELDS.contains(&key) {
+        return toml::Value::String(raw.to_string());
+    }
+
+    toml::from_str::<toml::Table>(&format!("value = {}", raw)).ok()
+        .and_then(|mut table| table.remove("value"))
+        .unwrap_or_else(|| toml::Value::String(raw.to_string()))
+}
+
+/// Set a dotted path in the config, recording where the value came from
+fn set_value(
+    config: &mut toml::Value,
+    sources: &mut BTreeMap<String, ConfigSource>,
+    key: &str,
+    value: toml::Value,
+    source: ConfigSource,
+) -> Result<()> {
+    let mut table = config.as_table_mut().context("Config file is not a TOML table")?;
+    let (parents, field) = match key.rsplit_once('.') {
+        Some((parents, field)) => (Some(parents), field),
+        None => (None, key),
+    };
+
+    if parents.is_none() && PAT_FIELDS.contains(&field) {
+        for other in PAT_FIELDS.iter().filter(|other| **other != field) {
+            if sources.get(*other).is_some_and(|other_source| other_source.precedence() < source.precedence()) {
+                table.remove(*other);
+                sources.remove(*other);
+            }
+        }
+    }
+
+    for parent in parents.into_iter().flat_map(|parents| parents.split('.')) {
+        table = table.entry(parent)
+            .or_insert_with(|| toml::Value::Table(toml::Table::new()))
+            .as_table_mut()
+            .with_context(|| format!("Cannot set {} from {}: {} is not a table", key, source, parent))?;
+    }
+    table.insert(field.to_string(), value);
+    sources.insert(key.to_string(), source);
+    Ok(())
+}
+
+/// Flatten a TOML value into dotted paths; arrays of tables are indexed, e.g. rules[0].name
+fn flatten_value(value: &toml::Value, path: &str, values: &mut Vec<(String, toml::Value)>) {
+    match value {
+        toml::Value::Table(table) => {
+            for (key, item) in table {
+                let path = if path.is_empty() { key.clone() } else { format!("{}.{}", path, key) };
+                flatten_value(item, &path, values);
+            }
+        }
+        toml::Value::Array(array) if array.iter().any(toml::Value::is_table) => {
+            for (index, item) in array.iter().enumerate() {
+                flatten_value(item, &format!("{}[{}]", path, index), values);
+            }
+        }
+        _ => values.push((path.to_string(), value.clone())),
+    }
+}
+
+/// Top-level settings of AppConfig, as its Deserialize impl names them; an FPR_ variable naming anything else
+/// is ignored with a warning
+fn config_fields() -> &'static [&'static str] {
+    // A deserializer that only records the fields it is asked for
+    struct FieldNames<'a>(&'a mut &'static [&'static str]);
+
+    impl<'de> serde::Deserializer<'de> for FieldNames<'_> {
+        type Error = serde::de::value::Error;
+
+        fn deserialize_any<V: serde::de::Visitor<'de>>(self, _visitor: V) -> std::result::Result<V::Value, Self::Error> {
+            Err(serde::de::Error::custom("not a struct"))
+        }
+
+        fn deserialize_struct<V: serde::de::Visitor<'de>>(
+            self,
+            _name: &'static str,
+            fields: &'static [&'static str],
+            _visitor: V,
+        ) -> std::result::Result<V::Value, Self::Error> {
+            *self.0 = fields;
+            Err(serde::de::Error::custom("only the field names are wanted"))
+        }
+
+        serde::forward_to_deserialize_any! {
+            bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf option unit
+            unit_struct newtype_struct seq tuple tuple_struct map enum identifier ignored_any
+        }
+    }
+
+    let mut fields: &'static [&'static str] = &[];
+    let _ = AppConfig::deserialize(FieldNames(&mut fields));
+    fields
+}
+
+/// Expand ${VAR} and ${VAR:-default} in every string inside a TOML value
+fn interpolate_value(value: &mut toml::Value, path: &str, env: &BTreeMap<String, String>) -> Result<()> {
+    match value {
+        toml::Value::String(string) => {
+            // Errors name the setting rather than its value, which may be a secret
+            *string = interpolate(string, env).with_context(|| format!("Invalid value for {}", path))?;
+        }
+        toml::Value::Array(array) => {
+            for (index, item) in array.iter_mut().enumerate() {
+                interpolate_value(item, &format!("{}[{}]", path, index), env)?;
+            }
+        }
+        toml::Value::Table(table) => {
+            for (key, item) in table.iter_mut() {
+                let path = if path.is_empty() { key.clone() } else { format!("{}.{}", path, key) };
+                interpolate_value(item, &path, env)?;
+            }
+        }
+        _ => (),
+    }
+    Ok(())
+}
+
+/// Expand ${VAR} and ${VAR:-default} in a string with the variables in `env`; the default applies when VAR
+/// is unset or empty
+pub fn interpolate(value: &str, env: &BTreeMap<String, String>) -> Result<String> {
+    let mut result = String::with_capacity(value.len());
+    let mut rest = value;
+
+    while let Some(start) = rest.find("${") {
+        result.push_str(&rest[..start]);
+        let end = rest[start..].find('}')
+            .context("Unterminated ${ in value")?;
+        let expression = &rest[start + 2..start + end];
+
+        let (name, default) = match expression.split_once(":-") {
+            Some((name, default)) => (name, Some(default)),
+            None => (expression, None),
+        };
+        if name.is_empty() {
+            return Err(anyhow::anyhow!("Empty variable name in ${{}}"));
+        }
+
+        match (env.get(name).filter(|value| !value.is_empty()), default) {
+            (Some(value), _) => result.push_str(value),
+            (None, Some(default)) => result.push_str(default),
+            (None, None) => return Err(anyhow::anyhow!("Environment variable {} not set", name)),
+        }
+        rest = &rest[start + end + 1..];
+    }
+
+    result.push_str(rest);
+    Ok(result)
+}
+
+/// Top-level config fields that differ between two configs, in alphabetical order
+pub fn changed_fields(old: &AppConfig, new: &AppConfig) -> Result<Vec<String>> {
+    let old = toml::Value::try_from(old).context("Failed to serialize config")?;
+    let new = toml::Value::try_from(new).context("Failed to serialize config")?;
+    let (Some(old), Some(new)) = (old.as_table(), new.as_table()) else {
+        return Ok(Vec::new());
+    };
+
+    let keys: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+    Ok(keys.into_iter()
+        .filter(|key| old.get(*key) != new.get(*key))
+        .cloned()
+        .collect())
+}
+
+/// Notices edits to the config file by checking its modification time
+pub struct ConfigWatcher {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+}
+
+impl ConfigWatcher {
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        let path = path.as_ref().to_path_buf();
+        let modified = Self::modified(&path);
+        Self { path, modified }
+    }
+
+    /// Whether the file was modified since the last check
+    pub fn changed(&mut self) -> bool {
+        // A missing file is usually an editor halfway through saving; wait for it to come back
+        let Some(modified) = Self::modified(&self.path) else {
+            return false;
+        };
+        if self.modified == Some(modified) {
+            return false;
+        }
+        self.modified = Some(modified);
+        true
+    }
+
+    /// Take the file as it is now as seen, e.g. after it was reloaded for another reason
+    pub fn mark_seen(&mut self) {
+        self.modified = Self::modified(&self.path);
+    }
+
+    fn modified(path: &Path) -> Option<SystemTime> {
+        fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+    }
+}
+
+fn default_approval_queue() -> String {
+    "approval_queue.json".to_string()
+}
+
+fn default_max_concurrency() -> usize {
+    4
+}
+
+/// Parse a duration such as "90s", "5m", "2h" or "1d"; a bare number is seconds
+pub fn parse_duration(value: &str) -> Result<Duration> {
+    let value = value.trim();
+    let split = value.find(|c: char| !c.is_ascii_digit()).unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+
+    let number: u64 = number.parse()
+        .with_context(|| format!("Invalid duration '{}'", value))?;
+    let unit_seconds: u64 = match unit.trim() {
+        "" | "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 60 * 60 * 24,
+        other => return Err(anyhow::anyhow!("Unknown duration unit '{}' in '{}'", other, value)),
+    };
+    let seconds = number.checked_mul(unit_seconds)
+        .with_context(|| format!("Duration '{}' is too long", value))?;
+
+    Ok(Duration::from_secs(seconds))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Load `body` as a config file named after the test
+    fn load(name: &str, body: &str) -> Result<AppConfig> {
+        let path = env::temp_dir().join(format!("fpr-config-test-{}-{}.toml", name, std::process::id()));
+        fs::write(&path, body).unwrap();
+        let config = AppConfig::from_file(&path, &ConfigOverrides::default());
+        fs::remove_file(&path).ok();
+        config
+    }
+
+    const BASE: &str = r#"
+organization = "contoso"
+project = "web"
+personal_access_token = "secret"
+"#;
+
+    #[test]
+    fn rate_limit_must_be_a_positive_number() {
+        for (name, rate) in [("zero", "0.0"), ("negative", "-1.0"), ("nan", "nan"), ("inf", "inf")] {
+            let body = format!("{}\n[rate_limit]\nrequests_per_second = {}\n", BASE, rate);
+            let error = load(&format!("rate-{}", name), &body).unwrap_err();
+            assert!(error.to_string().contains("requests_per_second"), "{}: {}", rate, error);
+        }
+
+        let body = format!("{}\n[rate_limit]\nrequests_per_second = 2.5\n", BASE);
+        assert_eq!(load("rate-ok", &body).unwrap().rate_limit.unwrap().requests_per_second, 2.5);
+    }
+
+    #[test]
+    fn variables_are_interpolated_with_defaults() {
+        let env = BTreeMap::from([
+            ("SET".to_string(), "contoso".to_string()),
+            ("EMPTY".to_string(), String::new()),
+        ]);
+        let interpolate = |value: &str| interpolate(value, &env);
+
+        assert_eq!(interpolate("org ${SET}!").unwrap(), "org contoso!");
+        assert_eq!(interpolate("${SET:-other}").unwrap(), "contoso");
+        assert_eq!(interpolate("${UNSET:-other}").unwrap(), "other");
+        assert_eq!(interpolate("${EMPTY:-other}").unwrap(), "other");
+        assert_eq!(interpolate("${UNSET:-}").unwrap(), "");
+        assert_eq!(interpolate("no variables").unwrap(), "no variables");
+
+        assert!(interpolate("${UNSET}").unwrap_err().to_string().contains("UNSET"));
+        assert!(interpolate("${SET").is_err());
+        assert!(interpolate("${}").is_err());
+
+        // Every string of the file is interpolated, with the variables given to the parser
+        let path = env::temp_dir().join(format!("fpr-config-test-interpolate-{}.toml", std::process::id()));
+        fs::write(&path, "organization = \"${SET}\"\nproject = \"${UNSET:-web}\"\npersonal_access_token = \"secret\"\n").unwrap();
+        let config = AppConfig::from_file_with_env(&path, &ConfigOverrides::default(), &env);
+        fs::remove_file(&path).ok();
+        let config = config.unwrap();
+        assert_eq!((config.organization.as_str(), config.project.as_str()), ("contoso", "web"));
+    }
+
+    #[test]
+    fn pat_is_read_trimmed_from_its_file() {
+        let pat_path = env::temp_dir().join(format!("fpr-config-test-pat-{}", std::process::id()));
+        fs::write(&pat_path, "  secret-from-file\n\n").unwrap();
+
+        let body = format!("organization = \"contoso\"\nproject = \"web\"\npat_file = {:?}\n", pat_path.display().to_string());
+        let config = load("pat-file", &body).unwrap();
+        assert_eq!(config.personal_access_token, "secret-from-file");
+
+        let inline_too = format!("{}pat_file = {:?}\n", BASE, pat_path.display().to_string());
+        assert!(load("pat-both", &inline_too).unwrap_err().to_string().contains("only one"));
+
+        fs::write(&pat_path, " \n").unwrap();
+        assert!(load("pat-empty", &body).unwrap_err().to_string().contains("empty PAT"));
+        fs::remove_file(&pat_path).ok();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn pat_command_is_read_without_blocking() {
+        let source = PatSource::Command(vec!["sh".to_string(), "-c".to_string(), "echo ' from-command '".to_string()]);
+        assert_eq!(source.read_async().await.unwrap(), "from-command");
+        assert_eq!(source.read().unwrap(), "from-command");
+
+        let failing = PatSource::Command(vec!["sh".to_string(), "-c".to_string(), "exit 2".to_string()]);
+        assert!(failing.read_async().await.unwrap_err().to_string().contains("failed with"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn pat_command_is_run_with_a_timeout_and_hidden_from_config_show() {
+        let path = env::temp_dir().join(format!("fpr-config-test-pat-command-{}.toml", std::process::id()));
+        fs::write(&path, "organization = \"contoso\"\nproject = \"web\"\npat_command = [\"sh\", \"-c\", \"echo secret-from-command\"]\n").unwrap();
+        let config = AppConfig::from_file_async(&path, &ConfigOverrides::default()).await.unwrap();
+        fs::remove_file(&path).ok();
+        assert_eq!(config.personal_access_token, "secret-from-command");
+
+        let values = config.effective_values().unwrap();
+        let value = |path: &str| values.iter().find(|value| value.path == path).unwrap();
+        assert_eq!(value("pat_command").value, format!("{:?}", REDACTED));
+        let shown: Vec<&str> = values.iter().map(|value| value.value.as_str()).collect();
+        assert!(shown.iter().all(|value| !value.contains("secret")), "{:?}", shown);
+    }
+
+    #[test]
+    fn cli_beats_environment_beats_file() {
+        let env = BTreeMap::from([
+            ("FPR_MAX_CONCURRENCY".to_string(), "5".to_string()),
+            ("FPR_DECISION_LOG".to_string(), "env-decisions.jsonl".to_string()),
+            ("FPR_NOT_A_SETTING".to_string(), "ignored".to_string()),
+        ]);
+        let path = env::temp_dir().join(format!("fpr-config-test-precedence-{}.toml", std::process::id()));
+        fs::write(&path, format!("{}max_concurrency = 2\ndecision_log = \"file-decisions.jsonl\"\napproval_queue = \"file-queue.json\"\n", BASE)).unwrap();
+
+        let mut overrides = ConfigOverrides::default();
+        overrides.set("max_concurrency", "--max-concurrency", 9);
+        let config = AppConfig::from_file_with_env(&path, &overrides, &env);
+        let env_only = AppConfig::from_file_with_env(&path, &ConfigOverrides::default(), &env);
+        fs::remove_file(&path).ok();
+        let (config, env_only) = (config.unwrap(), env_only.unwrap());
+
+        assert_eq!(config.max_concurrency, 9);
+        assert_eq!(config.sources["max_concurrency"], ConfigSource::Cli("--max-concurrency".to_string()));
+        assert_eq!(env_only.max_concurrency, 5);
+        assert_eq!(env_only.sources["max_concurrency"], ConfigSource::Env("FPR_MAX_CONCURRENCY".to_string()));
+        assert_eq!(config.decision_log.as_deref(), Some("env-decisions.jsonl"));
+        assert_eq!(config.approval_queue, "file-queue.json");
+        assert_eq!(config.sources["approval_queue"], ConfigSource::File);
+        assert!(!config.sources.contains_key("not_a_setting"));
+    }
+
+    #[test]
+    fn every_setting_can_be_set_from_the_environment() {
+        let fields = config_fields();
+        assert!(fields.contains(&"organization") && fields.contains(&"scripting"), "{:?}", fields);
+        assert!(!fields.contains(&"sources"));
+
+        // Every field the config serializes is one the environment can set
+        let config = load("fields", &format!("{}[shutdown]\n[chatops]\n", BASE)).unwrap();
+        let value = toml::Value::try_from(&config).unwrap();
+        for key in value.as_table().unwrap().keys() {
+            assert!(fields.contains(&key.as_str()), "{} is missing", key);
+        }
+    }
+
+    #[test]
+    fn config_show_redacts_secrets() {
+        let body = format!("{}[admin_api]\nlisten = \"127.0.0.1:8787\"\ntoken = \"admin-secret\"\n", BASE);
+        let values = load("show", &body).unwrap().effective_values().unwrap();
+        let value = |path: &str| values.iter().find(|value| value.path == path).unwrap();
+
+        assert_eq!(value("personal_access_token").value, format!("{:?}", REDACTED));
+        assert_eq!(value("admin_api.token").value, format!("{:?}", REDACTED));
+        assert_eq!(value("organization").value, "\"contoso\"");
+        assert_eq!(value("organization").source, ConfigSource::File);
+        let shown: Vec<&str> = values.iter().map(|value| value.value.as_str()).collect();
+        assert!(shown.iter().all(|value| !value.contains("secret")), "{:?}", shown);
+    }
+
+    #[test]
+    fn durations_parse_with_units_and_reject_overflow() {
+        assert_eq!(parse_duration("90").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse_duration(" 45s ").unwrap(), Duration::from_secs(45));
+        assert_eq!(parse_duration("5m").unwrap(), Duration::from_secs(300));
+        assert_eq!(parse_duration("2h").unwrap(), Duration::from_secs(7200));
+        assert_eq!(parse_duration("1d").unwrap(), Duration::from_secs(86400));
+
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("m").is_err());
+        assert!(parse_duration("-5m").is_err());
+        assert!(parse_duration("5w").unwrap_err().to_string().contains("Unknown duration unit"));
+        assert!(parse_duration(&format!("{}d", u64::MAX / 1000)).unwrap_err().to_string().contains("too long"));
+        assert!(parse_duration(&format!("{}s", u64::MAX)).is_ok());
+    }
+
+    #[test]
+    fn admin_api_only_listens_locally() {
+        let config = |listen: &str| AdminApiConfig { listen: listen.to_string(), token: "secret".to_string() };
+
+        assert!(config("127.0.0.1:8787").validate().is_ok());
+        assert!(config("[::1]:8787").validate().is_ok());
+        assert!(config("0.0.0.0:8787").validate().is_err());
+        assert!(config("localhost").validate().is_err());
+        assert!(AdminApiConfig { token: " ".to_string(), ..config("127.0.0.1:8787") }.validate().is_err());
+    }
+}
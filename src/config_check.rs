@@ -0,0 +1,124 @@
+use anyhow::Result;
+
+use fast_pr_reviewer::{AppConfig, AzureDevOpsClient};
+
+/// Result of one line of the checklist
+enum CheckStatus {
+    Passed,
+    Warning,
+    Failed,
+}
+
+/// Collects and prints the checklist as it runs
+#[derive(Default)]
+struct Checklist {
+    passed: usize,
+    warnings: usize,
+    failed: usize,
+}
+
+impl Checklist {
+    fn add(&mut self, status: CheckStatus, message: impl AsRef<str>) {
+        let icon = match status {
+            CheckStatus::Passed => {
+                self.passed += 1;
+                "✅"
+            }
+            CheckStatus::Warning => {
+                self.warnings += 1;
+                "⚠️ "
+            }
+            CheckStatus::Failed => {
+                self.failed += 1;
+                "❌"
+            }
+        };
+        println!("{} {}", icon, message.as_ref());
+    }
+}
+
+/// Check the configuration against Azure DevOps, printing a checklist; fails if any check failed
+pub async fn run(client: &AzureDevOpsClient, config: &AppConfig) -> Result<()> {
+    let mut checklist = Checklist::default();
+    println!("Checking configuration for organization '{}', project '{}'", config.organization, config.project);
+
+    // Nothing else can be checked without a working PAT
+    match client.get_connection_data().await {
+        Ok(connection) => {
+            checklist.add(CheckStatus::Passed, format!("PAT authenticates as {}", connection.authenticated_user.display_name()));
+        }
+        Err(e) => {
+            checklist.add(CheckStatus::Failed, format!("PAT does not authenticate against the organization: {:#}", e));
+            return finish(checklist);
+        }
+    }
+
+    let project_id = match client.get_project().await {
+        Ok(project) => {
+            checklist.add(CheckStatus::Passed, format!("Project '{}' exists ({})", project.name, project.id));
+            Some(project.id)
+        }
+        Err(e) => {
+            checklist.add(CheckStatus::Failed, format!("Project '{}' not found: {:#}", config.project, e));
+            None
+        }
+    };
+
+    match client.get_repositories().await {
+        Ok(repositories) => {
+            checklist.add(CheckStatus::Passed, format!("PAT can read Code ({} repositories)", repositories.len()));
+            let repository = repositories.first().and_then(|repository| {
+                let project_id = repository.project.as_ref().map(|project| project.id.clone()).or_else(|| project_id.clone())?;
+                Some((project_id, repository))
+            });
+            match repository {
+                Some((project_id, repository)) => match client.can_vote(&project_id, &repository.id).await {
+                    Ok(true) => checklist.add(CheckStatus::Passed, "PAT can vote on pull requests"),
+                    Ok(false) => checklist.add(CheckStatus::Failed, "PAT cannot vote on pull requests; it needs the Code (Read & write) scope"),
+                    Err(e) => checklist.add(CheckStatus::Failed, format!("Could not check vote permission: {:#}", e)),
+                },
+                None => checklist.add(CheckStatus::Warning, "No repositories to check vote permission against"),
+            }
+        }
+        Err(e) => checklist.add(CheckStatus::Failed, format!("PAT cannot read Code; it needs the Code (Read & write) scope: {:#}", e)),
+    }
+
+    if config.watched_users.is_empty() {
+        checklist.add(CheckStatus::Warning, "No watched users configured");
+    }
+    for name in &config.watched_users {
+        // Watched users are matched on the exact display name of the PR author
+        match client.search_identities(name).await {
+            Ok(identities) => {
+                let matches: Vec<_> = identities.iter().filter(|identity| identity.display_name() == name).collect();
+                match matches.as_slice() {
+                    [] => checklist.add(CheckStatus::Failed, format!("Watched user '{}' does not match any identity", name)),
+                    [identity] => checklist.add(CheckStatus::Passed, format!("Watched user '{}' is {}", name, identity.id)),
+                    _ => checklist.add(CheckStatus::Failed, format!("Watched user '{}' is ambiguous, {} identities share that name: {}",
+                        name, matches.len(), matches.iter().map(|identity| identity.id.as_str()).collect::<Vec<_>>().join(", "))),
+                }
+            }
+            Err(e) => checklist.add(CheckStatus::Failed, format!("Could not look up watched user '{}': {:#}", name, e)),
+        }
+    }
+
+    match &config.reviewer_id {
+        Some(reviewer_id) => match client.get_identity(reviewer_id).await {
+            Ok(Some(identity)) => checklist.add(CheckStatus::Passed, format!("reviewer_id belongs to {}", identity.display_name())),
+            Ok(None) => checklist.add(CheckStatus::Failed, format!("reviewer_id {} is not a known identity", reviewer_id)),
+            Err(e) => checklist.add(CheckStatus::Failed, format!("Could not look up reviewer_id {}: {:#}", reviewer_id, e)),
+        },
+        None => checklist.add(CheckStatus::Warning, "reviewer_id is not set; the bot will ask for it on its first run"),
+    }
+
+    finish(checklist)
+}
+
+fn finish(checklist: Checklist) -> Result<()> {
+    println!("\n{} passed, {} warnings, {} failed", checklist.passed, checklist.warnings, checklist.failed);
+
+    if checklist.failed > 0 {
+        return Err(anyhow::anyhow!("{} configuration checks failed", checklist.failed));
+    }
+    Ok(())
+}
@@ -0,0 +1,46 @@
+use anyhow::Result;
+
+use crate::ado_client::AzureDevOpsClient;
+use crate::decisions::Decision;
+use crate::models::PullRequest;
+use crate::ReviewerState;
+
+/// Approve a PR right away on someone's request, regardless of rules and schedules
+pub async fn force_approve(
+    client: &AzureDevOpsClient,
+    state: &ReviewerState,
+    pr: &PullRequest,
+    reviewer_id: &str,
+    requested_by: &str,
+) -> Result<()> {
+    let pr_id = pr.pull_request_id;
+    // No rule asks for a required reviewer here, but a bot that already is one stays required
+    match client.approve_pull_request(pr, reviewer_id, false).await {
+        Ok(_) => {
+            state.seen_prs.lock().await.insert(pr_id);
+            state.blocked_prs.lock().await.remove(&pr_id);
+            state.policy_blocks.lock().await.remove(&pr_id);
+            state.queued_prs.lock().await.remove(&pr_id);
+            state.approval_queue.lock().await.cancel(pr_id);
+            state.decisions.record(Decision::new(pr, None, "approve", "approved", format!("requested by {}", requested_by)));
+            Ok(())
+        }
+        Err(e) => {
+            state.decisions.record(Decision::new(pr, None, "approve", "failed", e.to_string()));
+            Err(e)
+        }
+    }
+}
+
+/// Stop handling a PR: it won't be approved, retried or completed by the bot
+pub async fn skip(state: &ReviewerState, pr: &PullRequest, requested_by: &str) {
+    let pr_id = pr.pull_request_id;
+    state.seen_prs.lock().await.insert(pr_id);
+    state.blocked_prs.lock().await.remove(&pr_id);
+    state.policy_blocks.lock().await.remove(&pr_id);
+    state.pending_completions.lock().await.remove(&pr_id);
+    state.recheck_prs.lock().await.remove(&pr_id);
+    state.queued_prs.lock().await.remove(&pr_id);
+    state.approval_queue.lock().await.cancel(pr_id);
+    state.decisions.record(Decision::new(pr, None, "approve", "skipped", format!("requested by {}", requested_by)));
+}
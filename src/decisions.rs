@@ -0,0 +1,118 @@
+use chrono::{DateTime, Utc};
+use tracing::{info, warn};
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::models::PullRequest;
+use crate::redact;
+
+// Number of decisions kept in memory for inspection
+const MAX_RECENT_DECISIONS: usize = 200;
+
+/// A single decision the bot made about a pull request
+#[derive(Debug, Clone, Serialize)]
+pub struct Decision {
+    pub timestamp: DateTime<Utc>,
+    pub pr_id: i32,
+    pub repository: String,
+    pub author: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rule: Option<String>,
+    /// What the bot tried to do, e.g. "approve" or "complete"
+    pub action: String,
+    /// How it went, e.g. "approved", "completed" or "conflicts"
+    pub outcome: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub detail: String,
+}
+
+impl Decision {
+    pub fn new(pr: &PullRequest, rule: Option<&str>, action: &str, outcome: &str, detail: impl Into<String>) -> Self {
+        Self {
+            timestamp: Utc::now(),
+            pr_id: pr.pull_request_id,
+            repository: pr.repository.name.clone(),
+            author: pr.created_by.display_name.clone(),
+            rule: rule.map(str::to_string),
+            action: action.to_string(),
+            outcome: outcome.to_string(),
+            detail: detail.into(),
+        }
+    }
+}
+
+/// Called with every decision once it was logged
+pub type DecisionCallback = Box<dyn Fn(&Decision) + Send + Sync>;
+
+/// Keeps the most recent decisions in memory and optionally appends them to a JSON lines file
+pub struct DecisionLog {
+    path: Option<PathBuf>,
+    recent: Mutex<VecDeque<Decision>>,
+    // Decisions per outcome since startup, including those no longer in `recent`
+    outcome_counts: Mutex<HashMap<String, usize>>,
+    subscribers: Mutex<Vec<DecisionCallback>>,
+}
+
+impl DecisionLog {
+    pub fn new(path: Option<PathBuf>) -> Self {
+        Self {
+            path,
+            recent: Mutex::new(VecDeque::new()),
+            outcome_counts: Mutex::new(HashMap::new()),
+            subscribers: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn record(&self, mut decision: Decision) {
+        // Details often hold error messages, which must not carry secrets into the log file
+        decision.detail = redact::redact(&decision.detail);
+
+        info!(pr_id = decision.pr_id, repo = %decision.repository, author = %decision.author, rule = decision.rule.as_deref(),
+            operation = %decision.action, outcome = %decision.outcome,
+            "📝 Decision for PR #{}: {} -> {}{}", decision.pr_id, decision.action, decision.outcome,
+            if decision.detail.is_empty() { String::new() } else { format!(" ({})", decision.detail) });
+
+        if let Some(path) = &self.path
+            && let Err(e) = Self::append_to_file(path, &decision)
+        {
+            warn!("Failed to write decision to {:?}: {}", path, e);
+        }
+
+        *self.outcome_counts.lock().unwrap().entry(decision.outcome.clone()).or_default() += 1;
+
+        for subscriber in self.subscribers.lock().unwrap().iter() {
+            subscriber(&decision);
+        }
+
+        let mut recent = self.recent.lock().unwrap();
+        if recent.len() == MAX_RECENT_DECISIONS {
+            recent.pop_front();
+        }
+        recent.push_back(decision);
+    }
+
+    /// Call `callback` with every decision recorded from now on
+    pub fn subscribe(&self, callback: impl Fn(&Decision) + Send + Sync + 'static) {
+        self.subscribers.lock().unwrap().push(Box::new(callback));
+    }
+
+    /// Most recent decisions, oldest first
+    pub fn recent(&self) -> Vec<Decision> {
+        self.recent.lock().unwrap().iter().cloned().collect()
+    }
+
+    /// Number of decisions with the given outcome since startup
+    pub fn count(&self, outcome: &str) -> usize {
+        self.outcome_counts.lock().unwrap().get(outcome).copied().unwrap_or(0)
+    }
+
+    fn append_to_file(path: &PathBuf, decision: &Decision) -> anyhow::Result<()> {
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        writeln!(file, "{}", serde_json::to_string(decision)?)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,191 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::process::Stdio;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tokio::time;
+use tracing::{debug, warn};
+
+use crate::config::parse_duration;
+use crate::models::{FileChange, PullRequest, Reviewer};
+
+/// An external command that decides what happens to a PR, run with the PR as JSON on stdin
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct DecisionHook {
+    /// Program and its arguments, e.g. ["python3", "hooks/review.py"]
+    pub command: Vec<String>,
+    /// The command is killed when it runs longer than this, which skips the PR
+    #[serde(default = "default_hook_timeout")]
+    pub timeout: String,
+}
+
+fn default_hook_timeout() -> String {
+    "30s".to_string()
+}
+
+impl DecisionHook {
+    pub fn validate(&self) -> Result<()> {
+        if self.command.first().is_none_or(|program| program.is_empty()) {
+            return Err(anyhow::anyhow!("command must name a program"));
+        }
+        parse_duration(&self.timeout)?;
+        Ok(())
+    }
+}
+
+/// What the hook wants the bot to do with the PR
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HookVote {
+    /// Carry on with the approval, including any approve_after delay
+    Approve,
+    /// Vote wait-for-author; the hook runs again once the PR is pushed to
+    WaitForAuthor,
+    /// Vote reject; the hook runs again once the PR is pushed to
+    Reject,
+    /// Leave the PR alone
+    Skip,
+}
+
+impl HookVote {
+    /// Parse a vote as written in a decision, e.g. "wait_for_author"
+    pub fn parse(vote: &str) -> Option<Self> {
+        serde_json::from_value(serde_json::Value::String(vote.to_string())).ok()
+    }
+}
+
+/// Decision printed by the hook as JSON on stdout, or returned by a policy script
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct HookDecision {
+    pub vote: HookVote,
+    /// Comment left on the PR
+    #[serde(default)]
+    pub comment: Option<String>,
+    /// Why the hook decided this, for the log and the decision log
+    #[serde(default)]
+    pub reason: Option<String>,
+    /// The hook couldn't decide, e.g. it timed out, which skips the PR like an explicit skip
+    #[serde(skip)]
+    pub failed: bool,
+}
+
+impl HookDecision {
+    /// No decision because the hook or script failed; the PR is skipped
+    pub fn failed(reason: String) -> Self {
+        Self { vote: HookVote::Skip, comment: None, reason: Some(reason), failed: true }
+    }
+
+    pub fn reason(&self) -> &str {
+        self.reason.as_deref().unwrap_or("no reason given")
+    }
+}
+
+/// Everything the hook gets on stdin
+#[derive(Serialize)]
+struct HookInput<'a> {
+    pull_request: &'a PullRequest,
+    reviewers: &'a [Reviewer],
+    changes: &'a [FileChange],
+}
+
+/// JSON the hook gets on stdin for a PR
+pub fn input(pr: &PullRequest, reviewers: &[Reviewer], changes: &[FileChange]) -> Result<Vec<u8>> {
+    Ok(serde_json::to_vec(&HookInput { pull_request: pr, reviewers, changes })?)
+}
+
+/// Run the hook with `input` on stdin and parse the decision it prints
+pub async fn run(hook: &DecisionHook, input: &[u8]) -> HookDecision {
+    let Some((program, args)) = hook.command.split_first() else {
+        return HookDecision::failed("hook has no command".to_string());
+    };
+    let timeout = match parse_duration(&hook.timeout) {
+        Ok(timeout) => timeout,
+        Err(e) => return HookDecision::failed(format!("invalid hook timeout: {}", e)),
+    };
+
+    let mut child = match Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        // Dropping the child on timeout kills it
+        .kill_on_drop(true)
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(e) => return HookDecision::failed(format!("failed to start hook {}: {}", program, e)),
+    };
+
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    let write_input = async move {
+        // A hook that doesn't read its input closes the pipe early; that's its business
+        if let Err(e) = stdin.write_all(input).await {
+            debug!("Hook {} did not read all of its input: {}", program, e);
+        }
+    };
+
+    let output = match time::timeout(timeout, async { tokio::join!(write_input, child.wait_with_output()).1 }).await {
+        Ok(Ok(output)) => output,
+        Ok(Err(e)) => return HookDecision::failed(format!("failed to run hook {}: {}", program, e)),
+        Err(_) => return HookDecision::failed(format!("hook {} timed out after {}", program, hook.timeout)),
+    };
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !stderr.trim().is_empty() {
+        debug!("Hook {} wrote to stderr: {}", program, stderr.trim());
+    }
+    if !output.status.success() {
+        warn!("⚠ Hook {} failed with {}: {}", program, output.status, stderr.trim());
+        return HookDecision::failed(format!("hook {} failed with {}", program, output.status));
+    }
+
+    match serde_json::from_slice(&output.stdout) {
+        Ok(decision) => decision,
+        Err(e) => HookDecision::failed(format!("hook {} printed an invalid decision: {}", program, e)),
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn shell_hook(script: &str, timeout: &str) -> DecisionHook {
+        DecisionHook {
+            command: vec!["sh".to_string(), "-c".to_string(), script.to_string()],
+            timeout: timeout.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn decision_is_read_from_stdout_and_failures_skip() {
+        // The hook sees the PR on stdin
+        let hook = shell_hook(r#"grep -q '"title":"Bump serde"' && echo '{"vote":"reject","comment":"No bumps","reason":"bump"}'"#, "10s");
+        let decision = run(&hook, br#"{"pull_request":{"title":"Bump serde"}}"#).await;
+        assert_eq!(decision, HookDecision {
+            vote: HookVote::Reject,
+            comment: Some("No bumps".to_string()),
+            reason: Some("bump".to_string()),
+            failed: false,
+        });
+
+        // An explicit skip is a decision; a failed run skips as well, but is logged as a failure
+        let skipped = run(&shell_hook(r#"echo '{"vote":"skip","reason":"not mine"}'"#, "10s"), b"{}").await;
+        assert_eq!(skipped.vote, HookVote::Skip);
+        assert!(!skipped.failed);
+
+        let failed = run(&shell_hook("echo '{\"vote\":\"approve\"}'; exit 3", "10s"), b"{}").await;
+        assert!(failed.failed);
+        assert_eq!(failed.vote, HookVote::Skip);
+        assert!(failed.reason().contains("failed with exit status: 3"), "{}", failed.reason());
+
+        let garbage = run(&shell_hook("echo approve", "10s"), b"{}").await;
+        assert!(garbage.failed);
+        assert!(garbage.reason().contains("invalid decision"));
+
+        let started = std::time::Instant::now();
+        let slow = run(&shell_hook("sleep 10", "1s"), b"{}").await;
+        assert!(slow.failed);
+        assert!(slow.reason().contains("timed out after 1s"));
+        assert!(started.elapsed() < std::time::Duration::from_secs(5));
+    }
+}
@@ -0,0 +1,204 @@
+//! Automatically approve Azure DevOps pull requests from a set of watched users.
+//!
+//! [`Reviewer`] is the engine behind the `fast-pr-reviewer` command: give it an [`AzureDevOpsClient`] and an
+//! [`AppConfig`], then call [`Reviewer::run_once`] for a single poll or [`Reviewer::run`] to keep polling.
+//! Every decision it makes is passed to the callbacks registered with [`Reviewer::on_decision`].
+//!
+//! ```no_run
+//! use fast_pr_reviewer::config::ConfigOverrides;
+//! use fast_pr_reviewer::{AppConfig, AzureDevOpsClient, Reviewer};
+//!
+//! # async fn example() -> anyhow::Result<()> {
+//! let config = AppConfig::from_file("config.toml", &ConfigOverrides::default())?;
+//! let client = AzureDevOpsClient::new(&config.organization, &config.project, &config.personal_access_token);
+//! let reviewer = Reviewer::new(client, config)?;
+//! reviewer.on_decision(|decision| println!("PR #{}: {}", decision.pr_id, decision.outcome));
+//! reviewer.run_once().await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Each [`Reviewer`] keeps its own state, such as the PRs it has seen, in a [`ReviewerState`] it shares with
+//! whatever controls it, so several reviewers can run side by side in one process.
+
+use chrono::{DateTime, Utc};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+pub mod ado_client;
+pub mod approval_queue;
+pub mod cassette;
+pub mod chatops;
+mod completion;
+mod conditions;
+pub mod config;
+mod control;
+pub mod decisions;
+pub mod hook;
+pub mod models;
+pub mod polling;
+pub mod rate_limiter;
+pub mod redact;
+pub mod reviewer;
+pub mod rules;
+pub mod schedule;
+pub mod scripting;
+
+pub use ado_client::AzureDevOpsClient;
+pub use config::AppConfig;
+pub use decisions::{Decision, DecisionLog};
+pub use reviewer::Reviewer;
+
+/// What a [`Reviewer`] knows about the PRs it handles, shared with the dashboard, the admin API and anything
+/// else that inspects or steers it while it runs
+pub struct ReviewerState {
+    // Only PRs created after this are approved, unless they are rechecked
+    started: DateTime<Utc>,
+    decisions: Arc<DecisionLog>,
+    approval_queue: Mutex<approval_queue::ApprovalQueue>,
+    seen_prs: Mutex<HashSet<i32>>,
+    // PRs held back by a rule condition, hook or script, with the reason, so they are re-evaluated on later polls
+    blocked_prs: Mutex<HashMap<i32, String>>,
+    // The hook or script decision blocking a PR, reused on later polls until the PR is pushed to
+    policy_blocks: Mutex<HashMap<i32, PolicyBlock>>,
+    // PRs a recheck asked us to evaluate again, even if created before the reviewer started
+    recheck_prs: Mutex<HashSet<i32>>,
+    // Chat-ops commands already handled, loaded from and saved to the chat-ops state file
+    handled_comments: Mutex<chatops::HandledCommands>,
+    // Watched PRs that arrived outside the approval window, processed once it opens
+    queued_prs: Mutex<HashSet<i32>>,
+    // Approved PRs waiting for the bot to complete them, keyed by PR ID
+    pending_completions: Mutex<HashMap<i32, PendingCompletion>>,
+    // Active PRs from watched users, keyed by PR ID
+    tracked_prs: Mutex<HashMap<i32, TrackedPr>>,
+    paused: Mutex<bool>,
+    // When the next poll starts; None while polling or paused
+    next_poll: Mutex<Option<DateTime<Utc>>>,
+    // Compiled approval policy scripts, reloaded when their files change
+    scripts: Mutex<scripting::ScriptCache>,
+}
+
+impl ReviewerState {
+    fn new(decisions: Arc<DecisionLog>, approval_queue: approval_queue::ApprovalQueue) -> Self {
+        Self {
+            started: Utc::now(),
+            decisions,
+            approval_queue: Mutex::new(approval_queue),
+            seen_prs: Mutex::default(),
+            blocked_prs: Mutex::default(),
+            policy_blocks: Mutex::default(),
+            recheck_prs: Mutex::default(),
+            handled_comments: Mutex::default(),
+            queued_prs: Mutex::default(),
+            pending_completions: Mutex::default(),
+            tracked_prs: Mutex::default(),
+            paused: Mutex::default(),
+            next_poll: Mutex::default(),
+            scripts: Mutex::default(),
+        }
+    }
+
+    /// When the reviewer was created
+    pub fn started(&self) -> DateTime<Utc> {
+        self.started
+    }
+
+    pub fn decisions(&self) -> &Arc<DecisionLog> {
+        &self.decisions
+    }
+
+    pub fn approval_queue(&self) -> &Mutex<approval_queue::ApprovalQueue> {
+        &self.approval_queue
+    }
+
+    pub async fn is_paused(&self) -> bool {
+        *self.paused.lock().await
+    }
+
+    /// Stop or restart polling
+    pub async fn set_paused(&self, paused: bool) {
+        *self.paused.lock().await = paused;
+    }
+
+    /// When the next poll starts; None while polling or paused
+    pub async fn next_poll(&self) -> Option<DateTime<Utc>> {
+        *self.next_poll.lock().await
+    }
+
+    /// Active PRs from watched users, in no particular order
+    pub async fn tracked_prs(&self) -> Vec<TrackedPr> {
+        self.tracked_prs.lock().await.values().cloned().collect()
+    }
+
+    /// How many PRs have been seen since the reviewer started
+    pub async fn seen_count(&self) -> usize {
+        self.seen_prs.lock().await.len()
+    }
+
+    pub async fn is_seen(&self, pr_id: i32) -> bool {
+        self.seen_prs.lock().await.contains(&pr_id)
+    }
+
+    /// Treat a PR as new again on the next poll; returns false if it hadn't been seen
+    pub async fn forget(&self, pr_id: i32) -> bool {
+        self.seen_prs.lock().await.remove(&pr_id)
+    }
+
+    /// Evaluate a PR again on the next poll, even if it was created before the reviewer started
+    pub async fn recheck(&self, pr_id: i32) {
+        self.seen_prs.lock().await.remove(&pr_id);
+        self.policy_blocks.lock().await.remove(&pr_id);
+        self.recheck_prs.lock().await.insert(pr_id);
+    }
+
+    /// Why a rule condition, hook or script holds the PR back
+    pub async fn blocked_reason(&self, pr_id: i32) -> Option<String> {
+        self.blocked_prs.lock().await.get(&pr_id).cloned()
+    }
+
+    /// Whether the PR waits for the approval window to open
+    pub async fn is_queued(&self, pr_id: i32) -> bool {
+        self.queued_prs.lock().await.contains(&pr_id)
+    }
+
+    /// Whether the PR was approved and waits to be completed by the bot
+    pub async fn is_completing(&self, pr_id: i32) -> bool {
+        self.pending_completions.lock().await.contains_key(&pr_id)
+    }
+}
+
+/// A PR approved under a rule with direct completion enabled
+struct PendingCompletion {
+    rule: String,
+    // Last outcome recorded in the decision log, so repeated polls don't spam it
+    last_outcome: Option<completion::CompletionOutcome>,
+}
+
+/// A hook or script decision that blocked a PR, with what it was made for
+struct PolicyBlock {
+    commit: String,
+    hook: Option<hook::DecisionHook>,
+    scripts: Vec<String>,
+    source: String,
+    decision: hook::HookDecision,
+}
+
+impl PolicyBlock {
+    /// Whether the decision still holds: same commit and the rule still runs the same hook and scripts
+    fn applies_to(&self, rule: &rules::Rule, pr: &models::PullRequest) -> bool {
+        pr.last_merge_source_commit.as_ref().is_some_and(|commit| commit.commit_id == self.commit)
+            && rule.hook == self.hook
+            && rule.scripts == self.scripts
+    }
+}
+
+/// An active PR from a watched user
+#[derive(Debug, Clone)]
+pub struct TrackedPr {
+    pub pr_id: i32,
+    pub repository: String,
+    pub author: String,
+    pub title: String,
+    pub created: Option<DateTime<Utc>>,
+}
@@ -1,28 +1,35 @@
 use anyhow::{Context, Result};
-use clap::Parser;
-use log::{error, info, warn};
+use clap::{Parser, Subcommand, ValueEnum};
+use tracing::{error, info, warn};
+use tracing_subscriber::{prelude::*, EnvFilter, Layer};
 use std::time::Duration;
-use std::collections::HashSet;
-use std::io::{self, Write};
-use tokio::{time, signal, sync::oneshot};
-use tokio::sync::Mutex;
-use env_logger::Env;
-use std::sync::Arc;
-use lazy_static::lazy_static;
-use chrono::{DateTime, Utc};
-
-mod ado_client;
-mod config;
-mod models;
-
-use ado_client::AzureDevOpsClient;
-use config::AppConfig;
-
-// Use lazy_static with a mutex to safely track previously seen PRs
-lazy_static! {
-    static ref SEEN_PRS: Arc<Mutex<HashSet<i32>>> = Arc::new(Mutex::new(HashSet::new()));
-    static ref PROGRAM_START_TIME: DateTime<Utc> = Utc::now();
-}
+use std::io::{self, IsTerminal, Write};
+use tokio::{time, signal, sync::{mpsc, oneshot}};
+use std::path::PathBuf;
+
+mod admin;
+mod config_check;
+mod systemd;
+mod tui;
+
+use admin::AdminRequest;
+use fast_pr_reviewer::approval_queue::ApprovalQueue;
+use fast_pr_reviewer::cassette::{MatchOn, Player, Recorder};
+use fast_pr_reviewer::config::{self, ConfigOverrides, ConfigWatcher};
+use fast_pr_reviewer::polling::AdaptiveInterval;
+use fast_pr_reviewer::reviewer::{Flow, RunHooks, Stopped};
+use fast_pr_reviewer::schedule::Schedule;
+use fast_pr_reviewer::{redact, AppConfig, AzureDevOpsClient, Reviewer};
+use tui::{Dashboard, DashboardCommand, ErrorCapture, RecentErrors};
+
+// How often the config file is checked for edits
+const CONFIG_CHECK_INTERVAL: Duration = Duration::from_secs(2);
+
+// Config fields the client, approval queue, decision log and admin API are built from once at startup
+const RESTART_ONLY_FIELDS: [&str; 9] = [
+    "organization", "project", "personal_access_token", "pat_file", "pat_command", "rate_limit", "approval_queue", "decision_log",
+    "admin_api",
+];
 
 /// Fast PR Reviewer - Automatically approve PRs from specified users
 #[derive(Parser, Debug)]
@@ -35,36 +42,178 @@ struct Args {
     /// Polling interval in seconds
     #[clap(short, long, default_value = "1")]
     interval: u64,
+
+    /// Log output format
+    #[clap(long, value_enum, default_value = "text")]
+    log_format: LogFormat,
+
+    /// Show a live dashboard instead of log output
+    #[clap(long)]
+    tui: bool,
+    
+    /// Azure DevOps organization (overrides config file and FPR_ORGANIZATION)
+    #[clap(long)]
+    organization: Option<String>,
+
+    /// Azure DevOps project (overrides config file and FPR_PROJECT)
+    #[clap(long)]
+    project: Option<String>,
+
+    /// Identity ID the bot votes as (overrides config file and FPR_REVIEWER_ID)
+    #[clap(long)]
+    reviewer_id: Option<String>,
+
+    /// File holding the PAT (overrides config file and FPR_PAT_FILE)
+    #[clap(long)]
+    pat_file: Option<String>,
+
+    /// Save every Azure DevOps request and response to this cassette file, without the auth header
+    #[clap(long, conflicts_with = "replay")]
+    record: Option<PathBuf>,
+
+    /// Answer Azure DevOps requests from this cassette file instead of the network
+    #[clap(long)]
+    replay: Option<PathBuf>,
+
+    /// What a request must share with a recorded one to be answered by it when replaying
+    #[clap(long, value_enum, value_delimiter = ',', default_value = "method,path,query")]
+    replay_match: Vec<MatchOn>,
     
-    /// Users to watch for PRs (overrides config file)
+    /// Users to watch for PRs (overrides config file and FPR_WATCHED_USERS)
     #[clap(trailing_var_arg = true)]
     watched_users: Vec<String>,
+
+    #[clap(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum LogFormat {
+    /// Human-readable lines
+    Text,
+    /// One JSON object per event, with the enclosing poll and PR spans
+    Json,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// List the approvals scheduled for later
+    Queue,
+    /// Inspect the configuration
+    Config {
+        #[clap(subcommand)]
+        command: ConfigCommand,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum ConfigCommand {
+    /// Check the configuration against Azure DevOps and exit non-zero if anything is wrong
+    Check,
+    /// Print the effective configuration and where each value came from, with secrets redacted
+    Show,
+}
+
+impl Args {
+    /// Config fields set through command line flags
+    fn config_overrides(&self) -> ConfigOverrides {
+        let mut overrides = ConfigOverrides::default();
+        if let Some(organization) = &self.organization {
+            overrides.set("organization", "--organization", organization.as_str());
+        }
+        if let Some(project) = &self.project {
+            overrides.set("project", "--project", project.as_str());
+        }
+        if let Some(reviewer_id) = &self.reviewer_id {
+            overrides.set("reviewer_id", "--reviewer-id", reviewer_id.as_str());
+        }
+        if let Some(pat_file) = &self.pat_file {
+            overrides.set("pat_file", "--pat-file", pat_file.as_str());
+        }
+        if !self.watched_users.is_empty() {
+            overrides.set("watched_users", "watched users arguments", self.watched_users.clone());
+        }
+        overrides
+    }
 }
 
 #[tokio::main]
-async fn main() -> Result<()> {
-    // Initialize logger with custom settings to always show info logs
-    env_logger::Builder::from_env(Env::default().default_filter_or("info")).init();
-    
+async fn main() {
+    // The reviewer, dashboard and other state are dropped by the time run returns, so exiting here skips nothing
+    let exit_code = match run().await {
+        Ok(exit_code) => exit_code,
+        Err(e) => {
+            // Errors can carry response bodies and file contents, so they go through redaction like the logs
+            eprintln!("Error: {}", redact::redact(&format!("{:?}", e)));
+            1
+        }
+    };
+    if exit_code != 0 {
+        std::process::exit(exit_code);
+    }
+}
+
+/// Run the bot or the requested subcommand; returns the process exit code
+async fn run() -> Result<i32> {
     // Parse command line arguments
     let args = Args::parse();
     
-    // Load configuration
-    let mut config = AppConfig::from_file(&args.config)
+    // Initialize logging; RUST_LOG overrides the default of showing info logs.
+    // The dashboard owns the terminal, so it only collects warnings and errors for its errors pane
+    let show_dashboard = args.tui && args.command.is_none();
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    let logger = tracing_subscriber::fmt::layer()
+        .with_writer(|| redact::Redacting(io::stderr()))
+        .with_ansi(io::stderr().is_terminal());
+    let logger = match args.log_format {
+        LogFormat::Text => logger.boxed(),
+        LogFormat::Json => logger.json().with_current_span(true).with_span_list(true).boxed(),
+    };
+    let recent_errors = RecentErrors::default();
+    tracing_subscriber::registry()
+        .with((!show_dashboard).then_some(logger))
+        .with(show_dashboard.then(|| ErrorCapture(recent_errors.clone())))
+        .with(filter)
+        .init();
+    
+    // Load configuration; environment variables and CLI flags override the file
+    let mut config = AppConfig::from_file_async(&args.config, &args.config_overrides()).await
         .context("Failed to load configuration")?;
     
-    // Override watched users with CLI arguments if provided
+    match &args.command {
+        Some(Command::Queue) => return print_approval_queue(&config).map(|()| 0),
+        Some(Command::Config { command: ConfigCommand::Show }) => return print_effective_config(&args.config, &config).map(|()| 0),
+        _ => (),
+    }
+    
     if !args.watched_users.is_empty() {
         info!("Overriding watched users from config with CLI arguments");
-        config.watched_users = args.watched_users;
     }
     
     // Create Azure DevOps client
-    let ado_client = AzureDevOpsClient::new(
+    let mut ado_client = AzureDevOpsClient::new(
         &config.organization,
         &config.project,
         &config.personal_access_token,
     );
+    if let Some(rate_limit) = &config.rate_limit {
+        ado_client = ado_client.with_rate_limit(rate_limit.burst, rate_limit.requests_per_second);
+    }
+    if let Some(pat_source) = config.pat_source()? {
+        ado_client = ado_client.with_pat_source(pat_source);
+    }
+    if let Some(path) = &args.record {
+        info!("📼 Recording Azure DevOps requests to {:?}", path);
+        ado_client = ado_client.with_recorder(Recorder::new(path)?);
+    }
+    if let Some(path) = &args.replay {
+        info!("📼 Replaying Azure DevOps responses from {:?}", path);
+        ado_client = ado_client.with_player(Player::load(path, &args.replay_match)?);
+    }
+    
+    if let Some(Command::Config { command: ConfigCommand::Check }) = &args.command {
+        return config_check::run(&ado_client, &config).await.map(|()| 0);
+    }
     
     // Check if reviewer ID is set, if not prompt the user to set it
     if config.reviewer_id.is_none() {
@@ -76,9 +225,16 @@ async fn main() -> Result<()> {
     info!("Organization: {}", config.organization);
     info!("Project: {}", config.project);
     info!("Polling interval: {} seconds", args.interval);
+    if let Some(polling) = &config.polling {
+        info!("Backing off to at most {} while idle (x{} per idle poll)", polling.max_interval, polling.backoff_factor);
+    }
     if let Some(reviewer_id) = &config.reviewer_id {
         info!("Using reviewer ID: {}", reviewer_id);
     }
+    if let Some(schedule_config) = &config.schedule {
+        info!("Approval schedule ({}): {} windows, {} cron expressions, {} holidays", schedule_config.timezone,
+            schedule_config.windows.len(), schedule_config.cron.len(), schedule_config.holidays.len());
+    }
     
     // Log who we're watching for PRs
     if !config.watched_users.is_empty() {
@@ -90,48 +246,342 @@ async fn main() -> Result<()> {
         warn!("No users being watched! Add users to config.toml or specify them as command line arguments.");
     }
     
+    // Only PRs created from now on are approved; approvals scheduled by an earlier run are resumed
+    let mut reviewer = Reviewer::new(ado_client, config)?;
+    
     // Create a channel to signal shutdown
-    let (shutdown_tx, mut shutdown_rx) = oneshot::channel();
+    let (shutdown_tx, shutdown_rx) = oneshot::channel();
     
-    // Handle Ctrl+C signal
+    // Handle Ctrl+C and SIGTERM, e.g. from systemctl stop or a container runtime
     tokio::spawn(async move {
-        match signal::ctrl_c().await {
-            Ok(()) => {
-                info!("Received Ctrl+C, initiating graceful shutdown...");
+        match wait_for_stop_signal().await {
+            Ok(signal) => {
+                info!("Received {}, initiating graceful shutdown...", signal);
                 let _ = shutdown_tx.send(());
             }
             Err(err) => {
-                error!("Failed to listen for Ctrl+C signal: {}", err);
+                error!("Failed to listen for stop signals: {}", err);
+            }
+        }
+    });
+    
+    // Reload the configuration on SIGHUP
+    let (reload_tx, reload_rx) = mpsc::channel(1);
+    #[cfg(unix)]
+    tokio::spawn(async move {
+        match signal::unix::signal(signal::unix::SignalKind::hangup()) {
+            Ok(mut hangup) => {
+                while hangup.recv().await.is_some() {
+                    let _ = reload_tx.try_send(());
+                }
+            }
+            Err(err) => {
+                error!("Failed to listen for SIGHUP: {}", err);
             }
         }
     });
+    #[cfg(not(unix))]
+    drop(reload_tx);
+    
+    // Approvals and reloads requested through the admin API are carried out between polls
+    let (admin_tx, admin_rx) = mpsc::channel(8);
+    match &reviewer.config().admin_api {
+        Some(admin_api) => admin::start(admin_api, reviewer.state().clone(), admin_tx).await
+            .context("Failed to start the admin API")?,
+        None => drop(admin_tx),
+    }
+    
+    // Key bindings of the dashboard arrive here; without a dashboard the channel is closed
+    let (dashboard_tx, dashboard_rx) = mpsc::channel(8);
+    let dashboard = if show_dashboard {
+        Some(Dashboard::start(reviewer.state().clone(), recent_errors, dashboard_tx).context("Failed to start the dashboard")?)
+    } else {
+        drop(dashboard_tx);
+        None
+    };
     
-    let polling_interval = Duration::from_secs(args.interval);
+    let mut hooks = CliHooks {
+        args: &args,
+        shutdown: shutdown_rx,
+        stopping: false,
+        reload: reload_rx,
+        config_watcher: ConfigWatcher::new(&args.config),
+        config_check: time::interval(CONFIG_CHECK_INTERVAL),
+        watchdog: systemd::watchdog_interval().map(|period| {
+            // Pings missed during a long poll don't need catching up
+            let mut interval = time::interval(period);
+            interval.set_missed_tick_behavior(time::MissedTickBehavior::Skip);
+            interval
+        }),
+        dashboard: dashboard_rx,
+        admin: admin_rx,
+    };
+    
+    systemd::notify_ready();
+    
+    // Poll for new PRs and approve them until a stop signal, or quitting the dashboard
+    let stopped = reviewer.run_with(Duration::from_secs(args.interval), &mut hooks).await?;
+    
+    if let Some(dashboard) = dashboard {
+        dashboard.stop().await;
+    }
     
-    // Main loop - Poll for new PRs and approve them until shutdown signal
-    loop {
-        // Check if shutdown was requested
-        if shutdown_rx.try_recv().is_ok() {
-            info!("Shutting down...");
-            break;
+    info!("FastPRReviewer bot has stopped");
+    let shutdown = reviewer.config().shutdown.clone().unwrap_or_default();
+    Ok(match stopped {
+        Stopped::DrainTimedOut => shutdown.timeout_exit_code,
+        Stopped::Gracefully => shutdown.exit_code,
+    })
+}
+
+/// What the command reacts to while the engine runs: stop signals, config edits, the dashboard and the admin API
+struct CliHooks<'a> {
+    args: &'a Args,
+    shutdown: oneshot::Receiver<()>,
+    stopping: bool,
+    reload: mpsc::Receiver<()>,
+    config_watcher: ConfigWatcher,
+    config_check: time::Interval,
+    watchdog: Option<time::Interval>,
+    dashboard: mpsc::Receiver<DashboardCommand>,
+    admin: mpsc::Receiver<AdminRequest>,
+}
+
+impl CliHooks<'_> {
+    fn stop(&mut self) -> Flow {
+        info!("Shutting down...");
+        self.stopping = true;
+        systemd::notify_stopping();
+        Flow::Stop
+    }
+
+    /// Reload the config file, whatever asked for it, so the file watcher doesn't load the same edit again
+    async fn reload_config(&mut self, reviewer: &mut Reviewer) -> Result<Vec<String>> {
+        let reloaded = reload_config(self.args, reviewer).await;
+        if reloaded.is_ok() {
+            self.config_watcher.mark_seen();
         }
-        
-        match check_and_approve_prs(&ado_client, &config).await {
-            Ok(_) => (),
-            Err(e) => error!("Error checking PRs: {}", e),
+        reloaded
+    }
+}
+
+impl RunHooks for CliHooks<'_> {
+    /// Keep the watchdog fed during long polls and the drain, and stop on a signal
+    async fn while_polling(&mut self) {
+        loop {
+            tokio::select! {
+                _ = watchdog_tick(&mut self.watchdog) => systemd::notify_watchdog(),
+                _ = &mut self.shutdown, if !self.stopping => {
+                    self.stop();
+                    return;
+                }
+            }
         }
-        
-        // Wait before checking again, but also listen for shutdown signal
-        tokio::select! {
-            _ = time::sleep(polling_interval) => {}
-            _ = &mut shutdown_rx => {
-                info!("Shutting down...");
-                break;
+    }
+
+    async fn between_polls(&mut self, reviewer: &mut Reviewer, wait: Duration) -> Flow {
+        if self.stopping {
+            return Flow::Stop;
+        }
+
+        // Wait before checking again, but also listen for shutdown and config changes
+        let sleep = time::sleep(wait);
+        tokio::pin!(sleep);
+        loop {
+            tokio::select! {
+                _ = &mut sleep => return Flow::Continue,
+                _ = watchdog_tick(&mut self.watchdog) => systemd::notify_watchdog(),
+                _ = self.config_check.tick() => {
+                    if self.config_watcher.changed() {
+                        info!("Configuration file {} changed, reloading it", self.args.config);
+                        // Failures are logged, and the bot carries on with the previous config
+                        let _ = self.reload_config(reviewer).await;
+                        return Flow::Continue;
+                    }
+                }
+                Some(()) = self.reload.recv() => {
+                    info!("Received SIGHUP, reloading configuration from {}", self.args.config);
+                    let _ = self.reload_config(reviewer).await;
+                    return Flow::Continue;
+                }
+                Some(command) = self.dashboard.recv() => match command {
+                    DashboardCommand::Quit => return self.stop(),
+                    DashboardCommand::TogglePause => {
+                        let paused = !reviewer.is_paused().await;
+                        reviewer.set_paused(paused).await;
+                        info!("{}", if paused { "⏸ Polling paused from the dashboard" } else { "▶ Polling resumed from the dashboard" });
+                        // Poll right away after resuming
+                        if !paused {
+                            return Flow::Continue;
+                        }
+                    }
+                    DashboardCommand::Approve(_) | DashboardCommand::Skip(_) => {
+                        run_dashboard_command(reviewer, command).await;
+                    }
+                },
+                Some(request) = self.admin.recv() => match request {
+                    AdminRequest::Approve { pr_id, reply } => {
+                        let result = reviewer.approve(pr_id, "the admin API").await;
+                        let _ = reply.send(result);
+                    }
+                    AdminRequest::Reload { reply } => {
+                        let _ = reply.send(self.reload_config(reviewer).await);
+                    }
+                    AdminRequest::Resumed => return Flow::Continue,
+                },
+                _ = &mut self.shutdown => return self.stop(),
             }
         }
     }
-    
-    info!("FastPRReviewer bot has stopped");
+}
+
+/// Wait for Ctrl+C, or SIGTERM on Unix; returns which one arrived
+async fn wait_for_stop_signal() -> io::Result<&'static str> {
+    #[cfg(unix)]
+    {
+        let mut terminate = signal::unix::signal(signal::unix::SignalKind::terminate())?;
+        tokio::select! {
+            result = signal::ctrl_c() => result.map(|()| "Ctrl+C"),
+            _ = terminate.recv() => Ok("SIGTERM"),
+        }
+    }
+    #[cfg(not(unix))]
+    signal::ctrl_c().await.map(|()| "Ctrl+C")
+}
+
+/// Next tick of the systemd watchdog; never fires when the watchdog is off
+async fn watchdog_tick(watchdog: &mut Option<time::Interval>) {
+    match watchdog {
+        Some(interval) => {
+            interval.tick().await;
+        }
+        None => std::future::pending().await,
+    }
+}
+
+/// Force-approve or skip the PR selected on the dashboard
+async fn run_dashboard_command(reviewer: &Reviewer, command: DashboardCommand) {
+    info!("🖥 Running {:?} from the dashboard", command);
+    let (pr_id, result) = match command {
+        DashboardCommand::Approve(pr_id) => (pr_id, reviewer.approve(pr_id, "the dashboard").await),
+        DashboardCommand::Skip(pr_id) => (pr_id, reviewer.skip(pr_id, "the dashboard").await),
+        DashboardCommand::TogglePause | DashboardCommand::Quit => return,
+    };
+    if let Err(e) = result {
+        error!("❌ Failed to {:?} PR #{}: {:#}", command, pr_id, e);
+    }
+}
+
+/// Load the config file again and swap it in, keeping the current config if the new one is invalid;
+/// returns the fields that changed and took effect
+async fn reload_config(args: &Args, reviewer: &mut Reviewer) -> Result<Vec<String>> {
+    // A PAT command may take a while; don't stall the other tasks meanwhile, nor wait on it forever
+    let loaded = AppConfig::from_file_async(&args.config, &args.config_overrides()).await.and_then(|new_config| {
+        new_config.schedule.as_ref().map(Schedule::from_config).transpose()
+            .context("Invalid schedule")?;
+        AdaptiveInterval::new(Duration::from_secs(args.interval), new_config.polling.as_ref())?;
+        Ok(new_config)
+    });
+    let mut new_config = match loaded {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            error!("❌ Invalid configuration in {}, keeping the previous one: {:#}", args.config, e);
+            return Err(e);
+        }
+    };
+
+    let config = reviewer.config();
+
+    // A reviewer ID set up at startup stays in use
+    if new_config.reviewer_id.is_none() {
+        new_config.reviewer_id = config.reviewer_id.clone();
+    }
+
+    let changed = match config::changed_fields(config, &new_config) {
+        Ok(changed) => changed,
+        Err(e) => {
+            error!("❌ Failed to compare configurations, keeping the previous one: {:#}", e);
+            return Err(e);
+        }
+    };
+    if changed.is_empty() {
+        info!("Configuration reloaded, nothing changed");
+        return Ok(Vec::new());
+    }
+
+    for field in changed.iter().filter(|field| RESTART_ONLY_FIELDS.contains(&field.as_str())) {
+        warn!("⚠ {} can't change while the bot is running, restart it to apply the new value", field);
+    }
+    new_config.organization = config.organization.clone();
+    new_config.project = config.project.clone();
+    new_config.personal_access_token = config.personal_access_token.clone();
+    new_config.pat_file = config.pat_file.clone();
+    new_config.pat_command = config.pat_command.clone();
+    new_config.rate_limit = config.rate_limit.clone();
+    new_config.approval_queue = config.approval_queue.clone();
+    new_config.decision_log = config.decision_log.clone();
+    new_config.admin_api = config.admin_api.clone();
+
+    let applied: Vec<&str> = changed.iter()
+        .map(String::as_str)
+        .filter(|field| !RESTART_ONLY_FIELDS.contains(field))
+        .collect();
+    if applied.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    if applied.contains(&"watched_users") {
+        let added: Vec<&str> = new_config.watched_users.iter()
+            .filter(|user| !config.watched_users.contains(user))
+            .map(String::as_str)
+            .collect();
+        let removed: Vec<&str> = config.watched_users.iter()
+            .filter(|user| !new_config.watched_users.contains(user))
+            .map(String::as_str)
+            .collect();
+        info!("👀 Now watching {} users (added: [{}], removed: [{}])", new_config.watched_users.len(),
+            added.join(", "), removed.join(", "));
+    }
+    if applied.contains(&"rules") {
+        let names = |config: &AppConfig| config.rules.iter().map(|rule| rule.name.clone()).collect::<Vec<_>>();
+        info!("📜 Rules changed from [{}] to [{}]", names(config).join(", "), names(&new_config).join(", "));
+    }
+    reviewer.set_config(new_config)?;
+    info!("🔧 Configuration reloaded from {}, changed: {}", args.config, applied.join(", "));
+    Ok(applied.into_iter().map(str::to_string).collect())
+}
+
+/// Print every setting of the effective configuration with where it came from
+fn print_effective_config(config_path: &str, config: &AppConfig) -> Result<()> {
+    println!("# Effective configuration (file: {}, precedence: command line > FPR_* environment > file > default)", config_path);
+
+    let values = config.effective_values()?;
+    let width = values.iter().map(|value| value.path.len() + value.value.len() + 3).max().unwrap_or(0);
+    for value in values {
+        let setting = format!("{} = {}", value.path, value.value);
+        println!("{:<width$}  # {}", setting, value.source, width = width);
+    }
+
+    Ok(())
+}
+
+/// Print the scheduled approvals saved by a running (or stopped) bot
+fn print_approval_queue(config: &AppConfig) -> Result<()> {
+    let approval_queue = ApprovalQueue::load(&config.approval_queue)?;
+
+    if approval_queue.entries().is_empty() {
+        println!("No approvals are scheduled.");
+        return Ok(());
+    }
+
+    println!("{} scheduled approvals:", approval_queue.entries().len());
+    for entry in approval_queue.entries() {
+        println!("  PR #{} in {} from {} - '{}'", entry.pr_id, entry.repository, entry.author, entry.title);
+        println!("    due {} (scheduled {}){}", entry.due_at.format("%Y-%m-%d %H:%M:%S UTC"),
+            entry.scheduled_at.format("%Y-%m-%d %H:%M:%S UTC"),
+            entry.rule.as_deref().map(|rule| format!(", rule '{}'", rule)).unwrap_or_default());
+    }
+
     Ok(())
 }
 
@@ -176,7 +626,8 @@ async fn setup_reviewer_id(client: &AzureDevOpsClient, config_path: &str) -> Res
     
     println!("\nAvailable reviewers:");
     for (i, reviewer) in reviewers.iter().enumerate() {
-        println!("{}: {} (ID: {})", i + 1, reviewer.display_name, reviewer.id);
+        println!("{}: {} (ID: {}){}", i + 1, reviewer.display_name, reviewer.id,
+            if reviewer.is_required { " [required]" } else { "" });
     }
     
     print!("\nSelect your reviewer number (or 0 to cancel): ");
@@ -199,117 +650,9 @@ async fn setup_reviewer_id(client: &AzureDevOpsClient, config_path: &str) -> Res
     println!("Selected reviewer: {} (ID: {})", selected_reviewer.display_name, reviewer_id);
     
     // Save the reviewer ID to the config file
-    let mut config = AppConfig::from_file(config_path)?;
-    config.reviewer_id = Some(reviewer_id.clone());
-    config.save_to_file(config_path)?;
+    AppConfig::save_reviewer_id(config_path, &reviewer_id)?;
     
     println!("Reviewer ID saved to config file.");
     
     Ok(Some(reviewer_id))
 }
-
-async fn check_and_approve_prs(client: &AzureDevOpsClient, config: &AppConfig) -> Result<()> {
-    // Check if reviewer ID is configured
-    let reviewer_id = match &config.reviewer_id {
-        Some(id) => id,
-        None => {
-            error!("No reviewer ID configured. Cannot approve PRs.");
-            return Ok(());
-        }
-    };
-
-    // Get active pull requests
-    let prs = client.get_active_pull_requests().await?;
-    
-    if prs.is_empty() {
-        info!("No active pull requests found");
-        return Ok(());
-    }
-    
-    let mut new_prs = Vec::new();
-    
-    // Lock the mutex to safely access the HashSet of seen PRs
-    let mut seen_prs = SEEN_PRS.lock().await;
-    for pr in &prs {
-        if !seen_prs.contains(&pr.pull_request_id) {
-            new_prs.push(pr);
-            seen_prs.insert(pr.pull_request_id);
-        }
-    }
-    // Mutex is automatically unlocked when seen_prs goes out of scope
-    
-    if new_prs.is_empty() {
-        info!("No new pull requests found");
-        return Ok(());
-    }
-    
-    info!("Found {} new pull requests", new_prs.len());
-    
-    let watched_prs: Vec<_> = new_prs.iter()
-        .filter(|&&pr| {
-            // Check if user is in watched list
-            let is_watched_user = config.watched_users.contains(&pr.created_by.display_name);
-            
-            // Parse the PR creation date
-            if let Ok(pr_creation_date) = DateTime::parse_from_rfc3339(&pr.creation_date) {
-                let pr_creation_utc = pr_creation_date.with_timezone(&Utc);
-                
-                // Only include PRs created after the program started
-                if pr_creation_utc < *PROGRAM_START_TIME {
-                    info!("Skipping PR #{} from {} - created before program start", 
-                          pr.pull_request_id, pr.created_by.display_name);
-                    return false;
-                }
-                
-                return is_watched_user;
-            } else {
-                // If we can't parse the date, log a warning but still include the PR if it's from a watched user
-                warn!("Could not parse creation date for PR #{}", pr.pull_request_id);
-                return is_watched_user;
-            }
-        })
-        .collect();
-    
-    if !watched_prs.is_empty() {
-        info!("Found {} PRs from watched users created after program start", watched_prs.len());
-    } else {
-        info!("No PRs from watched users found in this poll that were created after program start");
-        return Ok(());
-    }
-    
-    // Process PRs from watched users
-    for &pr in &watched_prs {
-        info!("🔍 Processing PR #{} from watched user {} - '{}'", 
-            pr.pull_request_id, pr.created_by.display_name, pr.title);
-        
-        // Check if we've already approved this PR using our reviewer ID
-        match client.check_approval_status(pr, reviewer_id).await {
-            Ok(already_approved) => {
-                if already_approved {
-                    info!("✓ PR #{} is already approved", pr.pull_request_id);
-                    continue;
-                } else {
-                    info!("PR #{} needs approval, will approve now...", pr.pull_request_id);
-                }
-            },
-            Err(e) => {
-                warn!("⚠ Failed to check approval status for PR #{}: {}", pr.pull_request_id, e);
-                info!("Will attempt to approve PR #{} anyway", pr.pull_request_id);
-            }
-        }
-        
-        // Try to approve the PR using our reviewer ID
-        match client.approve_pull_request(pr, reviewer_id).await {
-            Ok(_) => {
-                info!("✅ Successfully approved PR #{} from {}", 
-                    pr.pull_request_id, pr.created_by.display_name);
-                info!("Approval timestamp: {}", chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC"));
-            }
-            Err(e) => {
-                error!("❌ Failed to approve PR #{}: {}", pr.pull_request_id, e);
-            }
-        }
-    }
-    
-    Ok(())
-}
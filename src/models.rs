@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct PullRequest {
     #[serde(rename = "pullRequestId")]
     pub pull_request_id: i32,
@@ -9,24 +9,100 @@ pub struct PullRequest {
     pub created_by: IdentityRef,
     #[serde(rename = "creationDate")]
     pub creation_date: String,
+    #[serde(rename = "sourceRefName")]
+    pub source_branch: Option<String>,
     #[serde(rename = "targetRefName")]
-    #[allow(dead_code)]
     pub target_branch: Option<String>,
     // Add repository information
     pub repository: Repository,
+    #[serde(default)]
+    pub status: Option<String>,
+    #[serde(rename = "mergeStatus", default)]
+    pub merge_status: Option<String>,
+    #[serde(rename = "lastMergeSourceCommit", default)]
+    pub last_merge_source_commit: Option<GitCommitRef>,
 }
 
 // Add Repository struct to store repository information
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct Repository {
     pub id: String,
     pub name: String,
+    #[serde(default)]
+    pub project: Option<ProjectRef>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ProjectRef {
+    pub id: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RepositoryList {
+    pub value: Vec<Repository>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TeamProject {
+    pub id: String,
+    pub name: String,
+}
+
+/// Answer to a permission query: one flag per security token asked about
+#[derive(Debug, Deserialize)]
+pub struct PermissionCheck {
+    pub value: Vec<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ConnectionData {
+    #[serde(rename = "authenticatedUser")]
+    pub authenticated_user: Identity,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Identity {
+    pub id: String,
+    #[serde(rename = "providerDisplayName", default)]
+    pub provider_display_name: String,
+    #[serde(rename = "customDisplayName", default)]
+    pub custom_display_name: Option<String>,
+}
+
+impl Identity {
+    /// Name shown for the identity in pull requests
+    pub fn display_name(&self) -> &str {
+        self.custom_display_name.as_deref().unwrap_or(&self.provider_display_name)
+    }
 }
 
 #[derive(Debug, Deserialize)]
+pub struct IdentityList {
+    // Lookups by ID return null for IDs that don't exist
+    pub value: Vec<Option<Identity>>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct GitCommitRef {
+    #[serde(rename = "commitId")]
+    pub commit_id: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
 pub struct IdentityRef {
+    #[serde(default)]
+    pub id: String,
     #[serde(rename = "displayName")]
     pub display_name: String,
+    // Account name such as "jane@contoso.com"; unlike the display name it is unique and can't be changed by its owner
+    #[serde(rename = "uniqueName", default, skip_serializing_if = "Option::is_none")]
+    pub unique_name: Option<String>,
+}
+
+// Minimal identity reference used when sending an identity back to the API
+#[derive(Debug, Serialize)]
+pub struct IdentityRefWithId {
+    pub id: String,
 }
 
 #[derive(Debug, Deserialize)]
@@ -38,16 +114,217 @@ pub struct PullRequestList {
 pub struct ReviewRequest {
     pub vote: i32,
     pub comment: String,
+    /// Azure DevOps makes a reviewer optional when a vote leaves this out
+    #[serde(rename = "isRequired", skip_serializing_if = "std::ops::Not::not")]
+    pub is_required: bool,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct Reviewer {
     pub id: String,
-    #[serde(rename = "displayName")]
+    #[serde(rename = "displayName", default)]
     pub display_name: String,
+    #[serde(default)]
+    pub vote: i32,
+    #[serde(rename = "isRequired", default)]
+    pub is_required: bool,
+}
+
+// Body used to add a reviewer to a PR without voting
+#[derive(Debug, Serialize)]
+pub struct AddReviewerRequest {
+    pub vote: i32,
+    #[serde(rename = "isRequired")]
+    pub is_required: bool,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct ReviewerList {
     pub value: Vec<Reviewer>,
-}
\ No newline at end of file
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Iteration {
+    pub id: i32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct IterationList {
+    pub value: Vec<Iteration>,
+}
+
+// A file changed by a PR, e.g. "/src/main.rs" with change type "edit"
+#[derive(Debug, Deserialize, Serialize)]
+pub struct FileChange {
+    pub path: String,
+    #[serde(rename = "changeType")]
+    pub change_type: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChangeEntry {
+    pub item: ChangeItem,
+    #[serde(rename = "changeType")]
+    pub change_type: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChangeItem {
+    #[serde(default)]
+    pub path: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct IterationChanges {
+    #[serde(rename = "changeEntries", default)]
+    pub change_entries: Vec<ChangeEntry>,
+}
+
+// Merge strategies supported by completion options
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MergeStrategy {
+    Squash,
+    Rebase,
+    NoFastForward,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct CompletionOptions {
+    #[serde(rename = "mergeStrategy", skip_serializing_if = "Option::is_none")]
+    pub merge_strategy: Option<MergeStrategy>,
+    #[serde(rename = "deleteSourceBranch")]
+    pub delete_source_branch: bool,
+    #[serde(rename = "transitionWorkItems")]
+    pub transition_work_items: bool,
+    #[serde(rename = "mergeCommitMessage", skip_serializing_if = "Option::is_none")]
+    pub merge_commit_message: Option<String>,
+}
+
+// Body for PATCH requests against a pull request; only set fields are sent
+#[derive(Debug, Default, Serialize)]
+pub struct PullRequestUpdate {
+    #[serde(rename = "autoCompleteSetBy", skip_serializing_if = "Option::is_none")]
+    pub auto_complete_set_by: Option<IdentityRefWithId>,
+    #[serde(rename = "completionOptions", skip_serializing_if = "Option::is_none")]
+    pub completion_options: Option<CompletionOptions>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    #[serde(rename = "lastMergeSourceCommit", skip_serializing_if = "Option::is_none")]
+    pub last_merge_source_commit: Option<GitCommitRef>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PolicyEvaluation {
+    // approved, rejected, running, queued, notApplicable or broken
+    pub status: String,
+    pub configuration: PolicyConfiguration,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PolicyConfiguration {
+    #[serde(rename = "isBlocking", default)]
+    pub is_blocking: bool,
+    #[serde(rename = "type")]
+    pub policy_type: PolicyType,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PolicyType {
+    #[serde(rename = "displayName")]
+    pub display_name: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PolicyEvaluationList {
+    pub value: Vec<PolicyEvaluation>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ResourceRef {
+    pub id: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ResourceRefList {
+    pub value: Vec<ResourceRef>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WorkItem {
+    pub id: i32,
+    pub fields: WorkItemFields,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WorkItemFields {
+    #[serde(rename = "System.WorkItemType")]
+    pub work_item_type: String,
+    #[serde(rename = "System.State")]
+    pub state: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WorkItemList {
+    pub value: Vec<WorkItem>,
+}
+
+// A comment in a new thread, or a reply in an existing one
+#[derive(Debug, Serialize)]
+pub struct CommentRequest {
+    #[serde(rename = "parentCommentId")]
+    pub parent_comment_id: i32,
+    pub content: String,
+    #[serde(rename = "commentType")]
+    pub comment_type: String,
+}
+
+// Body used to start a new comment thread on a PR
+#[derive(Debug, Serialize)]
+pub struct CommentThreadRequest {
+    pub comments: Vec<CommentRequest>,
+    pub status: String,
+}
+
+impl CommentThreadRequest {
+    pub fn new(content: &str) -> Self {
+        Self {
+            comments: vec![CommentRequest {
+                parent_comment_id: 0,
+                content: content.to_string(),
+                comment_type: "text".to_string(),
+            }],
+            status: "active".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Comment {
+    pub id: i32,
+    pub author: IdentityRef,
+    #[serde(default)]
+    pub content: Option<String>,
+    #[serde(rename = "publishedDate", default)]
+    pub published_date: Option<String>,
+    // "text" for people, "system" for comments generated by ADO
+    #[serde(rename = "commentType", default)]
+    pub comment_type: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CommentThread {
+    pub id: i32,
+    // active, fixed, wontFix, closed, byDesign or pending; system threads have none
+    #[serde(default)]
+    pub status: Option<String>,
+    #[serde(default)]
+    pub comments: Vec<Comment>,
+    #[serde(rename = "isDeleted", default)]
+    pub is_deleted: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CommentThreadList {
+    pub value: Vec<CommentThread>,
+}
@@ -0,0 +1,120 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::config::parse_duration;
+
+// Backing off never starts from less than this, so a zero interval can still grow
+const MIN_BACKOFF_BASE: Duration = Duration::from_secs(1);
+
+// Largest factor accepted; anything bigger jumps straight to the max interval anyway
+const MAX_BACKOFF_FACTOR: f64 = 100.0;
+
+/// Settings for slowing down polling while no new PRs arrive
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct PollingConfig {
+    /// Longest wait between polls, e.g. "5m"
+    #[serde(default = "default_max_interval")]
+    pub max_interval: String,
+    /// Factor the interval grows by after every idle poll
+    #[serde(default = "default_backoff_factor")]
+    pub backoff_factor: f64,
+}
+
+fn default_max_interval() -> String {
+    "5m".to_string()
+}
+
+fn default_backoff_factor() -> f64 {
+    2.0
+}
+
+/// Polling interval that backs off while idle and snaps back when new PRs appear
+#[derive(Debug)]
+pub struct AdaptiveInterval {
+    fast: Duration,
+    max: Duration,
+    factor: f64,
+    current: Duration,
+}
+
+impl AdaptiveInterval {
+    /// Start at the fast interval; without a polling config the interval never changes
+    pub fn new(fast: Duration, config: Option<&PollingConfig>) -> Result<Self> {
+        let (max, factor) = match config {
+            Some(config) => {
+                let max = parse_duration(&config.max_interval).context("Invalid polling.max_interval")?;
+                if !(1.0..=MAX_BACKOFF_FACTOR).contains(&config.backoff_factor) {
+                    return Err(anyhow::anyhow!("polling.backoff_factor must be between 1.0 and {}", MAX_BACKOFF_FACTOR));
+                }
+                (max.max(fast), config.backoff_factor)
+            }
+            None => (fast, 1.0),
+        };
+
+        Ok(Self { fast, max, factor, current: fast })
+    }
+
+    pub fn current(&self) -> Duration {
+        self.current
+    }
+
+    /// Adjust the interval after a poll; returns the previous interval if it changed
+    pub fn update(&mut self, found_new_prs: bool) -> Option<Duration> {
+        let previous = self.current;
+        self.current = if found_new_prs {
+            self.fast
+        } else {
+            // Overflowing the duration just means the max interval was reached
+            Duration::try_from_secs_f64(self.current.max(MIN_BACKOFF_BASE).as_secs_f64() * self.factor)
+                .map_or(self.max, |next| next.min(self.max))
+        };
+
+        (self.current != previous).then_some(previous)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(max_interval: &str, backoff_factor: f64) -> PollingConfig {
+        PollingConfig { max_interval: max_interval.to_string(), backoff_factor }
+    }
+
+    #[test]
+    fn interval_backs_off_while_idle_and_resets_on_new_prs() {
+        let mut interval = AdaptiveInterval::new(Duration::from_secs(5), Some(&config("30s", 2.0))).unwrap();
+
+        assert_eq!(interval.update(false), Some(Duration::from_secs(5)));
+        assert_eq!(interval.current(), Duration::from_secs(10));
+        interval.update(false);
+        interval.update(false);
+        assert_eq!(interval.current(), Duration::from_secs(30));
+        assert_eq!(interval.update(false), None);
+
+        assert_eq!(interval.update(true), Some(Duration::from_secs(30)));
+        assert_eq!(interval.current(), Duration::from_secs(5));
+
+        // A zero interval still grows, and without a polling config nothing changes
+        let mut zero = AdaptiveInterval::new(Duration::ZERO, Some(&config("1m", 3.0))).unwrap();
+        zero.update(false);
+        assert_eq!(zero.current(), Duration::from_secs(3));
+        let mut fixed = AdaptiveInterval::new(Duration::from_secs(5), None).unwrap();
+        assert_eq!(fixed.update(false), None);
+    }
+
+    #[test]
+    fn bad_backoff_factors_are_rejected() {
+        for factor in [0.5, f64::NAN, f64::INFINITY, 1e300] {
+            assert!(AdaptiveInterval::new(Duration::from_secs(1), Some(&config("5m", factor))).is_err(), "{}", factor);
+        }
+
+        // The largest factor backs off without overflowing, even from a huge max interval
+        let mut interval = AdaptiveInterval::new(Duration::from_secs(1), Some(&config("99999999999d", MAX_BACKOFF_FACTOR))).unwrap();
+        for _ in 0..20 {
+            interval.update(false);
+        }
+        assert!(interval.current() > Duration::from_secs(1));
+    }
+}
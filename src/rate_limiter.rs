@@ -0,0 +1,207 @@
+use tracing::{info, warn};
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::time::sleep;
+
+// Azure DevOps throttling headers
+const RATE_LIMIT_RESOURCE: &str = "X-RateLimit-Resource";
+const RATE_LIMIT_LIMIT: &str = "X-RateLimit-Limit";
+const RATE_LIMIT_REMAINING: &str = "X-RateLimit-Remaining";
+
+// Below this share of remaining quota we start slowing down
+const SLOWDOWN_THRESHOLD: f64 = 0.25;
+// Never slow down below this share of the configured rate
+const MIN_RATE_FACTOR: f64 = 0.1;
+
+pub const DEFAULT_BURST: u32 = 10;
+pub const DEFAULT_REQUESTS_PER_SECOND: f64 = 5.0;
+
+/// Snapshot of the limiter for logging and inspection
+#[derive(Debug, Clone, Serialize)]
+pub struct RateLimiterStatus {
+    pub tokens: f64,
+    pub requests_per_second: f64,
+    pub paused_for: Option<Duration>,
+    pub resource: Option<String>,
+    pub limit: Option<u64>,
+    pub remaining: Option<u64>,
+}
+
+/// Token bucket shared by every request the client makes, slowed down by ADO's throttling headers
+pub struct RateLimiter {
+    state: Mutex<LimiterState>,
+}
+
+struct LimiterState {
+    tokens: f64,
+    burst: f64,
+    base_rate: f64,
+    rate: f64,
+    last_refill: Instant,
+    paused_until: Option<Instant>,
+    resource: Option<String>,
+    limit: Option<u64>,
+    remaining: Option<u64>,
+}
+
+impl LimiterState {
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.burst);
+        self.last_refill = now;
+    }
+}
+
+impl RateLimiter {
+    pub fn new(burst: u32, requests_per_second: f64) -> Self {
+        let burst = f64::from(burst.max(1));
+        Self {
+            state: Mutex::new(LimiterState {
+                tokens: burst,
+                burst,
+                base_rate: requests_per_second,
+                rate: requests_per_second,
+                last_refill: Instant::now(),
+                paused_until: None,
+                resource: None,
+                limit: None,
+                remaining: None,
+            }),
+        }
+    }
+
+    /// Wait until a request may be sent
+    pub async fn acquire(&self) {
+        loop {
+            let wait = {
+                let mut state = self.state.lock().unwrap();
+                let now = Instant::now();
+
+                match state.paused_until {
+                    Some(until) if until > now => until - now,
+                    _ => {
+                        state.paused_until = None;
+                        state.refill(now);
+                        if state.tokens >= 1.0 {
+                            state.tokens -= 1.0;
+                            return;
+                        }
+                        Duration::from_secs_f64((1.0 - state.tokens) / state.rate)
+                    }
+                }
+            };
+
+            sleep(wait).await;
+        }
+    }
+
+    /// Adjust the limiter to the throttling headers of a response
+    pub fn update_from_headers(&self, headers: &HeaderMap) {
+        let header = |name: &str| headers.get(name).and_then(|value| value.to_str().ok());
+
+        let mut state = self.state.lock().unwrap();
+
+        if let Some(resource) = header(RATE_LIMIT_RESOURCE) {
+            state.resource = Some(resource.to_string());
+        }
+        state.limit = header(RATE_LIMIT_LIMIT).and_then(|value| value.parse().ok()).or(state.limit);
+        state.remaining = header(RATE_LIMIT_REMAINING).and_then(|value| value.parse().ok());
+
+        // Retry-After means ADO wants us to stop completely for a while
+        if let Some(pause) = header(RETRY_AFTER.as_str()).and_then(parse_retry_after) {
+            let until = Instant::now() + pause;
+            if state.paused_until.is_none_or(|current| current < until) {
+                warn!("🛑 Azure DevOps asked us to back off, pausing requests for {}s (resource: {})",
+                    pause.as_secs(), state.resource.as_deref().unwrap_or("unknown"));
+                state.paused_until = Some(until);
+            }
+        }
+
+        // Slow down in proportion to how little quota is left
+        let now = Instant::now();
+        state.refill(now);
+        let factor = match (state.remaining, state.limit) {
+            (Some(remaining), Some(limit)) if limit > 0 => {
+                let share = remaining as f64 / limit as f64;
+                if share < SLOWDOWN_THRESHOLD {
+                    (share / SLOWDOWN_THRESHOLD).max(MIN_RATE_FACTOR)
+                } else {
+                    1.0
+                }
+            }
+            _ => 1.0,
+        };
+
+        let rate = state.base_rate * factor;
+        if (rate - state.rate).abs() > f64::EPSILON {
+            if factor < 1.0 {
+                warn!("🐢 Azure DevOps quota is running low ({}/{} remaining), slowing to {:.2} requests/s",
+                    state.remaining.unwrap_or(0), state.limit.unwrap_or(0), rate);
+            } else {
+                info!("Azure DevOps quota recovered, back to {:.2} requests/s", rate);
+            }
+            state.rate = rate;
+        }
+    }
+
+    pub fn status(&self) -> RateLimiterStatus {
+        let mut state = self.state.lock().unwrap();
+        let now = Instant::now();
+        state.refill(now);
+
+        RateLimiterStatus {
+            tokens: state.tokens,
+            requests_per_second: state.rate,
+            paused_for: state.paused_until.filter(|until| *until > now).map(|until| until - now),
+            resource: state.resource.clone(),
+            limit: state.limit,
+            remaining: state.remaining,
+        }
+    }
+}
+
+/// Parse a Retry-After value, either a number of seconds or an HTTP date such as "Wed, 21 Oct 2026 07:28:00 GMT";
+/// a date in the past means no pause
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    Some((date.with_timezone(&chrono::Utc) - chrono::Utc::now()).to_std().unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    fn retry_after(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, HeaderValue::from_str(value).unwrap());
+        headers
+    }
+
+    #[test]
+    fn retry_after_is_read_as_seconds_or_an_http_date() {
+        let limiter = RateLimiter::new(DEFAULT_BURST, DEFAULT_REQUESTS_PER_SECOND);
+        limiter.update_from_headers(&retry_after("2"));
+        let paused = limiter.status().paused_for.unwrap();
+        assert!(paused > Duration::from_secs(1) && paused <= Duration::from_secs(2), "{:?}", paused);
+
+        let limiter = RateLimiter::new(DEFAULT_BURST, DEFAULT_REQUESTS_PER_SECOND);
+        let date = (chrono::Utc::now() + chrono::Duration::seconds(30)).format("%a, %d %b %Y %H:%M:%S GMT").to_string();
+        limiter.update_from_headers(&retry_after(&date));
+        let paused = limiter.status().paused_for.unwrap();
+        assert!(paused > Duration::from_secs(28) && paused <= Duration::from_secs(30), "{:?}", paused);
+
+        // Dates in the past and values that are neither don't pause anything
+        let limiter = RateLimiter::new(DEFAULT_BURST, DEFAULT_REQUESTS_PER_SECOND);
+        limiter.update_from_headers(&retry_after("Wed, 21 Oct 2015 07:28:00 GMT"));
+        limiter.update_from_headers(&retry_after("soon"));
+        assert!(limiter.status().paused_for.is_none());
+    }
+}
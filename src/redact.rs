@@ -0,0 +1,124 @@
+use anyhow::{Context, Result};
+use base64::{engine::general_purpose, Engine as _};
+use regex::Regex;
+use std::io::{self, Write};
+use std::sync::RwLock;
+
+// What secrets are replaced with
+const PLACEHOLDER: &str = "[REDACTED]";
+
+// Shorter values would redact ordinary words
+const MIN_SECRET_LEN: usize = 4;
+
+// Secrets known to this process; everything leaving it (logs, errors, the decision log, PR comments) goes through here
+static REDACTOR: RwLock<Redactor> = RwLock::new(Redactor { secrets: Vec::new(), patterns: Vec::new() });
+
+struct Redactor {
+    // Longest first, so a secret containing another is replaced whole
+    secrets: Vec<String>,
+    patterns: Vec<Regex>,
+}
+
+/// Never show this value in any output
+pub fn register_secret(secret: &str) {
+    let secret = secret.trim();
+    if secret.len() < MIN_SECRET_LEN {
+        return;
+    }
+
+    let mut redactor = REDACTOR.write().unwrap();
+    if !redactor.secrets.iter().any(|known| known == secret) {
+        redactor.secrets.push(secret.to_string());
+        redactor.secrets.sort_by_key(|known| std::cmp::Reverse(known.len()));
+    }
+}
+
+/// Never show the PAT, nor the Basic auth header made from it
+pub fn register_pat(pat: &str) {
+    register_secret(pat);
+    register_secret(&general_purpose::STANDARD.encode(format!(":{}", pat)));
+}
+
+/// Redact everything matching a regular expression, e.g. other tokens that may show up in responses
+pub fn register_pattern(pattern: &str) -> Result<()> {
+    let regex = Regex::new(pattern).with_context(|| format!("Invalid redact pattern '{}'", pattern))?;
+
+    let mut redactor = REDACTOR.write().unwrap();
+    if !redactor.patterns.iter().any(|known| known.as_str() == regex.as_str()) {
+        redactor.patterns.push(regex);
+    }
+    Ok(())
+}
+
+/// Replace every known secret in the text
+pub fn redact(text: &str) -> String {
+    let redactor = REDACTOR.read().unwrap();
+
+    let mut redacted = text.to_string();
+    for secret in &redactor.secrets {
+        if redacted.contains(secret.as_str()) {
+            redacted = redacted.replace(secret.as_str(), PLACEHOLDER);
+        }
+    }
+    for pattern in &redactor.patterns {
+        redacted = pattern.replace_all(&redacted, PLACEHOLDER).into_owned();
+    }
+    redacted
+}
+
+/// Writer that redacts secrets before passing the text on, used for log output
+pub struct Redacting<W: Write>(pub W);
+
+impl<W: Write> Write for Redacting<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // The log formatter writes each event in one go, so secrets aren't split between writes
+        let text = String::from_utf8_lossy(buf);
+        self.0.write_all(redact(&text).as_bytes())?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registered_pat_and_auth_header_are_redacted() {
+        let pat = "redact-test-pat-0123456789";
+        register_pat(pat);
+        let header = format!("Basic {}", general_purpose::STANDARD.encode(format!(":{}", pat)));
+
+        let redacted = redact(&format!("token {} sent as {}", pat, header));
+
+        assert!(!redacted.contains(pat));
+        assert!(!redacted.contains(&header[6..]));
+        assert_eq!(redacted, "token [REDACTED] sent as Basic [REDACTED]");
+    }
+
+    #[test]
+    fn configured_patterns_are_redacted() {
+        register_pattern(r"ghp_[A-Za-z0-9]{8,}").unwrap();
+
+        let redacted = redact("leaked ghp_abcdEFGH1234 in a response");
+
+        assert_eq!(redacted, "leaked [REDACTED] in a response");
+        assert!(register_pattern("(unclosed").is_err());
+    }
+
+    #[test]
+    fn redacting_writer_removes_secrets_from_output() {
+        let secret = "writer-test-secret-42";
+        register_secret(secret);
+
+        let mut writer = Redacting(Vec::new());
+        write!(writer, "ERROR something failed with {}", secret).unwrap();
+
+        let output = String::from_utf8(writer.0).unwrap();
+        assert!(!output.contains(secret));
+        assert!(output.contains("[REDACTED]"));
+    }
+}
@@ -0,0 +1,969 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use futures::future::{self, OptionFuture};
+use futures::stream::{self, StreamExt};
+use std::future::Future;
+use std::pin::Pin;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::time;
+use tracing::{debug, error, info, info_span, warn, Instrument, Span};
+
+use crate::ado_client::{AzureDevOpsClient, VOTE_REJECT, VOTE_WAIT_FOR_AUTHOR};
+use crate::approval_queue::{self, ApprovalQueue, Revalidation, ScheduledApproval};
+use crate::chatops;
+use crate::completion::{self, CompletionOutcome};
+use crate::conditions::{self, ConditionResult};
+use crate::config::{parse_duration, AppConfig};
+use crate::control;
+use crate::decisions::{Decision, DecisionLog};
+use crate::hook::{self, HookDecision, HookVote};
+use crate::models::PullRequest;
+use crate::polling::AdaptiveInterval;
+use crate::rules::{self, ReviewerSetup, Rule};
+use crate::scripting::{self, ScriptInput};
+use crate::schedule::Schedule;
+use crate::{PendingCompletion, PolicyBlock, ReviewerState, TrackedPr};
+
+/// What happened to a watched PR during a poll
+enum PrOutcome {
+    AlreadyApproved,
+    Blocked,
+    Scheduled,
+    Skipped,
+    Failed(String),
+}
+
+/// What [`Reviewer::run_with`] should do next
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flow {
+    /// Poll now
+    Continue,
+    /// Stop, after letting a poll under way finish
+    Stop,
+}
+
+/// How [`Reviewer::run_with`] ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stopped {
+    /// Between polls, or after the poll under way finished
+    Gracefully,
+    /// The poll under way didn't finish within `shutdown.timeout`
+    DrainTimedOut,
+}
+
+/// Lets the caller of [`Reviewer::run_with`] react to its own events, such as signals, while the engine polls
+pub trait RunHooks {
+    /// Runs alongside every poll; resolves once the engine should stop. After that it is run again alongside the
+    /// poll being drained, and should keep doing its background work without resolving
+    fn while_polling(&mut self) -> impl Future<Output = ()> + Send;
+
+    /// Wait up to `wait` for the next poll, handling whatever arrives meanwhile. The reviewer may be changed,
+    /// e.g. with [`Reviewer::set_config`]
+    fn between_polls(&mut self, reviewer: &mut Reviewer, wait: Duration) -> impl Future<Output = Flow> + Send;
+}
+
+/// Sleeps between polls until `shutdown` completes
+struct StopOn<F> {
+    shutdown: Pin<Box<F>>,
+    stopped: bool,
+}
+
+impl<F: Future<Output = ()> + Send> RunHooks for StopOn<F> {
+    async fn while_polling(&mut self) {
+        if self.stopped {
+            return std::future::pending().await;
+        }
+        (&mut self.shutdown).await;
+        self.stopped = true;
+    }
+
+    async fn between_polls(&mut self, _reviewer: &mut Reviewer, wait: Duration) -> Flow {
+        if self.stopped {
+            return Flow::Stop;
+        }
+        tokio::select! {
+            _ = time::sleep(wait) => Flow::Continue,
+            _ = &mut self.shutdown => {
+                self.stopped = true;
+                Flow::Stop
+            }
+        }
+    }
+}
+
+/// Polls Azure DevOps for PRs from watched users and approves them according to the configured rules
+pub struct Reviewer {
+    client: AzureDevOpsClient,
+    config: AppConfig,
+    schedule: Option<Schedule>,
+    state: Arc<ReviewerState>,
+}
+
+impl Reviewer {
+    /// Set up the engine, resuming the approvals scheduled by an earlier run.
+    /// Only PRs created from now on are approved, unless they are rechecked
+    pub fn new(client: AzureDevOpsClient, config: AppConfig) -> Result<Self> {
+        let decisions = Arc::new(DecisionLog::new(config.decision_log.as_ref().map(PathBuf::from)));
+        let schedule = config.schedule.as_ref().map(Schedule::from_config).transpose()
+            .context("Invalid schedule")?;
+        let approval_queue = ApprovalQueue::load(&config.approval_queue)?;
+        if !approval_queue.entries().is_empty() {
+            info!("Resuming {} scheduled approvals from {}", approval_queue.entries().len(), config.approval_queue);
+        }
+
+        let state = Arc::new(ReviewerState::new(decisions, approval_queue));
+        Ok(Self { client, config, schedule, state })
+    }
+
+    pub fn client(&self) -> &AzureDevOpsClient {
+        &self.client
+    }
+
+    pub fn config(&self) -> &AppConfig {
+        &self.config
+    }
+
+    /// Swap in a new configuration between polls; the client, decision log and approval queue are kept
+    pub fn set_config(&mut self, config: AppConfig) -> Result<()> {
+        self.schedule = config.schedule.as_ref().map(Schedule::from_config).transpose()
+            .context("Invalid schedule")?;
+        AdaptiveInterval::new(Duration::ZERO, config.polling.as_ref())?;
+        self.config = config;
+        Ok(())
+    }
+
+    /// The engine's state, to inspect or steer it from elsewhere while it runs
+    pub fn state(&self) -> &Arc<ReviewerState> {
+        &self.state
+    }
+
+    pub fn decisions(&self) -> &Arc<DecisionLog> {
+        &self.state.decisions
+    }
+
+    pub fn approval_queue(&self) -> &Mutex<ApprovalQueue> {
+        &self.state.approval_queue
+    }
+
+    /// Call `callback` with every decision from now on, after it was logged
+    pub fn on_decision(&self, callback: impl Fn(&Decision) + Send + Sync + 'static) {
+        self.state.decisions.subscribe(callback);
+    }
+
+    /// Poll once: look for new PRs, approve the ones that are due and complete the ones that are ready.
+    /// Returns how many PRs were new; approvals and completions are attempted even if listing PRs failed
+    pub async fn run_once(&self) -> Result<usize> {
+        let new_count = check_and_approve_prs(&self.client, &self.config, self.schedule.as_ref(), &self.state).await;
+
+        process_due_approvals(&self.client, &self.config, self.schedule.as_ref(), &self.state).await;
+
+        process_pending_completions(&self.client, &self.config, &self.state).await;
+
+        new_count
+    }
+
+    /// Poll every `interval` (backing off while idle if the config says so) until `shutdown` completes.
+    /// A poll under way when it does is finished first, within `shutdown.timeout`, and scheduled approvals
+    /// are saved before returning
+    pub async fn run(&mut self, interval: Duration, shutdown: impl Future<Output = ()> + Send) -> Result<Stopped> {
+        self.run_with(interval, &mut StopOn { shutdown: Box::pin(shutdown), stopped: false }).await
+    }
+
+    /// Like [`Reviewer::run`], with `hooks` deciding when to stop and what happens between polls
+    pub async fn run_with(&mut self, interval: Duration, hooks: &mut impl RunHooks) -> Result<Stopped> {
+        let mut polling_interval = AdaptiveInterval::new(interval, self.config.polling.as_ref())?;
+        let mut poll_number: u64 = 0;
+
+        let stopped = loop {
+            let paused = self.is_paused().await;
+            if !paused {
+                poll_number += 1;
+                *self.state.next_poll.lock().await = None;
+                let poll = self.run_once().instrument(info_span!("poll", poll = poll_number));
+                tokio::pin!(poll);
+                let result = tokio::select! {
+                    result = &mut poll => result,
+                    _ = hooks.while_polling() => break self.drain(poll, hooks).await,
+                };
+                let found_new_prs = match result {
+                    Ok(new_count) => new_count > 0,
+                    Err(e) => {
+                        error!("Error checking PRs: {:#}", e);
+                        false
+                    }
+                };
+
+                let limiter = self.client.rate_limit_status();
+                debug!("Rate limiter: {:.1} tokens, {:.2} requests/s, quota {}/{} ({}){}", limiter.tokens,
+                    limiter.requests_per_second,
+                    limiter.remaining.map_or("?".to_string(), |remaining| remaining.to_string()),
+                    limiter.limit.map_or("?".to_string(), |limit| limit.to_string()),
+                    limiter.resource.as_deref().unwrap_or("no resource reported"),
+                    limiter.paused_for.map(|paused| format!(", paused for {}s", paused.as_secs())).unwrap_or_default());
+
+                if let Some(previous) = polling_interval.update(found_new_prs) {
+                    info!("⏱ Polling interval changed from {}s to {}s ({})", previous.as_secs(), polling_interval.current().as_secs(),
+                        if found_new_prs { "new pull requests" } else { "idle" });
+                }
+            }
+
+            // Don't sleep past the next scheduled approval
+            let mut wait = polling_interval.current();
+            if let Some(next_due) = self.next_due_approval().await {
+                wait = wait.min((next_due - Utc::now()).to_std().unwrap_or_default());
+            }
+            *self.state.next_poll.lock().await = (!paused).then(|| Utc::now() + chrono::Duration::from_std(wait).unwrap_or_default());
+
+            let polling = self.config.polling.clone();
+            if hooks.between_polls(self, wait).await == Flow::Stop {
+                break Stopped::Gracefully;
+            }
+            // A reloaded config may back off differently
+            if self.config.polling != polling {
+                polling_interval = AdaptiveInterval::new(interval, self.config.polling.as_ref())?;
+            }
+        };
+
+        self.state.approval_queue.lock().await.save();
+        Ok(stopped)
+    }
+
+    /// Let the poll under way finish, but don't hold up stopping for longer than `shutdown.timeout`
+    async fn drain(&self, poll: Pin<&mut impl Future>, hooks: &mut impl RunHooks) -> Stopped {
+        let timeout = self.config.shutdown.clone().unwrap_or_default().timeout;
+        let limit = parse_duration(&timeout).unwrap_or_default();
+        info!("Waiting up to {} for the current poll to finish...", timeout);
+        tokio::select! {
+            _ = poll => Stopped::Gracefully,
+            _ = time::sleep(limit) => {
+                error!("❌ The current poll did not finish within {}, stopping anyway", timeout);
+                Stopped::DrainTimedOut
+            }
+            _ = hooks.while_polling() => Stopped::Gracefully,
+        }
+    }
+
+    /// When the earliest scheduled approval is due
+    pub async fn next_due_approval(&self) -> Option<DateTime<Utc>> {
+        self.state.approval_queue.lock().await.entries().first().map(|entry| entry.due_at)
+    }
+
+    /// Approve a PR right away, regardless of rules and schedules
+    pub async fn approve(&self, pr_id: i32, requested_by: &str) -> Result<()> {
+        let reviewer_id = self.config.reviewer_id.as_deref().context("No reviewer ID configured")?;
+        let pr = self.client.get_pull_request_by_id(pr_id).await
+            .with_context(|| format!("Failed to fetch PR #{}", pr_id))?;
+        control::force_approve(&self.client, &self.state, &pr, reviewer_id, requested_by).await
+    }
+
+    /// Stop handling a PR: it won't be approved, retried or completed
+    pub async fn skip(&self, pr_id: i32, requested_by: &str) -> Result<()> {
+        let pr = self.client.get_pull_request_by_id(pr_id).await
+            .with_context(|| format!("Failed to fetch PR #{}", pr_id))?;
+        control::skip(&self.state, &pr, requested_by).await;
+        Ok(())
+    }
+
+    /// Evaluate a PR again on the next poll, even if it was created before the engine started
+    pub async fn recheck(&self, pr_id: i32) {
+        self.state.recheck(pr_id).await;
+    }
+
+    /// Stop or restart polling; [`Reviewer::run_once`] still polls while paused
+    pub async fn set_paused(&self, paused: bool) {
+        self.state.set_paused(paused).await;
+    }
+
+    pub async fn is_paused(&self) -> bool {
+        self.state.is_paused().await
+    }
+}
+
+/// Look for new PRs and schedule approvals for the watched ones; returns how many PRs were new
+async fn check_and_approve_prs(
+    client: &AzureDevOpsClient,
+    config: &AppConfig,
+    schedule: Option<&Schedule>,
+    state: &ReviewerState,
+) -> Result<usize> {
+    // Check if reviewer ID is configured
+    let reviewer_id = match &config.reviewer_id {
+        Some(id) => id,
+        None => {
+            error!("No reviewer ID configured. Cannot approve PRs.");
+            return Ok(0);
+        }
+    };
+
+    // Get active pull requests
+    let mut prs = client.get_active_pull_requests().await?;
+    
+    let schedule_open = schedule.is_none_or(|schedule| schedule.is_open(Utc::now()));
+    if schedule_open {
+        fetch_queued_prs(client, config, state, &mut prs).await;
+    }
+    
+    // The list holds every active PR, so PRs missing from it were completed or abandoned: they leave the
+    // dashboard feed, and are no longer revisited for the reason they were blocked
+    state.tracked_prs.lock().await.retain(|pr_id, _| prs.iter().any(|pr| pr.pull_request_id == *pr_id));
+    state.blocked_prs.lock().await.retain(|pr_id, _| prs.iter().any(|pr| pr.pull_request_id == *pr_id));
+    state.policy_blocks.lock().await.retain(|pr_id, _| prs.iter().any(|pr| pr.pull_request_id == *pr_id));
+    
+    if prs.is_empty() {
+        info!("No active pull requests found");
+        return Ok(0);
+    }
+    
+    // Run any chat-ops commands first so skips and rechecks apply to this poll
+    chatops::process_commands(client, config, &prs, reviewer_id, state).await;
+    
+    let mut new_prs = Vec::new();
+    
+    let mut revisit_count = 0;
+    
+    // Lock the mutex to safely access the HashSet of seen PRs
+    let mut seen_prs = state.seen_prs.lock().await;
+    let blocked_prs = state.blocked_prs.lock().await;
+    let queued_prs = state.queued_prs.lock().await;
+    for pr in &prs {
+        if !seen_prs.contains(&pr.pull_request_id) {
+            new_prs.push(pr);
+            seen_prs.insert(pr.pull_request_id);
+        } else if blocked_prs.contains_key(&pr.pull_request_id)
+            || (schedule_open && queued_prs.contains(&pr.pull_request_id))
+        {
+            // Blocked PRs are re-evaluated until they meet their rule's conditions,
+            // and queued PRs are picked up once the approval window opens
+            new_prs.push(pr);
+            revisit_count += 1;
+        }
+    }
+    drop(queued_prs);
+    drop(blocked_prs);
+    drop(seen_prs);
+    
+    if new_prs.is_empty() {
+        info!("No new pull requests found");
+        return Ok(0);
+    }
+    
+    let new_count = new_prs.len() - revisit_count;
+    if revisit_count > 0 {
+        info!("Found {} new pull requests and {} to revisit", new_count, revisit_count);
+    } else {
+        info!("Found {} new pull requests", new_prs.len());
+    }
+    
+    // PRs explicitly rechecked skip the start time check
+    let rechecks = std::mem::take(&mut *state.recheck_prs.lock().await);
+    
+    let watched_prs: Vec<_> = new_prs.iter()
+        .filter(|&&pr| {
+            // Check if user is in watched list
+            let is_watched_user = config.watched_users.contains(&pr.created_by.display_name);
+            
+            if rechecks.contains(&pr.pull_request_id) {
+                return is_watched_user;
+            }
+            
+            // Parse the PR creation date
+            if let Ok(pr_creation_date) = DateTime::parse_from_rfc3339(&pr.creation_date) {
+                let pr_creation_utc = pr_creation_date.with_timezone(&Utc);
+                
+                // Only include PRs created after the reviewer started
+                if pr_creation_utc < state.started {
+                    info!("Skipping PR #{} from {} - created before program start", 
+                          pr.pull_request_id, pr.created_by.display_name);
+                    return false;
+                }
+                
+                is_watched_user
+            } else {
+                // If we can't parse the date, log a warning but still include the PR if it's from a watched user
+                warn!("Could not parse creation date for PR #{}", pr.pull_request_id);
+                is_watched_user
+            }
+        })
+        .collect();
+    
+    let mut tracked_prs = state.tracked_prs.lock().await;
+    for pr in &watched_prs {
+        tracked_prs.insert(pr.pull_request_id, TrackedPr {
+            pr_id: pr.pull_request_id,
+            repository: pr.repository.name.clone(),
+            author: pr.created_by.display_name.clone(),
+            title: pr.title.clone(),
+            created: DateTime::parse_from_rfc3339(&pr.creation_date).ok().map(|date| date.with_timezone(&Utc)),
+        });
+    }
+    drop(tracked_prs);
+    
+    if !watched_prs.is_empty() {
+        info!("Found {} PRs from watched users created after program start", watched_prs.len());
+    } else {
+        info!("No PRs from watched users found in this poll that were created after program start");
+        return Ok(new_count);
+    }
+    
+    // Outside the approval window, queue the PRs until it opens instead of dropping them
+    let mut queued_prs = state.queued_prs.lock().await;
+    if let Some(schedule) = schedule.filter(|_| !schedule_open) {
+        queued_prs.extend(watched_prs.iter().map(|pr| pr.pull_request_id));
+        match schedule.next_open(Utc::now()) {
+            Some(next_open) => info!("🌙 Outside the approval window, {} PRs queued until {}", queued_prs.len(),
+                next_open.with_timezone(&schedule.timezone()).format("%Y-%m-%d %H:%M %Z")),
+            None => info!("🌙 Outside the approval window, {} PRs queued", queued_prs.len()),
+        }
+        return Ok(new_count);
+    }
+    for pr in &watched_prs {
+        queued_prs.remove(&pr.pull_request_id);
+    }
+    drop(queued_prs);
+    
+    // Process PRs from watched users a few at a time; results come back in PR order
+    let outcomes: Vec<(i32, PrOutcome)> = stream::iter(watched_prs.iter().copied())
+        .map(|pr| async move {
+            let outcome = process_watched_pr(client, config, state, pr, reviewer_id)
+                .instrument(pr_span(pr.pull_request_id, &pr.repository.name, &pr.created_by.display_name))
+                .await;
+            (pr.pull_request_id, outcome)
+        })
+        .buffered(config.max_concurrency)
+        .collect()
+        .await;
+    
+    log_poll_summary(&outcomes);
+    
+    Ok(new_count)
+}
+
+/// Check one watched PR against its rule and schedule its approval
+async fn process_watched_pr(
+    client: &AzureDevOpsClient,
+    config: &AppConfig,
+    state: &ReviewerState,
+    pr: &PullRequest,
+    reviewer_id: &str,
+) -> PrOutcome {
+    info!("🔍 Processing PR #{} from watched user {} - '{}'", 
+        pr.pull_request_id, pr.created_by.display_name, pr.title);
+    
+    let rule = rules::find_rule(&config.rules, pr);
+    let rule_name = rule.map(|r| r.name.as_str());
+    Span::current().record("rule", rule_name);
+
+    // The rule's hook and scripts run while the approval status and conditions are checked. When those
+    // already decide, the hook and scripts are dropped, which kills a hook still running
+    let policies = async {
+        match rule.filter(|r| r.hook.is_some() || !r.scripts.is_empty()) {
+            Some(rule) => policy_decisions(client, config, state, rule, pr).await,
+            None => Ok(Vec::new()),
+        }
+    };
+    let checked = check_before_approval(client, state, rule, pr, reviewer_id);
+    tokio::pin!(policies, checked);
+    let policy_decisions = tokio::select! {
+        outcome = &mut checked => match outcome {
+            Some(outcome) => return outcome,
+            None => policies.await,
+        },
+        decisions = &mut policies => match checked.await {
+            Some(outcome) => return outcome,
+            None => decisions,
+        },
+    };
+    match policy_decisions {
+        Ok(policy_decisions) => {
+            for (source, decision) in policy_decisions {
+                if let Some(outcome) = apply_policy_decision(client, state, rule, pr, reviewer_id, &source, decision).await {
+                    return outcome;
+                }
+            }
+        }
+        Err(e) => {
+            error!("❌ Failed to get the changes of PR #{} for its hook and scripts: {}", pr.pull_request_id, e);
+            state.decisions.record(Decision::new(pr, rule_name, "approve", "failed", e.to_string()));
+            return PrOutcome::Failed(e.to_string());
+        }
+    }
+    if let Some(rule) = rule
+        && state.blocked_prs.lock().await.remove(&pr.pull_request_id).is_some()
+    {
+        info!("PR #{} now meets the conditions of rule '{}'", pr.pull_request_id, rule.name);
+    }
+
+    // Schedule the approval; rules with approve_after wait before voting
+    let delay = match rule.and_then(|r| r.approve_after.as_ref()).map(|delay| delay.sample()).transpose() {
+        Ok(delay) => delay.unwrap_or_default(),
+        Err(e) => {
+            error!("❌ Invalid approval delay for PR #{}: {}", pr.pull_request_id, e);
+            return PrOutcome::Failed(e.to_string());
+        }
+    };
+    let Some(due_at) = chrono::Duration::from_std(delay).ok().and_then(|delay| Utc::now().checked_add_signed(delay)) else {
+        error!("❌ Approval delay for PR #{} is too long", pr.pull_request_id);
+        return PrOutcome::Failed("approval delay is too long".to_string());
+    };
+    state.approval_queue.lock().await.schedule(ScheduledApproval::new(pr, rule_name, due_at));
+
+    if !delay.is_zero() {
+        let due = due_at.format("%Y-%m-%d %H:%M:%S UTC").to_string();
+        info!("⏲ PR #{} will be approved at {} unless it changes", pr.pull_request_id, due);
+        state.decisions.record(Decision::new(pr, rule_name, "approve", "scheduled", format!("due at {}", due)));
+    }
+    PrOutcome::Scheduled
+}
+
+/// Check whether the PR is already approved and meets the rule's conditions; returns an outcome when it shouldn't be approved
+async fn check_before_approval(
+    client: &AzureDevOpsClient,
+    state: &ReviewerState,
+    rule: Option<&Rule>,
+    pr: &PullRequest,
+    reviewer_id: &str,
+) -> Option<PrOutcome> {
+    let rule_name = rule.map(|r| r.name.as_str());
+
+    // Check if we've already approved this PR using our reviewer ID
+    match client.check_approval_status(pr, reviewer_id).await {
+        Ok(already_approved) => {
+            if already_approved {
+                info!("✓ PR #{} is already approved", pr.pull_request_id);
+                return Some(PrOutcome::AlreadyApproved);
+            } else {
+                info!("PR #{} needs approval, will approve now...", pr.pull_request_id);
+            }
+        },
+        Err(e) => {
+            warn!("⚠ Failed to check approval status for PR #{}: {}", pr.pull_request_id, e);
+            info!("Will attempt to approve PR #{} anyway", pr.pull_request_id);
+        }
+    }
+    
+    // Make sure the PR meets the rule's conditions before approving it
+    if let Some(rule) = rule {
+        match conditions::check_conditions(client, rule, pr, reviewer_id).await {
+            Ok(ConditionResult::Passed) => {}
+            Ok(ConditionResult::Failed { reason, comment }) => {
+                if state.blocked_prs.lock().await.get(&pr.pull_request_id) == Some(&reason) {
+                    // Still blocked for the same reason; we already voted and commented
+                    return Some(PrOutcome::Blocked);
+                }
+
+                warn!("⛔ PR #{} does not meet the conditions of rule '{}': {}", pr.pull_request_id, rule.name, reason);
+                if let Some(comment) = comment {
+                    if let Err(e) = client.vote_on_pull_request(pr, reviewer_id, VOTE_WAIT_FOR_AUTHOR, &comment, rule.requires_reviewer()).await {
+                        error!("❌ Failed to vote wait-for-author on PR #{}: {}", pr.pull_request_id, e);
+                    }
+                    if let Err(e) = client.create_thread(pr, &comment).await {
+                        warn!("⚠ Failed to comment on PR #{}: {}", pr.pull_request_id, e);
+                    }
+                }
+                state.decisions.record(Decision::new(pr, rule_name, "approve", "blocked", reason.clone()));
+                state.blocked_prs.lock().await.insert(pr.pull_request_id, reason);
+                return Some(PrOutcome::Blocked);
+            }
+            Err(e) => {
+                error!("❌ Failed to check conditions for PR #{}: {}", pr.pull_request_id, e);
+                state.decisions.record(Decision::new(pr, rule_name, "approve", "failed", e.to_string()));
+                return Some(PrOutcome::Failed(e.to_string()));
+            }
+        }
+    }
+
+    None
+}
+
+/// Ask the rule's hook and each of its scripts what to do with the PR, hook first. A PR they blocked isn't asked
+/// about again until it is pushed to, rechecked, or the rule's hook or scripts change
+async fn policy_decisions(
+    client: &AzureDevOpsClient,
+    config: &AppConfig,
+    state: &ReviewerState,
+    rule: &Rule,
+    pr: &PullRequest,
+) -> Result<Vec<(String, HookDecision)>> {
+    if let Some(block) = state.policy_blocks.lock().await.get(&pr.pull_request_id)
+        && block.applies_to(rule, pr)
+    {
+        debug!("PR #{} is still blocked by {} for commit {}", pr.pull_request_id, block.source, block.commit);
+        return Ok(vec![(block.source.clone(), block.decision.clone())]);
+    }
+
+    let (reviewers, changes) = tokio::try_join!(client.get_reviewers(pr), client.get_changed_files(pr))?;
+    let hook_input = rule.hook.as_ref().map(|_| hook::input(pr, &reviewers, &changes)).transpose()?;
+    let timezone = config.schedule.as_ref().and_then(|schedule| schedule.timezone.parse().ok());
+    let script_input = ScriptInput::new(pr, &reviewers, &changes, timezone);
+    let limits = config.scripting.clone().unwrap_or_default();
+
+    // The hook and every script run at the same time; the first of them, in order, that doesn't approve decides
+    let hook = OptionFuture::from(rule.hook.as_ref().zip(hook_input.as_deref()).map(|(hook, input)| hook::run(hook, input)));
+    let scripts = future::join_all(rule.scripts.iter()
+        .map(|path| scripting::decide(&state.scripts, path, &script_input, &limits)));
+    let (hook_decision, script_decisions) = tokio::join!(hook, scripts);
+
+    let decisions: Vec<_> = hook_decision.map(|decision| ("hook".to_string(), decision)).into_iter()
+        .chain(rule.scripts.iter().map(|path| format!("script {}", path)).zip(script_decisions))
+        .collect();
+
+    let blocking = decisions.iter().find(|(_, decision)| decision.vote != HookVote::Approve)
+        .filter(|(_, decision)| matches!(decision.vote, HookVote::WaitForAuthor | HookVote::Reject));
+    let mut policy_blocks = state.policy_blocks.lock().await;
+    match (blocking, &pr.last_merge_source_commit) {
+        (Some((source, decision)), Some(commit)) => {
+            policy_blocks.insert(pr.pull_request_id, PolicyBlock {
+                commit: commit.commit_id.clone(),
+                hook: rule.hook.clone(),
+                scripts: rule.scripts.clone(),
+                source: source.clone(),
+                decision: decision.clone(),
+            });
+        }
+        _ => {
+            policy_blocks.remove(&pr.pull_request_id);
+        }
+    }
+    Ok(decisions)
+}
+
+/// Act on a decision of the rule's hook or one of its scripts; returns an outcome unless the approval should go ahead
+async fn apply_policy_decision(
+    client: &AzureDevOpsClient,
+    state: &ReviewerState,
+    rule: Option<&rules::Rule>,
+    pr: &PullRequest,
+    reviewer_id: &str,
+    source: &str,
+    decision: HookDecision,
+) -> Option<PrOutcome> {
+    let rule_name = rule.map(|r| r.name.as_str());
+    let vote = match decision.vote {
+        HookVote::Approve => {
+            info!("🪝 PR #{} approved by {}: {}", pr.pull_request_id, source, decision.reason());
+            if let Some(comment) = &decision.comment
+                && let Err(e) = client.create_thread(pr, comment).await
+            {
+                warn!("⚠ Failed to comment on PR #{}: {}", pr.pull_request_id, e);
+            }
+            return None;
+        }
+        HookVote::Skip => {
+            // A hook or script that fails to decide, e.g. one that exits non-zero or times out, skips the PR too
+            if decision.failed {
+                warn!("⏭ {} failed for PR #{}, skipping it: {}", source, pr.pull_request_id, decision.reason());
+            } else {
+                warn!("⏭ PR #{} skipped by {}: {}", pr.pull_request_id, source, decision.reason());
+            }
+            // Skipped for good, even if an earlier run blocked it
+            state.blocked_prs.lock().await.remove(&pr.pull_request_id);
+            state.decisions.record(Decision::new(pr, rule_name, "approve", "skipped", format!("{}: {}", source, decision.reason())));
+            return Some(PrOutcome::Skipped);
+        }
+        HookVote::WaitForAuthor => VOTE_WAIT_FOR_AUTHOR,
+        HookVote::Reject => VOTE_REJECT,
+    };
+
+    let reason = format!("{}: {}", source, decision.reason());
+    if state.blocked_prs.lock().await.get(&pr.pull_request_id) == Some(&reason) {
+        // Still blocked for the same reason; we already voted and commented
+        return Some(PrOutcome::Blocked);
+    }
+
+    warn!("⛔ PR #{} blocked by {} with vote {}: {}", pr.pull_request_id, source, vote, decision.reason());
+    let comment = decision.comment.as_deref().unwrap_or(decision.reason());
+    if let Err(e) = client.vote_on_pull_request(pr, reviewer_id, vote, comment, rule.is_some_and(rules::Rule::requires_reviewer)).await {
+        error!("❌ Failed to vote {} on PR #{}: {}", vote, pr.pull_request_id, e);
+    }
+    if let Some(comment) = &decision.comment
+        && let Err(e) = client.create_thread(pr, comment).await
+    {
+        warn!("⚠ Failed to comment on PR #{}: {}", pr.pull_request_id, e);
+    }
+    state.decisions.record(Decision::new(pr, rule_name, "approve", "blocked", reason.clone()));
+    state.blocked_prs.lock().await.insert(pr.pull_request_id, reason);
+    Some(PrOutcome::Blocked)
+}
+
+/// Span tying together everything logged while handling one PR; the rule is recorded once known
+fn pr_span(pr_id: i32, repo: &str, author: &str) -> Span {
+    info_span!("pr", pr_id, repo = %repo, author = %author, rule = tracing::field::Empty)
+}
+
+/// Log how every PR of this poll was handled, in the order the PRs were listed
+fn log_poll_summary(outcomes: &[(i32, PrOutcome)]) {
+    let count = |wanted: fn(&PrOutcome) -> bool| outcomes.iter().filter(|(_, outcome)| wanted(outcome)).count();
+    info!("📋 Processed {} PRs: {} scheduled, {} already approved, {} blocked, {} skipped, {} failed", outcomes.len(),
+        count(|o| matches!(o, PrOutcome::Scheduled)),
+        count(|o| matches!(o, PrOutcome::AlreadyApproved)),
+        count(|o| matches!(o, PrOutcome::Blocked)),
+        count(|o| matches!(o, PrOutcome::Skipped)),
+        count(|o| matches!(o, PrOutcome::Failed(_))));
+
+    let failed: Vec<String> = outcomes.iter()
+        .filter_map(|(pr_id, outcome)| match outcome {
+            PrOutcome::Failed(reason) => Some(format!("#{} ({})", pr_id, reason)),
+            _ => None,
+        })
+        .collect();
+    if !failed.is_empty() {
+        warn!("Failed PRs this poll: {}", failed.join(", "));
+    }
+}
+
+/// Approve every scheduled PR whose timer has fired, after checking it is still worth approving
+async fn process_due_approvals(
+    client: &AzureDevOpsClient,
+    config: &AppConfig,
+    schedule: Option<&Schedule>,
+    state: &ReviewerState,
+) {
+    // Delayed approvals wait for the approval window like everything else
+    if schedule.is_some_and(|schedule| !schedule.is_open(Utc::now())) {
+        return;
+    }
+
+    let Some(reviewer_id) = &config.reviewer_id else {
+        return;
+    };
+
+    let due = state.approval_queue.lock().await.due(Utc::now());
+    stream::iter(due)
+        .for_each_concurrent(config.max_concurrency, |approval| {
+            let span = pr_span(approval.pr_id, &approval.repository, &approval.author);
+            span.record("rule", approval.rule.as_deref());
+            process_due_approval(client, config, state, approval, reviewer_id).instrument(span)
+        })
+        .await;
+}
+
+/// Re-validate one scheduled approval and approve the PR if nothing changed
+async fn process_due_approval(
+    client: &AzureDevOpsClient,
+    config: &AppConfig,
+    state: &ReviewerState,
+    approval: ScheduledApproval,
+    reviewer_id: &str,
+) {
+    let pr_id = approval.pr_id;
+    let rule_name = approval.rule.as_deref();
+
+    match approval_queue::revalidate(client, &approval).await {
+        Ok(Revalidation::Ready(pr)) => {
+            let rule = rule_name.and_then(|name| config.rules.iter().find(|rule| rule.name == name));
+            approve_pr(client, state, &pr, rule, reviewer_id).await;
+        }
+        Ok(Revalidation::PushedTo(pr)) => {
+            info!("🔄 PR #{} was pushed to after its approval was scheduled, re-evaluating it", pr_id);
+            state.decisions.record(Decision::new(&pr, rule_name, "approve", "cancelled", "new commits pushed"));
+            state.seen_prs.lock().await.remove(&pr_id);
+            state.recheck_prs.lock().await.insert(pr_id);
+        }
+        Ok(Revalidation::Cancelled(pr, reason)) => {
+            info!("🚫 Cancelled scheduled approval of PR #{}: {}", pr_id, reason);
+            state.decisions.record(Decision::new(&pr, rule_name, "approve", "cancelled", reason));
+        }
+        Err(e) => {
+            // Leave the approval queued and try again on the next poll
+            warn!("⚠ Failed to re-validate PR #{} before approving, will retry: {}", pr_id, e);
+            return;
+        }
+    }
+
+    state.approval_queue.lock().await.finish(&approval);
+}
+
+/// Add ourselves as a reviewer if the rule asks for it, vote to approve and run the rule's follow-up actions
+async fn approve_pr(
+    client: &AzureDevOpsClient,
+    state: &ReviewerState,
+    pr: &PullRequest,
+    rule: Option<&rules::Rule>,
+    reviewer_id: &str,
+) {
+    let rule_name = rule.map(|r| r.name.as_str());
+
+    // Explicitly add ourselves as a reviewer when the rule asks for it
+    let mut added_as_reviewer = false;
+    if let Some(settings) = rule.and_then(|r| r.add_reviewers.as_ref()) {
+        match rules::add_reviewers(client, settings, pr, reviewer_id).await {
+            Ok(ReviewerSetup::Added) => {
+                info!("➕ Added ourselves as {} reviewer on PR #{}",
+                    if settings.required { "a required" } else { "an optional" }, pr.pull_request_id);
+                added_as_reviewer = true;
+            }
+            Ok(ReviewerSetup::AlreadyReviewer) => (),
+            Ok(ReviewerSetup::Forbidden(reason)) => {
+                warn!("⚠ Repository policy does not allow adding us to PR #{}, skipping it", pr.pull_request_id);
+                state.decisions.record(Decision::new(pr, rule_name, "approve", "skipped", reason));
+                return;
+            }
+            Err(e) => {
+                warn!("⚠ Failed to add reviewers to PR #{}: {}", pr.pull_request_id, e);
+                info!("Will attempt to approve PR #{} anyway", pr.pull_request_id);
+            }
+        }
+    }
+
+    // Try to approve the PR using our reviewer ID
+    match client.approve_pull_request(pr, reviewer_id, rule.is_some_and(rules::Rule::requires_reviewer)).await {
+        Ok(_) => {
+            info!("✅ Successfully approved PR #{} from {}", 
+                pr.pull_request_id, pr.created_by.display_name);
+            info!("Approval timestamp: {}", chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC"));
+            state.decisions.record(Decision::new(pr, rule_name, "approve", "approved", ""));
+
+            // Run any follow-up actions configured for this PR's rule
+            if let Some(rule) = rule {
+                if let Err(e) = rules::apply_post_approval_actions(client, rule, pr, reviewer_id).await {
+                    error!("❌ Failed to run actions of rule '{}' on PR #{}: {}", rule.name, pr.pull_request_id, e);
+                }
+
+                if rule.complete.is_some() {
+                    info!("PR #{} will be completed once policies pass (rule '{}')", pr.pull_request_id, rule.name);
+                    state.pending_completions.lock().await.insert(pr.pull_request_id, PendingCompletion {
+                        rule: rule.name.clone(),
+                        last_outcome: None,
+                    });
+                }
+            }
+        }
+        Err(e) => {
+            error!("❌ Failed to approve PR #{}: {}", pr.pull_request_id, e);
+            state.decisions.record(Decision::new(pr, rule_name, "approve", "failed", e.to_string()));
+
+            // Don't leave ourselves on the PR as a reviewer that never voted
+            if added_as_reviewer && let Err(e) = client.remove_reviewer(pr, reviewer_id).await {
+                warn!("⚠ Failed to remove ourselves from PR #{}: {}", pr.pull_request_id, e);
+            }
+        }
+    }
+}
+
+/// Add queued PRs that are no longer in the active PR list, dropping any that were closed
+async fn fetch_queued_prs(client: &AzureDevOpsClient, config: &AppConfig, state: &ReviewerState, prs: &mut Vec<PullRequest>) {
+    let mut missing: Vec<i32> = state.queued_prs.lock().await.iter()
+        .filter(|&&id| !prs.iter().any(|pr| pr.pull_request_id == id))
+        .copied()
+        .collect();
+    missing.sort_unstable();
+
+    let results: Vec<_> = stream::iter(missing)
+        .map(|pr_id| async move { (pr_id, client.get_pull_request_by_id(pr_id).await) })
+        .buffered(config.max_concurrency)
+        .collect()
+        .await;
+
+    for (pr_id, result) in results {
+        match result {
+            Ok(pr) if pr.status.as_deref().is_none_or(|status| status == "active") => prs.push(pr),
+            Ok(_) => {
+                info!("Queued PR #{} is no longer active, removing it from the queue", pr_id);
+                state.queued_prs.lock().await.remove(&pr_id);
+            }
+            Err(e) => warn!("⚠ Failed to fetch queued PR #{}: {}", pr_id, e),
+        }
+    }
+}
+
+/// Try to complete every approved PR whose rule opted into direct completion
+async fn process_pending_completions(client: &AzureDevOpsClient, config: &AppConfig, state: &ReviewerState) {
+    // Work on a snapshot so the lock isn't held while talking to Azure DevOps
+    let mut pending: Vec<(i32, String)> = state.pending_completions.lock().await.iter()
+        .map(|(&pr_id, entry)| (pr_id, entry.rule.clone()))
+        .collect();
+    pending.sort_unstable();
+
+    let results: Vec<_> = stream::iter(pending)
+        .map(|(pr_id, rule_name)| async move {
+            // The rule may have been removed from the config since the PR was approved
+            let action = config.rules.iter()
+                .find(|rule| rule.name == rule_name)
+                .and_then(|rule| rule.complete.as_ref());
+            let result = match action {
+                Some(action) => Some(completion::try_complete(client, pr_id, action)
+                    .instrument(info_span!("pr", pr_id, rule = %rule_name))
+                    .await),
+                None => None,
+            };
+            (pr_id, rule_name, result)
+        })
+        .buffered(config.max_concurrency)
+        .collect()
+        .await;
+
+    let mut pending = state.pending_completions.lock().await;
+    for (pr_id, rule_name, result) in results {
+        let (pr, outcome) = match result {
+            Some(Ok(result)) => result,
+            Some(Err(e)) => {
+                warn!("⚠ Failed to check completion of PR #{}: {}", pr_id, e);
+                continue;
+            }
+            None => {
+                warn!("Rule '{}' no longer completes PRs, dropping PR #{}", rule_name, pr_id);
+                pending.remove(&pr_id);
+                continue;
+            }
+        };
+
+        match &outcome {
+            CompletionOutcome::Completed => info!("🎉 Completed PR #{} from {}", pr_id, pr.created_by.display_name),
+            CompletionOutcome::Waiting(reason) => info!("⏳ PR #{} not ready to complete: {}", pr_id, reason),
+            CompletionOutcome::Conflicts => warn!("⚠ PR #{} has merge conflicts, waiting for the author", pr_id),
+            CompletionOutcome::StaleCommit => warn!("⚠ PR #{} changed while completing, will retry", pr_id),
+            CompletionOutcome::Closed(reason) => info!("PR #{} will not be completed: {}", pr_id, reason),
+        }
+
+        // A chat-ops skip may have dropped the PR while we were checking it
+        let Some(entry) = pending.get_mut(&pr_id) else {
+            continue;
+        };
+
+        if entry.last_outcome.as_ref() != Some(&outcome) {
+            state.decisions.record(Decision::new(&pr, Some(&entry.rule), "complete", outcome.label(), outcome.detail()));
+        }
+
+        if matches!(outcome, CompletionOutcome::Completed | CompletionOutcome::Closed(_)) {
+            pending.remove(&pr_id);
+        } else {
+            entry.last_outcome = Some(outcome);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cassette::{MatchOn, Player};
+
+    #[tokio::test]
+    async fn replayed_cassette_approves_pr_from_watched_user() {
+        let player = Player::load("tests/cassettes/approve_new_pr.jsonl", &[MatchOn::Method, MatchOn::Path, MatchOn::Query]).unwrap();
+        let client = AzureDevOpsClient::with_base_url("https://dev.azure.com/contoso/web", "cassette-test-pat-0123").with_player(player);
+        let queue_path = std::env::temp_dir().join(format!("fpr-cassette-test-queue-{}.json", std::process::id()));
+        let config: AppConfig = toml::from_str(&format!(r#"
+            organization = "contoso"
+            project = "web"
+            reviewer_id = "bot-id"
+            watched_users = ["Jane Doe"]
+            approval_queue = {:?}
+        "#, queue_path)).unwrap();
+        let reviewer = Reviewer::new(client, config).unwrap();
+        let approved = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen = approved.clone();
+        reviewer.on_decision(move |decision| seen.lock().unwrap().push((decision.pr_id, decision.outcome.clone())));
+        // The PR was created before this test started, like any PR in a recording from the field
+        reviewer.recheck(4242).await;
+
+        let new_count = reviewer.run_once().await.unwrap();
+
+        assert_eq!(new_count, 1);
+        assert_eq!(*approved.lock().unwrap(), [(4242, "approved".to_string())]);
+        assert!(reviewer.next_due_approval().await.is_none());
+        let _ = std::fs::remove_file(queue_path);
+    }
+}
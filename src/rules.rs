@@ -0,0 +1,407 @@
+use anyhow::Result;
+use tracing::{info, warn};
+use rand::Rng;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::ado_client::{ApiError, AzureDevOpsClient};
+use crate::config::parse_duration;
+use crate::hook::DecisionHook;
+use crate::models::{CompletionOptions, IdentityRefWithId, MergeStrategy, PullRequest, PullRequestUpdate};
+
+/// A rule describing what the bot does with PRs from a set of authors
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Rule {
+    pub name: String,
+    /// Authors this rule applies to; an empty list matches every watched user
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub authors: Vec<String>,
+    /// Set auto-complete on the PR after approving it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_complete: Option<CompletionSettings>,
+    /// Complete the PR directly once policies and votes allow it (opt-in)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub complete: Option<CompleteAction>,
+    /// Explicitly add the bot (and others) as reviewers before voting
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub add_reviewers: Option<ReviewerSettings>,
+    /// Conditions a PR has to meet before the bot approves it
+    #[serde(default)]
+    pub conditions: RuleConditions,
+    /// Wait before approving; the PR is checked again when the delay is up
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub approve_after: Option<ApprovalDelay>,
+    /// External command deciding whether to approve, run while the conditions are checked
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hook: Option<DecisionHook>,
+    /// Rhai scripts deciding whether to approve, run alongside the hook; the first in order that doesn't approve decides
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub scripts: Vec<String>,
+}
+
+/// A fixed delay such as "10m", or a random one between `min` and `max`
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum ApprovalDelay {
+    Fixed(String),
+    Range { min: String, max: String },
+}
+
+impl ApprovalDelay {
+    /// Pick the delay to use for one approval
+    pub fn sample(&self) -> Result<Duration> {
+        match self {
+            ApprovalDelay::Fixed(delay) => parse_duration(delay),
+            ApprovalDelay::Range { min, max } => {
+                let (min, max) = (parse_duration(min)?, parse_duration(max)?);
+                if max < min {
+                    return Err(anyhow::anyhow!("max delay must not be shorter than min delay"));
+                }
+                Ok(rand::rng().random_range(min..=max))
+            }
+        }
+    }
+}
+
+/// Checks run against a PR before it is approved
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct RuleConditions {
+    /// Require at least one linked work item
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub work_items: Option<WorkItemCondition>,
+    /// Maximum number of active threads opened by people (not ADO or the bot)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_active_threads: Option<usize>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct WorkItemCondition {
+    /// Only count work items of these types (e.g. "User Story", "Bug"); empty means any
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub types: Vec<String>,
+    /// Only count work items in these states (e.g. "Active"); empty means any
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub states: Vec<String>,
+    /// Comment left on the PR when no matching work item is linked
+    #[serde(default = "default_work_item_comment")]
+    pub comment: String,
+}
+
+fn default_work_item_comment() -> String {
+    "Please link a work item to this pull request so it can be approved.".to_string()
+}
+
+/// How the bot adds itself and other identities as reviewers
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ReviewerSettings {
+    /// Add the bot as a required reviewer instead of an optional one
+    #[serde(default)]
+    pub required: bool,
+    /// Other identity IDs (users or teams) added as optional reviewers alongside the bot
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub additional: Vec<String>,
+}
+
+/// Result of making sure the bot is a reviewer on a PR
+#[derive(Debug, PartialEq, Eq)]
+pub enum ReviewerSetup {
+    /// The bot was already on the PR
+    AlreadyReviewer,
+    /// The bot was added by this call
+    Added,
+    /// Repository policy doesn't allow the bot to be added
+    Forbidden(String),
+}
+
+/// Completion options used when the bot sets auto-complete on a PR or completes it
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct CompletionSettings {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub merge_strategy: Option<MergeStrategy>,
+    #[serde(default)]
+    pub delete_source_branch: bool,
+    #[serde(default)]
+    pub transition_work_items: bool,
+    /// Supports the placeholders {id}, {title}, {author}, {source} and {target}
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub merge_commit_message: Option<String>,
+}
+
+/// Settings for completing a PR directly, for orgs where auto-complete isn't allowed
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CompleteAction {
+    /// Approving votes (including the bot's) required before completing
+    #[serde(default = "default_min_approvals")]
+    pub min_approvals: usize,
+    #[serde(flatten)]
+    pub options: CompletionSettings,
+}
+
+fn default_min_approvals() -> usize {
+    1
+}
+
+impl CompletionSettings {
+    pub fn to_completion_options(&self, pr: &PullRequest) -> CompletionOptions {
+        CompletionOptions {
+            merge_strategy: self.merge_strategy,
+            delete_source_branch: self.delete_source_branch,
+            transition_work_items: self.transition_work_items,
+            merge_commit_message: self.merge_commit_message.as_deref()
+                .map(|template| render_template(template, pr)),
+        }
+    }
+}
+
+impl Rule {
+    pub fn matches(&self, pr: &PullRequest) -> bool {
+        self.authors.is_empty() || self.authors.contains(&pr.created_by.display_name)
+    }
+
+    /// Whether the rule adds the bot as a required reviewer, which its votes then have to keep
+    pub fn requires_reviewer(&self) -> bool {
+        self.add_reviewers.as_ref().is_some_and(|settings| settings.required)
+    }
+}
+
+/// Find the first rule that applies to the given PR
+pub fn find_rule<'a>(rules: &'a [Rule], pr: &PullRequest) -> Option<&'a Rule> {
+    rules.iter().find(|rule| rule.matches(pr))
+}
+
+/// Fill in the PR placeholders of a message template in one pass, so placeholders inside the values
+/// (e.g. a PR titled "{author}") are left as they are
+pub fn render_template(template: &str, pr: &PullRequest) -> String {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        rendered.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let Some(end) = rest.find('}') else {
+            break;
+        };
+
+        let value = match &rest[1..end] {
+            "id" => pr.pull_request_id.to_string(),
+            "title" => pr.title.clone(),
+            "author" => pr.created_by.display_name.clone(),
+            "source" => pr.source_branch.clone().unwrap_or_default(),
+            "target" => pr.target_branch.clone().unwrap_or_default(),
+            // Not a placeholder; keep the brace and look for one after it
+            _ => {
+                rendered.push('{');
+                rest = &rest[1..];
+                continue;
+            }
+        };
+        rendered.push_str(&value);
+        rest = &rest[end + 1..];
+    }
+
+    rendered.push_str(rest);
+    rendered
+}
+
+/// Add the bot as a reviewer if it isn't on the PR yet, plus any additional reviewers
+pub async fn add_reviewers(
+    client: &AzureDevOpsClient,
+    settings: &ReviewerSettings,
+    pr: &PullRequest,
+    reviewer_id: &str,
+) -> Result<ReviewerSetup> {
+    let reviewers = client.get_reviewers(pr).await?;
+
+    let setup = if reviewers.iter().any(|r| r.id == reviewer_id) {
+        ReviewerSetup::AlreadyReviewer
+    } else {
+        match client.add_reviewer(pr, reviewer_id, settings.required).await {
+            Ok(_) => ReviewerSetup::Added,
+            Err(e) => match e.downcast_ref::<ApiError>() {
+                Some(api_error) if api_error.status == StatusCode::FORBIDDEN => {
+                    return Ok(ReviewerSetup::Forbidden(api_error.body.clone()));
+                }
+                _ => return Err(e),
+            },
+        }
+    };
+
+    // Additional reviewers are best effort; a failure here shouldn't block the vote
+    for id in settings.additional.iter().filter(|id| !reviewers.iter().any(|r| &r.id == *id)) {
+        if let Err(e) = client.add_reviewer(pr, id, false).await {
+            warn!("⚠ Failed to add reviewer {} to PR #{}: {}", id, pr.pull_request_id, e);
+        }
+    }
+
+    Ok(setup)
+}
+
+/// Run the actions of a rule against a PR the bot has just approved
+pub async fn apply_post_approval_actions(
+    client: &AzureDevOpsClient,
+    rule: &Rule,
+    pr: &PullRequest,
+    reviewer_id: &str,
+) -> Result<()> {
+    if let Some(settings) = &rule.auto_complete {
+        set_auto_complete(client, pr, reviewer_id, settings).await?;
+        info!("🔁 Auto-complete set on PR #{} (rule '{}')", pr.pull_request_id, rule.name);
+    }
+
+    Ok(())
+}
+
+async fn set_auto_complete(
+    client: &AzureDevOpsClient,
+    pr: &PullRequest,
+    reviewer_id: &str,
+    settings: &CompletionSettings,
+) -> Result<()> {
+    let update = PullRequestUpdate {
+        auto_complete_set_by: Some(IdentityRefWithId { id: reviewer_id.to_string() }),
+        completion_options: Some(settings.to_completion_options(pr)),
+        ..Default::default()
+    };
+
+    client.update_pull_request(pr, &update).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn pr() -> PullRequest {
+        serde_json::from_value(json!({
+            "pullRequestId": 7,
+            "title": "Bump serde",
+            "createdBy": { "id": "jane", "displayName": "Jane Doe" },
+            "creationDate": "2026-01-01T00:00:00Z",
+            "sourceRefName": "refs/heads/bump-serde",
+            "targetRefName": "refs/heads/main",
+            "repository": { "id": "repo-id", "name": "repo" },
+        })).unwrap()
+    }
+
+    #[test]
+    fn templates_are_rendered_in_one_pass() {
+        let mut pr = pr();
+        assert_eq!(render_template("Merged PR {id}: {title} by {author} ({source} -> {target})", &pr),
+            "Merged PR 7: Bump serde by Jane Doe (refs/heads/bump-serde -> refs/heads/main)");
+
+        // Placeholders in the PR's own fields, unknown placeholders and stray braces are kept as written
+        pr.title = "Support {author} and {id} in {templates".to_string();
+        pr.target_branch = None;
+        assert_eq!(render_template("{title} by {author} {unknown} {{id}} {target}{", &pr),
+            "Support {author} and {id} in {templates by Jane Doe {unknown} {7} {");
+    }
+
+    /// A server where `existing` are the PR's reviewers and adding a reviewer answers with `add_status`
+    async fn reviewer_server(existing: serde_json::Value, add_status: u16) -> MockServer {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/_apis/git/repositories/repo-id/pullRequests/7/reviewers"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "value": existing })))
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .respond_with(ResponseTemplate::new(add_status).set_body_json(json!({ "id": "added", "displayName": "Added" })))
+            .mount(&server)
+            .await;
+        server
+    }
+
+    /// Reviewers added by PUT requests, with whether they were added as required
+    async fn added_reviewers(server: &MockServer) -> Vec<(String, bool)> {
+        server.received_requests().await.unwrap().iter()
+            .filter(|request| request.method.as_str() == "PUT")
+            .map(|request| {
+                let body: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+                let id = request.url.path().rsplit('/').next().unwrap().to_string();
+                (id, body["isRequired"].as_bool().unwrap())
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn bot_and_additional_reviewers_are_added_unless_already_there() {
+        let settings = ReviewerSettings { required: true, additional: vec!["lead".to_string(), "team".to_string()] };
+
+        let server = reviewer_server(json!([{ "id": "team", "displayName": "Team" }]), 200).await;
+        let client = AzureDevOpsClient::with_base_url(&server.uri(), "pat");
+        assert_eq!(add_reviewers(&client, &settings, &pr(), "bot-id").await.unwrap(), ReviewerSetup::Added);
+        assert_eq!(added_reviewers(&server).await, [("bot-id".to_string(), true), ("lead".to_string(), false)]);
+
+        let server = reviewer_server(json!([{ "id": "bot-id", "displayName": "Bot" }]), 200).await;
+        let client = AzureDevOpsClient::with_base_url(&server.uri(), "pat");
+        assert_eq!(add_reviewers(&client, &settings, &pr(), "bot-id").await.unwrap(), ReviewerSetup::AlreadyReviewer);
+        assert_eq!(added_reviewers(&server).await, [("lead".to_string(), false), ("team".to_string(), false)]);
+
+        // A policy refusing the bot is reported rather than treated as an error, and nobody else is added
+        let server = reviewer_server(json!([]), 403).await;
+        let client = AzureDevOpsClient::with_base_url(&server.uri(), "pat");
+        let setup = add_reviewers(&client, &settings, &pr(), "bot-id").await.unwrap();
+        assert!(matches!(setup, ReviewerSetup::Forbidden(_)), "{:?}", setup);
+        assert_eq!(added_reviewers(&server).await, [("bot-id".to_string(), true)]);
+    }
+
+    #[tokio::test]
+    async fn auto_complete_is_set_with_the_rule_completion_options() {
+        let server = MockServer::start().await;
+        Mock::given(method("PATCH"))
+            .and(path("/_apis/git/repositories/repo-id/pullRequests/7"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::to_value(pr()).unwrap()))
+            .mount(&server)
+            .await;
+        let rule: Rule = toml::from_str(r#"
+            name = "deps"
+            [auto_complete]
+            merge_strategy = "squash"
+            delete_source_branch = true
+            merge_commit_message = "Merged PR {id}: {title}"
+        "#).unwrap();
+
+        let client = AzureDevOpsClient::with_base_url(&server.uri(), "pat");
+        apply_post_approval_actions(&client, &rule, &pr(), "bot-id").await.unwrap();
+
+        let requests = server.received_requests().await.unwrap();
+        assert_eq!(requests.len(), 1);
+        let update: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+        assert_eq!(update, json!({
+            "autoCompleteSetBy": { "id": "bot-id" },
+            "completionOptions": {
+                "mergeStrategy": "squash",
+                "deleteSourceBranch": true,
+                "transitionWorkItems": false,
+                "mergeCommitMessage": "Merged PR 7: Bump serde",
+            },
+        }));
+
+        // Rules without auto-complete leave the PR alone
+        let plain: Rule = toml::from_str("name = \"plain\"").unwrap();
+        apply_post_approval_actions(&client, &plain, &pr(), "bot-id").await.unwrap();
+        assert_eq!(server.received_requests().await.unwrap().len(), 1);
+    }
+
+    #[test]
+    fn approval_delays_are_fixed_or_sampled_from_their_range() {
+        assert_eq!(ApprovalDelay::Fixed("10m".to_string()).sample().unwrap(), Duration::from_secs(600));
+
+        let range = ApprovalDelay::Range { min: "1m".to_string(), max: "2m".to_string() };
+        for _ in 0..20 {
+            let delay = range.sample().unwrap();
+            assert!((Duration::from_secs(60)..=Duration::from_secs(120)).contains(&delay), "{:?}", delay);
+        }
+
+        let single = ApprovalDelay::Range { min: "30s".to_string(), max: "30s".to_string() };
+        assert_eq!(single.sample().unwrap(), Duration::from_secs(30));
+
+        let backwards = ApprovalDelay::Range { min: "2m".to_string(), max: "1m".to_string() };
+        assert!(backwards.sample().is_err());
+        assert!(ApprovalDelay::Fixed("soon".to_string()).sample().is_err());
+    }
+}
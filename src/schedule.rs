@@ -0,0 +1,369 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Timelike, Utc, Weekday};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+// How far ahead we look for the next open window
+const NEXT_OPEN_SEARCH_DAYS: i64 = 14;
+
+// Longest stretch of local time a DST change skips
+const MAX_DST_GAP_MINUTES: i64 = 180;
+
+/// When the bot is allowed to approve PRs
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ScheduleConfig {
+    /// IANA timezone the windows are written in, e.g. "Europe/London"
+    #[serde(default = "default_timezone")]
+    pub timezone: String,
+    /// Weekday/time-range windows such as "Mon-Fri 09:00-17:30"
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub windows: Vec<String>,
+    /// Cron-like expressions (minute hour day-of-month month day-of-week) for minutes that are open
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cron: Vec<String>,
+    /// Dates on which the bot never approves
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub holidays: Vec<NaiveDate>,
+}
+
+fn default_timezone() -> String {
+    "UTC".to_string()
+}
+
+/// A parsed schedule that can be checked against the current time
+#[derive(Debug)]
+pub struct Schedule {
+    timezone: Tz,
+    windows: Vec<TimeWindow>,
+    cron: Vec<CronExpression>,
+    holidays: HashSet<NaiveDate>,
+}
+
+/// A time range on a set of weekdays, e.g. "Mon-Fri 09:00-17:30"; a range such as "Fri 22:00-02:00"
+/// crosses midnight and belongs to the day it starts on
+#[derive(Debug)]
+struct TimeWindow {
+    days: Vec<Weekday>,
+    start: NaiveTime,
+    end: NaiveTime,
+}
+
+/// A five-field cron expression used as a "which minutes are open" matcher
+#[derive(Debug)]
+struct CronExpression {
+    minutes: Vec<u32>,
+    hours: Vec<u32>,
+    days_of_month: Option<Vec<u32>>,
+    months: Vec<u32>,
+    days_of_week: Option<Vec<u32>>,
+}
+
+impl Schedule {
+    pub fn from_config(config: &ScheduleConfig) -> Result<Self> {
+        let timezone: Tz = config.timezone.parse()
+            .map_err(|_| anyhow::anyhow!("Unknown timezone '{}'", config.timezone))?;
+
+        let windows = config.windows.iter()
+            .map(|window| TimeWindow::parse(window)
+                .with_context(|| format!("Invalid schedule window '{}'", window)))
+            .collect::<Result<Vec<_>>>()?;
+
+        let cron = config.cron.iter()
+            .map(|expression| CronExpression::parse(expression)
+                .with_context(|| format!("Invalid cron expression '{}'", expression)))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            timezone,
+            windows,
+            cron,
+            holidays: config.holidays.iter().copied().collect(),
+        })
+    }
+
+    /// Whether approvals are allowed at the given time
+    pub fn is_open(&self, now: DateTime<Utc>) -> bool {
+        let local = now.with_timezone(&self.timezone);
+
+        if self.holidays.contains(&local.date_naive()) {
+            return false;
+        }
+
+        // A schedule with only holidays is open on every other day
+        if self.windows.is_empty() && self.cron.is_empty() {
+            return true;
+        }
+
+        let time = local.time();
+        self.windows.iter().any(|window| window.contains(local.weekday(), time))
+            || self.cron.iter().any(|expression| expression.matches(&local))
+    }
+
+    /// The next minute at which the schedule opens, if it does within the next two weeks
+    pub fn next_open(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let start = now.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        if self.is_open(start) {
+            return Some(start);
+        }
+
+        // The schedule can only open at midnight (after a holiday), when a window starts or on a cron minute,
+        // so only those moments are checked, a day at a time
+        let end = start + Duration::days(NEXT_OPEN_SEARCH_DAYS);
+        let first_day = start.with_timezone(&self.timezone).date_naive();
+        for date in first_day.iter_days().take(NEXT_OPEN_SEARCH_DAYS as usize + 1) {
+            let mut times = vec![NaiveTime::MIN];
+            times.extend(self.windows.iter().map(|window| window.start));
+            for expression in self.cron.iter().filter(|expression| expression.matches_date(date)) {
+                times.extend(expression.hours.iter()
+                    .flat_map(|hour| expression.minutes.iter().filter_map(|minute| NaiveTime::from_hms_opt(*hour, *minute, 0))));
+            }
+
+            let mut candidates: Vec<DateTime<Utc>> = times.into_iter()
+                .filter_map(|time| self.to_utc(date.and_time(time)))
+                .filter(|candidate| *candidate > start && *candidate < end)
+                .collect();
+            candidates.sort();
+            if let Some(open) = candidates.into_iter().find(|candidate| self.is_open(*candidate)) {
+                return Some(open);
+            }
+        }
+        None
+    }
+
+    /// Resolve a local time; one skipped by a DST change resolves to the first minute after the gap
+    fn to_utc(&self, local: NaiveDateTime) -> Option<DateTime<Utc>> {
+        (0..=MAX_DST_GAP_MINUTES)
+            .find_map(|minutes| self.timezone.from_local_datetime(&(local + Duration::minutes(minutes))).earliest())
+            .map(|resolved| resolved.with_timezone(&Utc))
+    }
+
+    pub fn timezone(&self) -> Tz {
+        self.timezone
+    }
+}
+
+impl TimeWindow {
+    fn parse(spec: &str) -> Result<Self> {
+        let parts: Vec<&str> = spec.split_whitespace().collect();
+        let (days, range) = match parts.as_slice() {
+            [range] => (ALL_WEEKDAYS.to_vec(), *range),
+            [days, range] => (parse_weekdays(days)?, *range),
+            _ => return Err(anyhow::anyhow!("Expected '[days] HH:MM-HH:MM'")),
+        };
+
+        let (start, end) = range.split_once('-')
+            .context("Expected a time range like 09:00-17:30")?;
+        let start = NaiveTime::parse_from_str(start, "%H:%M").context("Invalid start time")?;
+        let end = NaiveTime::parse_from_str(end, "%H:%M").context("Invalid end time")?;
+        if end == start {
+            return Err(anyhow::anyhow!("End time must differ from start time"));
+        }
+
+        Ok(Self { days, start, end })
+    }
+
+    fn contains(&self, day: Weekday, time: NaiveTime) -> bool {
+        if self.start < self.end {
+            return self.days.contains(&day) && time >= self.start && time < self.end;
+        }
+
+        // Crosses midnight: the evening of one of our days or the early hours after it
+        (self.days.contains(&day) && time >= self.start)
+            || (self.days.contains(&day.pred()) && time < self.end)
+    }
+}
+
+const ALL_WEEKDAYS: [Weekday; 7] = [
+    Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri, Weekday::Sat, Weekday::Sun,
+];
+
+/// Parse "Mon-Fri", "Sat,Sun" or "Mon,Wed-Fri" into weekdays
+fn parse_weekdays(spec: &str) -> Result<Vec<Weekday>> {
+    let mut days = Vec::new();
+    for part in spec.split(',') {
+        match part.split_once('-') {
+            Some((from, to)) => {
+                let mut day: Weekday = from.parse().map_err(|_| anyhow::anyhow!("Unknown weekday '{}'", from))?;
+                let to: Weekday = to.parse().map_err(|_| anyhow::anyhow!("Unknown weekday '{}'", to))?;
+                // Ranges may wrap around the end of the week, e.g. Sat-Mon
+                while day != to {
+                    days.push(day);
+                    day = day.succ();
+                }
+                days.push(to);
+            }
+            None => days.push(part.parse().map_err(|_| anyhow::anyhow!("Unknown weekday '{}'", part))?),
+        }
+    }
+    Ok(days)
+}
+
+impl CronExpression {
+    fn parse(expression: &str) -> Result<Self> {
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let [minutes, hours, days_of_month, months, days_of_week] = fields.as_slice() else {
+            return Err(anyhow::anyhow!("Expected 5 fields: minute hour day-of-month month day-of-week"));
+        };
+
+        Ok(Self {
+            minutes: parse_cron_field(minutes, 0, 59)?,
+            hours: parse_cron_field(hours, 0, 23)?,
+            days_of_month: (*days_of_month != "*").then(|| parse_cron_field(days_of_month, 1, 31)).transpose()?,
+            months: parse_cron_field(months, 1, 12)?,
+            // Both 0 and 7 mean Sunday
+            days_of_week: (*days_of_week != "*")
+                .then(|| parse_cron_field(days_of_week, 0, 7)
+                    .map(|days| days.into_iter().map(|day| day % 7).collect()))
+                .transpose()?,
+        })
+    }
+
+    fn matches(&self, local: &DateTime<Tz>) -> bool {
+        self.matches_date(local.date_naive())
+            && self.minutes.contains(&local.minute())
+            && self.hours.contains(&local.hour())
+    }
+
+    /// Whether the month and day fields match the date
+    fn matches_date(&self, date: NaiveDate) -> bool {
+        let day_of_month = self.days_of_month.as_ref().map(|days| days.contains(&date.day()));
+        let day_of_week = self.days_of_week.as_ref()
+            .map(|days| days.contains(&date.weekday().num_days_from_sunday()));
+
+        // Like cron, when both day fields are restricted either one may match
+        let day_matches = match (day_of_month, day_of_week) {
+            (Some(dom), Some(dow)) => dom || dow,
+            (Some(matches), None) | (None, Some(matches)) => matches,
+            (None, None) => true,
+        };
+
+        day_matches && self.months.contains(&date.month())
+    }
+}
+
+/// Parse one cron field: "*", "5", "1-5", "*/15", "9-17/2", "5/15" (from 5 to the maximum) or a comma-separated list of those
+fn parse_cron_field(field: &str, min: u32, max: u32) -> Result<Vec<u32>> {
+    let mut values = Vec::new();
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, step.parse::<u32>().with_context(|| format!("Invalid step '{}'", step))?),
+            None => (part, 1),
+        };
+        if step == 0 {
+            return Err(anyhow::anyhow!("Step cannot be zero"));
+        }
+
+        let (from, to) = if range == "*" {
+            (min, max)
+        } else if let Some((from, to)) = range.split_once('-') {
+            (from.parse().with_context(|| format!("Invalid value '{}'", from))?,
+             to.parse().with_context(|| format!("Invalid value '{}'", to))?)
+        } else {
+            let value = range.parse().with_context(|| format!("Invalid value '{}'", range))?;
+            // As in cron, a stepped single value runs to the end of the range
+            if part.contains('/') { (value, max) } else { (value, value) }
+        };
+
+        if from < min || to > max || from > to {
+            return Err(anyhow::anyhow!("'{}' is outside {}-{}", part, min, max));
+        }
+        values.extend((from..=to).step_by(step as usize));
+    }
+    Ok(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schedule(timezone: &str, windows: &[&str], cron: &[&str], holidays: &[&str]) -> Schedule {
+        Schedule::from_config(&ScheduleConfig {
+            timezone: timezone.to_string(),
+            windows: windows.iter().map(|window| window.to_string()).collect(),
+            cron: cron.iter().map(|expression| expression.to_string()).collect(),
+            holidays: holidays.iter().map(|date| date.parse().unwrap()).collect(),
+        }).unwrap()
+    }
+
+    fn utc(time: &str) -> DateTime<Utc> {
+        time.parse().unwrap()
+    }
+
+    #[test]
+    fn weekdays_parse_as_lists_and_wrapping_ranges() {
+        use Weekday::*;
+        assert_eq!(parse_weekdays("Mon-Fri").unwrap(), vec![Mon, Tue, Wed, Thu, Fri]);
+        assert_eq!(parse_weekdays("Sat,Sun").unwrap(), vec![Sat, Sun]);
+        assert_eq!(parse_weekdays("Mon,Wed-Fri").unwrap(), vec![Mon, Wed, Thu, Fri]);
+        assert_eq!(parse_weekdays("Sat-Mon").unwrap(), vec![Sat, Sun, Mon]);
+        assert!(parse_weekdays("Mon-Funday").is_err());
+    }
+
+    #[test]
+    fn cron_fields_expand_ranges_and_steps() {
+        assert_eq!(parse_cron_field("*", 0, 5).unwrap(), vec![0, 1, 2, 3, 4, 5]);
+        assert_eq!(parse_cron_field("5", 0, 59).unwrap(), vec![5]);
+        assert_eq!(parse_cron_field("1-3,7", 0, 59).unwrap(), vec![1, 2, 3, 7]);
+        assert_eq!(parse_cron_field("*/15", 0, 59).unwrap(), vec![0, 15, 30, 45]);
+        assert_eq!(parse_cron_field("9-17/4", 0, 23).unwrap(), vec![9, 13, 17]);
+        assert_eq!(parse_cron_field("5/15", 0, 59).unwrap(), vec![5, 20, 35, 50]);
+
+        assert!(parse_cron_field("*/0", 0, 59).is_err());
+        assert!(parse_cron_field("60", 0, 59).is_err());
+        assert!(parse_cron_field("5-1", 0, 59).is_err());
+        assert!(parse_cron_field("x", 0, 59).is_err());
+    }
+
+    #[test]
+    fn windows_use_the_timezone_and_skip_holidays() {
+        let schedule = schedule("Europe/London", &["Mon-Fri 09:00-17:30"], &[], &["2026-12-25"]);
+
+        // Thursday 2026-07-02; London is on BST (UTC+1)
+        assert!(!schedule.is_open(utc("2026-07-02T07:59:00Z")));
+        assert!(schedule.is_open(utc("2026-07-02T08:00:00Z")));
+        assert!(schedule.is_open(utc("2026-07-02T16:29:00Z")));
+        assert!(!schedule.is_open(utc("2026-07-02T16:30:00Z")));
+
+        // Friday 2026-12-25 is a holiday; in winter London is on UTC
+        assert!(!schedule.is_open(utc("2026-12-25T12:00:00Z")));
+        assert!(schedule.is_open(utc("2026-12-24T09:00:00Z")));
+        assert_eq!(schedule.next_open(utc("2026-12-24T18:00:00Z")), Some(utc("2026-12-28T09:00:00Z")));
+    }
+
+    #[test]
+    fn windows_can_cross_midnight() {
+        let schedule = schedule("UTC", &["Fri 22:00-02:00"], &[], &[]);
+
+        // Friday 2026-07-03 evening into Saturday morning, but not Friday's early hours
+        assert!(!schedule.is_open(utc("2026-07-03T01:00:00Z")));
+        assert!(schedule.is_open(utc("2026-07-03T22:00:00Z")));
+        assert!(schedule.is_open(utc("2026-07-04T01:59:00Z")));
+        assert!(!schedule.is_open(utc("2026-07-04T02:00:00Z")));
+        assert!(!schedule.is_open(utc("2026-07-04T22:30:00Z")));
+        assert_eq!(schedule.next_open(utc("2026-07-04T02:00:00Z")), Some(utc("2026-07-10T22:00:00Z")));
+    }
+
+    #[test]
+    fn next_open_handles_dst_and_cron() {
+        // Clocks in New York jump from 02:00 to 03:00 on Sunday 2026-03-08 (UTC-5 to UTC-4)
+        let new_york = schedule("America/New_York", &["Sun 02:30-04:00", "Mon-Fri 09:00-17:00"], &[], &[]);
+        assert_eq!(new_york.next_open(utc("2026-03-07T12:00:00Z")), Some(utc("2026-03-08T07:00:00Z")));
+        assert_eq!(new_york.next_open(utc("2026-03-08T09:00:00Z")), Some(utc("2026-03-09T13:00:00Z")));
+
+        // Saturdays at 10:05, 10:20, ... in Berlin (UTC+2 in summer)
+        let berlin = schedule("Europe/Berlin", &[], &["5/15 10 * * 6"], &[]);
+        assert!(berlin.is_open(utc("2026-07-04T08:20:00Z")));
+        assert!(!berlin.is_open(utc("2026-07-04T08:21:00Z")));
+        assert_eq!(berlin.next_open(utc("2026-07-04T08:21:00Z")), Some(utc("2026-07-04T08:35:00Z")));
+        assert_eq!(berlin.next_open(utc("2026-07-04T09:00:00Z")), Some(utc("2026-07-11T08:05:00Z")));
+
+        // Already open: the next minute
+        let always = schedule("UTC", &[], &[], &[]);
+        assert_eq!(always.next_open(utc("2026-07-04T09:00:30Z")), Some(utc("2026-07-04T09:01:00Z")));
+
+        // February 30th never comes
+        let never = schedule("UTC", &[], &["0 0 30 2 *"], &[]);
+        assert_eq!(never.next_open(utc("2026-07-04T09:00:00Z")), None);
+    }
+}
@@ -0,0 +1,381 @@
+use anyhow::{Context, Result};
+use chrono::Utc;
+use chrono_tz::Tz;
+use regex::Regex;
+use rhai::module_resolvers::DummyModuleResolver;
+use rhai::{Array, Dynamic, Engine, EvalAltResult, Map, Scope, AST};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Instant, SystemTime};
+use tokio::sync::Mutex;
+use tracing::{debug, error, info};
+
+use crate::config::parse_duration;
+use crate::hook::{HookDecision, HookVote};
+use crate::models::{FileChange, PullRequest, Reviewer};
+
+// Limits on what a script may build, on top of the configured operation and time limits
+const MAX_CALL_LEVELS: usize = 32;
+const MAX_EXPR_DEPTH: usize = 64;
+const MAX_STRING_SIZE: usize = 1 << 20;
+const MAX_COLLECTION_SIZE: usize = 10_000;
+
+/// Limits applied to every approval policy script
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ScriptingConfig {
+    /// Operations a script may run before it is stopped
+    #[serde(default = "default_max_operations")]
+    pub max_operations: u64,
+    /// Wall-clock time a script may run before it is stopped, e.g. "1s"
+    #[serde(default = "default_script_timeout")]
+    pub timeout: String,
+}
+
+fn default_max_operations() -> u64 {
+    100_000
+}
+
+fn default_script_timeout() -> String {
+    "1s".to_string()
+}
+
+impl Default for ScriptingConfig {
+    fn default() -> Self {
+        Self {
+            max_operations: default_max_operations(),
+            timeout: default_script_timeout(),
+        }
+    }
+}
+
+impl ScriptingConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.max_operations == 0 {
+            return Err(anyhow::anyhow!("scripting.max_operations must be at least 1"));
+        }
+        parse_duration(&self.timeout).context("Invalid scripting.timeout")?;
+        Ok(())
+    }
+}
+
+/// Compiled scripts keyed by path, recompiled when their file changes
+#[derive(Default)]
+pub struct ScriptCache {
+    scripts: HashMap<PathBuf, CachedScript>,
+}
+
+struct CachedScript {
+    modified: SystemTime,
+    ast: Arc<AST>,
+    /// Why the file on disk couldn't replace this version, logged once per distinct error
+    error: Option<String>,
+}
+
+impl ScriptCache {
+    /// The compiled script at `path`, reloaded if the file changed since it was last used.
+    /// When the file can't be read or compiled, the last version that compiled keeps being used.
+    pub fn load(&mut self, path: &Path) -> Result<Arc<AST>> {
+        let reloaded = self.reload(path);
+        match (reloaded, self.scripts.get_mut(path)) {
+            (Ok(ast), _) => Ok(ast),
+            (Err(e), Some(cached)) => {
+                let error = format!("{:#}", e);
+                if cached.error.as_ref() != Some(&error) {
+                    error!("❌ Keeping the previous version of script {}: {}", path.display(), error);
+                    cached.error = Some(error);
+                }
+                Ok(cached.ast.clone())
+            }
+            (Err(e), None) => Err(e),
+        }
+    }
+
+    fn reload(&mut self, path: &Path) -> Result<Arc<AST>> {
+        let modified = fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .with_context(|| format!("Failed to read script {}", path.display()))?;
+        if let Some(cached) = self.scripts.get(path)
+            && cached.modified == modified
+        {
+            return Ok(cached.ast.clone());
+        }
+
+        let ast = Arc::new(compile(path)?);
+        if self.scripts.insert(path.to_path_buf(), CachedScript { modified, ast: ast.clone(), error: None }).is_some() {
+            info!("📜 Reloaded script {}", path.display());
+        }
+        Ok(ast)
+    }
+}
+
+/// Read and compile a script, e.g. to check it when the config is loaded
+pub fn compile(path: &Path) -> Result<AST> {
+    let source = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read script {}", path.display()))?;
+    sandboxed_engine(&ScriptingConfig::default(), None)
+        .compile(&source)
+        .map_err(|e| anyhow::anyhow!("Failed to compile script {}: {}", path.display(), e))
+}
+
+/// Values a script sees: `pr`, `files`, `changes`, `reviewers` and `now`
+#[derive(Clone)]
+pub struct ScriptInput {
+    pr: Map,
+    files: Array,
+    changes: Array,
+    reviewers: Array,
+    now: Map,
+}
+
+impl ScriptInput {
+    /// `now` is given in `timezone`, or UTC without one
+    pub fn new(pr: &PullRequest, reviewers: &[Reviewer], changes: &[FileChange], timezone: Option<Tz>) -> Self {
+        let pr_map = map([
+            ("id", Dynamic::from(pr.pull_request_id as i64)),
+            ("title", pr.title.clone().into()),
+            ("author", pr.created_by.display_name.clone().into()),
+            ("author_id", pr.created_by.id.clone().into()),
+            ("repository", pr.repository.name.clone().into()),
+            ("source_branch", pr.source_branch.clone().unwrap_or_default().into()),
+            ("target_branch", pr.target_branch.clone().unwrap_or_default().into()),
+            ("created", pr.creation_date.clone().into()),
+            ("status", pr.status.clone().unwrap_or_default().into()),
+            ("merge_status", pr.merge_status.clone().unwrap_or_default().into()),
+        ]);
+        let reviewers = reviewers.iter()
+            .map(|reviewer| map([
+                ("id", reviewer.id.clone().into()),
+                ("name", reviewer.display_name.clone().into()),
+                ("vote", Dynamic::from(reviewer.vote as i64)),
+                ("required", reviewer.is_required.into()),
+            ]).into())
+            .collect();
+        let now = Utc::now().with_timezone(&timezone.unwrap_or(Tz::UTC));
+
+        Self {
+            pr: pr_map,
+            files: changes.iter().map(|change| change.path.clone().into()).collect(),
+            changes: changes.iter()
+                .map(|change| map([
+                    ("path", change.path.clone().into()),
+                    ("change_type", change.change_type.clone().into()),
+                ]).into())
+                .collect(),
+            reviewers,
+            now: map([
+                ("weekday", now.format("%A").to_string().into()),
+                ("date", now.format("%Y-%m-%d").to_string().into()),
+                ("time", now.format("%H:%M").to_string().into()),
+                ("hour", Dynamic::from(now.format("%H").to_string().parse::<i64>().unwrap_or_default())),
+            ]),
+        }
+    }
+}
+
+fn map<const N: usize>(entries: [(&str, Dynamic); N]) -> Map {
+    entries.into_iter().map(|(key, value)| (key.into(), value)).collect()
+}
+
+/// Load the script at `path` through `cache` and run it off the async runtime
+pub async fn decide(cache: &Mutex<ScriptCache>, path: &str, input: &ScriptInput, config: &ScriptingConfig) -> HookDecision {
+    let ast = match cache.lock().await.load(Path::new(path)) {
+        Ok(ast) => ast,
+        Err(e) => return HookDecision::failed(format!("{:#}", e)),
+    };
+
+    let (input, config) = (input.clone(), config.clone());
+    match tokio::task::spawn_blocking(move || evaluate(&ast, &input, &config)).await {
+        Ok(decision) => decision,
+        Err(e) => HookDecision::failed(format!("script {} crashed: {}", path, e)),
+    }
+}
+
+/// Run a compiled script and turn what it returns into a decision; errors and exceeded limits leave it undecided
+pub fn evaluate(ast: &AST, input: &ScriptInput, config: &ScriptingConfig) -> HookDecision {
+    let timeout = match parse_duration(&config.timeout) {
+        Ok(timeout) => timeout,
+        Err(e) => return HookDecision::failed(format!("invalid script timeout: {}", e)),
+    };
+    let engine = sandboxed_engine(config, Some(Instant::now() + timeout));
+
+    let mut scope = Scope::new();
+    scope.push_constant("pr", input.pr.clone());
+    scope.push_constant("files", input.files.clone());
+    scope.push_constant("changes", input.changes.clone());
+    scope.push_constant("reviewers", input.reviewers.clone());
+    scope.push_constant("now", input.now.clone());
+
+    match engine.eval_ast_with_scope::<Dynamic>(&mut scope, ast) {
+        Ok(result) => to_decision(result),
+        Err(e) => match *e {
+            EvalAltResult::ErrorTooManyOperations(_) => {
+                HookDecision::failed(format!("script ran more than {} operations", config.max_operations))
+            }
+            EvalAltResult::ErrorTerminated(..) => HookDecision::failed(format!("script timed out after {}", config.timeout)),
+            e => HookDecision::failed(format!("script failed: {}", e)),
+        },
+    }
+}
+
+/// A script returns a vote such as "approve", or a map with `vote` and optional `comment` and `reason`
+fn to_decision(result: Dynamic) -> HookDecision {
+    let (vote, mut fields) = if result.is_string() {
+        (result.into_string().unwrap_or_default(), Map::new())
+    } else if let Some(mut fields) = result.clone().try_cast::<Map>() {
+        let vote = fields.remove("vote").and_then(|vote| vote.into_string().ok()).unwrap_or_default();
+        (vote, fields)
+    } else {
+        return HookDecision::failed(format!("script returned a {} instead of a decision", result.type_name()));
+    };
+
+    let Some(vote) = HookVote::parse(&vote) else {
+        return HookDecision::failed(format!("script returned an unknown vote '{}'", vote));
+    };
+    let mut text = |key: &str| fields.remove(key).and_then(|value| value.into_string().ok());
+    HookDecision { vote, comment: text("comment"), reason: text("reason"), failed: false }
+}
+
+/// An engine without access to files or imports, stopped once it runs past its limits or `deadline`
+fn sandboxed_engine(config: &ScriptingConfig, deadline: Option<Instant>) -> Engine {
+    let mut engine = Engine::new();
+    engine.set_module_resolver(DummyModuleResolver::new());
+    engine.set_max_operations(config.max_operations);
+    engine.set_max_call_levels(MAX_CALL_LEVELS);
+    engine.set_max_expr_depths(MAX_EXPR_DEPTH, MAX_EXPR_DEPTH);
+    engine.set_max_string_size(MAX_STRING_SIZE);
+    engine.set_max_array_size(MAX_COLLECTION_SIZE);
+    engine.set_max_map_size(MAX_COLLECTION_SIZE);
+    if let Some(deadline) = deadline {
+        engine.on_progress(move |_| (Instant::now() > deadline).then(|| "timed out".into()));
+    }
+    engine.on_print(|text| info!("📜 {}", text));
+    engine.on_debug(|text, source, position| debug!("📜 {} ({} {})", text, source.unwrap_or("script"), position));
+
+    engine.register_fn("glob_match", glob_match);
+    engine.register_fn("regex_match", |text: &str, pattern: &str| -> Result<bool, Box<EvalAltResult>> {
+        let regex = Regex::new(pattern).map_err(|e| format!("invalid regex '{}': {}", pattern, e))?;
+        Ok(regex.is_match(text))
+    });
+    engine
+}
+
+/// Match a path against a glob: `*` and `?` stay within a directory and `**` crosses them.
+/// A pattern without a `/` matches the file name anywhere, e.g. "*.md".
+pub fn glob_match(path: &str, pattern: &str) -> bool {
+    let path = path.trim_start_matches('/');
+    let (subject, pattern) = if pattern.contains('/') {
+        (path, pattern.trim_start_matches('/'))
+    } else {
+        (path.rsplit('/').next().unwrap_or(path), pattern)
+    };
+
+    let mut regex = String::from("^");
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    regex.push_str("(?:.*/)?");
+                } else {
+                    regex.push_str(".*");
+                }
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex.push('$');
+    Regex::new(&regex).is_ok_and(|regex| regex.is_match(subject))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(files: &[&str]) -> ScriptInput {
+        let pr: PullRequest = serde_json::from_value(serde_json::json!({
+            "pullRequestId": 7,
+            "title": "Bump serde",
+            "createdBy": { "id": "jane-id", "displayName": "Jane Doe" },
+            "creationDate": "2026-10-16T09:00:00Z",
+            "repository": { "id": "repo-1", "name": "backend" },
+        })).unwrap();
+        let changes: Vec<FileChange> = files.iter()
+            .map(|path| FileChange { path: path.to_string(), change_type: "edit".to_string() })
+            .collect();
+        ScriptInput::new(&pr, &[], &changes, None)
+    }
+
+    fn run(script: &str, input: &ScriptInput, config: &ScriptingConfig) -> HookDecision {
+        let ast = sandboxed_engine(config, None).compile(script).unwrap();
+        evaluate(&ast, input, config)
+    }
+
+    #[test]
+    fn scripts_decide_from_the_pr_and_its_files() {
+        let script = r#"
+            if pr.author != "Jane Doe" { return "skip"; }
+            if files.some(|f| glob_match(f, "db/migrations/**")) {
+                return #{ vote: "wait_for_author", comment: "Migrations need a human", reason: `${files.len()} files` };
+            }
+            if regex_match(pr.title, "^Bump ") && files.all(|f| glob_match(f, "*.toml")) { "approve" } else { "skip" }
+        "#;
+        let config = ScriptingConfig::default();
+
+        assert_eq!(run(script, &input(&["/Cargo.toml", "/crates/api/Cargo.toml"]), &config).vote, HookVote::Approve);
+        assert_eq!(run(script, &input(&["/src/main.rs"]), &config).vote, HookVote::Skip);
+        assert_eq!(run(script, &input(&["/db/migrations/001.sql", "/Cargo.toml"]), &config), HookDecision {
+            vote: HookVote::WaitForAuthor,
+            comment: Some("Migrations need a human".to_string()),
+            reason: Some("2 files".to_string()),
+            failed: false,
+        });
+
+        let unknown = run(r#"#{ vote: "merge" }"#, &input(&[]), &config);
+        assert_eq!(unknown.reason(), "script returned an unknown vote 'merge'");
+        assert!(unknown.failed);
+        assert!(run(r#"regex_match(pr.title, "(")"#, &input(&[]), &config).reason().contains("invalid regex"));
+    }
+
+    #[test]
+    fn runaway_scripts_are_stopped_and_changed_files_reloaded_unless_broken() {
+        let config = ScriptingConfig { max_operations: 1_000, timeout: "1s".to_string() };
+        let stopped = run("loop {}", &input(&[]), &config);
+        assert_eq!(stopped.reason(), "script ran more than 1000 operations");
+        assert!(stopped.failed);
+
+        let config = ScriptingConfig { max_operations: u64::MAX, timeout: "1s".to_string() };
+        let started = Instant::now();
+        let stopped = run("loop {}", &input(&[]), &config);
+        assert_eq!(stopped.reason(), "script timed out after 1s");
+        assert!(started.elapsed() < std::time::Duration::from_secs(5));
+        assert!(run(r#"import "other" as other; "approve""#, &input(&[]), &config).failed);
+
+        let path = std::env::temp_dir().join(format!("fpr-script-test-{}.rhai", std::process::id()));
+        let mut cache = ScriptCache::default();
+        fs::write(&path, r#""skip""#).unwrap();
+        assert_eq!(evaluate(&cache.load(&path).unwrap(), &input(&[]), &config).vote, HookVote::Skip);
+        // A later modification time makes the cache compile the new version
+        fs::write(&path, r#""approve""#).unwrap();
+        fs::File::options().write(true).open(&path).unwrap()
+            .set_modified(SystemTime::now() + std::time::Duration::from_secs(5)).unwrap();
+        assert_eq!(evaluate(&cache.load(&path).unwrap(), &input(&[]), &config).vote, HookVote::Approve);
+
+        // A broken edit keeps the last version that compiled, and so does a missing file
+        fs::write(&path, r#"if {"#).unwrap();
+        fs::File::options().write(true).open(&path).unwrap()
+            .set_modified(SystemTime::now() + std::time::Duration::from_secs(10)).unwrap();
+        assert_eq!(evaluate(&cache.load(&path).unwrap(), &input(&[]), &config).vote, HookVote::Approve);
+        let _ = fs::remove_file(&path);
+        assert_eq!(evaluate(&cache.load(&path).unwrap(), &input(&[]), &config).vote, HookVote::Approve);
+
+        // Without a version that ever compiled there's nothing to run
+        assert!(ScriptCache::default().load(&path).is_err());
+    }
+}
@@ -0,0 +1,91 @@
+use std::env;
+use std::time::Duration;
+use tracing::{debug, warn};
+
+// Socket systemd listens on for status notifications, set for Type=notify services
+const NOTIFY_SOCKET: &str = "NOTIFY_SOCKET";
+
+/// Tell systemd the bot has started and is polling
+pub fn notify_ready() {
+    notify("READY=1");
+}
+
+/// Tell systemd the bot is shutting down
+pub fn notify_stopping() {
+    notify("STOPPING=1");
+}
+
+/// Tell systemd the bot is still alive
+pub fn notify_watchdog() {
+    notify("WATCHDOG=1");
+}
+
+/// How often to ping the watchdog, if systemd enabled it for this process (WatchdogSec=)
+pub fn watchdog_interval() -> Option<Duration> {
+    let usec: u64 = env::var("WATCHDOG_USEC").ok()?.parse().ok()?;
+    // The watchdog may be meant for another process in the service, e.g. a wrapper script
+    if let Some(pid) = env::var("WATCHDOG_PID").ok().and_then(|pid| pid.parse::<u32>().ok())
+        && pid != std::process::id()
+    {
+        return None;
+    }
+    // Ping twice per period, as systemd recommends
+    Some(Duration::from_micros(usec / 2)).filter(|interval| !interval.is_zero())
+}
+
+fn notify(state: &str) {
+    let Some(socket) = env::var_os(NOTIFY_SOCKET) else {
+        return;
+    };
+
+    match send(&socket, state) {
+        Ok(()) => debug!("Notified systemd: {}", state),
+        Err(e) => warn!("⚠ Failed to notify systemd ({}): {}", state, e),
+    }
+}
+
+#[cfg(unix)]
+fn send(socket: &std::ffi::OsStr, state: &str) -> std::io::Result<()> {
+    use std::os::unix::ffi::OsStrExt;
+    use std::os::unix::net::UnixDatagram;
+
+    let datagram = UnixDatagram::unbound()?;
+    match socket.as_bytes().strip_prefix(b"@") {
+        // A leading @ names a socket in Linux's abstract namespace
+        #[cfg(target_os = "linux")]
+        Some(name) => {
+            use std::os::linux::net::SocketAddrExt;
+            let address = std::os::unix::net::SocketAddr::from_abstract_name(name)?;
+            datagram.send_to_addr(state.as_bytes(), &address)?;
+        }
+        _ => {
+            datagram.send_to(state.as_bytes(), socket)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn send(_socket: &std::ffi::OsStr, _state: &str) -> std::io::Result<()> {
+    Err(std::io::Error::new(std::io::ErrorKind::Unsupported, "sd_notify needs unix sockets"))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::net::UnixDatagram;
+
+    #[test]
+    fn notifications_reach_the_notify_socket() {
+        let path = env::temp_dir().join(format!("fpr-notify-test-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixDatagram::bind(&path).unwrap();
+
+        send(path.as_os_str(), "READY=1").unwrap();
+
+        let mut buffer = [0; 64];
+        let length = listener.recv(&mut buffer).unwrap();
+        assert_eq!(&buffer[..length], b"READY=1");
+        std::fs::remove_file(&path).unwrap();
+    }
+}
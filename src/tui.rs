@@ -0,0 +1,405 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use ratatui::crossterm::event::{self, Event as TerminalEvent, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, List, ListItem, Paragraph, Row, Table, TableState};
+use ratatui::{DefaultTerminal, Frame};
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+use tokio::time;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+
+use fast_pr_reviewer::{redact, ReviewerState};
+
+// How often the dashboard is redrawn, so ages and the next poll countdown stay current
+const RENDER_INTERVAL: Duration = Duration::from_millis(250);
+
+// How long the input thread waits for a key before checking whether the dashboard stopped
+const INPUT_POLL_TIMEOUT: Duration = Duration::from_millis(100);
+
+// Number of warnings and errors kept for the errors pane
+const MAX_RECENT_ERRORS: usize = 50;
+
+/// What a key binding asks the main loop to do
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DashboardCommand {
+    TogglePause,
+    Approve(i32),
+    Skip(i32),
+    Quit,
+}
+
+/// A logged warning or error with when it happened
+pub type LoggedError = (DateTime<Utc>, String);
+
+/// Warnings and errors logged by the bot, newest last
+#[derive(Clone, Default)]
+pub struct RecentErrors(Arc<StdMutex<VecDeque<LoggedError>>>);
+
+impl RecentErrors {
+    fn push(&self, message: String) {
+        let mut errors = self.0.lock().unwrap();
+        if errors.len() == MAX_RECENT_ERRORS {
+            errors.pop_front();
+        }
+        errors.push_back((Utc::now(), message));
+    }
+
+    fn snapshot(&self) -> Vec<LoggedError> {
+        self.0.lock().unwrap().iter().cloned().collect()
+    }
+}
+
+/// Tracing layer feeding warnings and errors into the errors pane, since log output would draw over the dashboard
+pub struct ErrorCapture(pub RecentErrors);
+
+impl<S: Subscriber> Layer<S> for ErrorCapture {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let level = *event.metadata().level();
+        if level > Level::WARN {
+            return;
+        }
+
+        let mut message = MessageVisitor::default();
+        event.record(&mut message);
+        self.0.push(redact::redact(&format!("{} {}", level, message.0)));
+    }
+}
+
+#[derive(Default)]
+struct MessageVisitor(String);
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            self.0 = format!("{:?}", value);
+        }
+    }
+}
+
+/// One watched PR in the live feed
+#[derive(Debug, Clone)]
+pub struct FeedRow {
+    pub pr_id: i32,
+    pub repository: String,
+    pub author: String,
+    pub title: String,
+    pub created: Option<DateTime<Utc>>,
+    /// Outcome of the latest decision about the PR
+    pub decision: Option<String>,
+}
+
+/// Everything the dashboard shows, gathered up front so rendering doesn't touch shared state
+#[derive(Debug, Clone)]
+pub struct DashboardView {
+    pub now: DateTime<Utc>,
+    pub rows: Vec<FeedRow>,
+    pub selected: Option<i32>,
+    pub seen: usize,
+    pub approved: usize,
+    pub skipped: usize,
+    pub failed: usize,
+    pub paused: bool,
+    pub next_poll: Option<DateTime<Utc>>,
+    pub errors: Vec<LoggedError>,
+}
+
+impl DashboardView {
+    async fn collect(reviewer: &ReviewerState, errors: &RecentErrors, selected: Option<i32>) -> Self {
+        let decisions = reviewer.decisions();
+        // The latest decision wins, as decisions are oldest first
+        let latest: HashMap<i32, String> = decisions.recent().into_iter()
+            .map(|decision| (decision.pr_id, decision.outcome))
+            .collect();
+
+        let mut rows: Vec<FeedRow> = reviewer.tracked_prs().await.into_iter()
+            .map(|tracked| FeedRow {
+                decision: latest.get(&tracked.pr_id).cloned(),
+                pr_id: tracked.pr_id,
+                repository: tracked.repository,
+                author: tracked.author,
+                title: tracked.title,
+                created: tracked.created,
+            })
+            .collect();
+        rows.sort_unstable_by_key(|row| std::cmp::Reverse(row.pr_id));
+
+        Self {
+            now: Utc::now(),
+            rows,
+            selected,
+            seen: reviewer.seen_count().await,
+            approved: decisions.count("approved"),
+            skipped: decisions.count("skipped"),
+            failed: decisions.count("failed"),
+            paused: reviewer.is_paused().await,
+            next_poll: reviewer.next_poll().await,
+            errors: errors.snapshot(),
+        }
+    }
+
+    fn selected_index(&self) -> Option<usize> {
+        self.selected.and_then(|pr_id| self.rows.iter().position(|row| row.pr_id == pr_id))
+    }
+}
+
+/// Draw the dashboard; kept free of terminal handling so it can be tested against a headless backend
+pub fn render(frame: &mut Frame, view: &DashboardView) {
+    let [header, feed, errors, help] = Layout::vertical([
+        Constraint::Length(3),
+        Constraint::Min(5),
+        Constraint::Length(8),
+        Constraint::Length(1),
+    ]).areas(frame.area());
+
+    let next_poll = if view.paused {
+        Span::styled("paused", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
+    } else {
+        match view.next_poll {
+            Some(next_poll) if next_poll > view.now => Span::raw(format!("next poll in {}", format_age(next_poll - view.now))),
+            _ => Span::raw("polling..."),
+        }
+    };
+    let counters = Line::from(vec![
+        Span::raw(format!("Seen {}  ", view.seen)),
+        Span::styled(format!("Approved {}  ", view.approved), Style::default().fg(Color::Green)),
+        Span::styled(format!("Skipped {}  ", view.skipped), Style::default().fg(Color::Blue)),
+        Span::styled(format!("Failed {}  ", view.failed), Style::default().fg(Color::Red)),
+        Span::raw("| "),
+        next_poll,
+    ]);
+    frame.render_widget(Paragraph::new(counters).block(Block::default().borders(Borders::ALL).title(" FastPRReviewer ")), header);
+
+    let rows = view.rows.iter().map(|row| Row::new(vec![
+        format!("#{}", row.pr_id),
+        row.repository.clone(),
+        row.author.clone(),
+        row.title.clone(),
+        row.created.map(|created| format_age(view.now - created)).unwrap_or_else(|| "?".to_string()),
+        row.decision.clone().unwrap_or_else(|| "-".to_string()),
+    ]));
+    let table = Table::new(rows, [
+        Constraint::Length(8),
+        Constraint::Length(20),
+        Constraint::Length(20),
+        Constraint::Fill(1),
+        Constraint::Length(5),
+        Constraint::Length(10),
+    ])
+        .header(Row::new(["PR", "Repository", "Author", "Title", "Age", "Decision"]).style(Style::default().add_modifier(Modifier::BOLD)))
+        .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+        .block(Block::default().borders(Borders::ALL).title(" Pull requests "));
+    let mut state = TableState::default().with_selected(view.selected_index());
+    frame.render_stateful_widget(table, feed, &mut state);
+
+    let error_lines: Vec<ListItem> = view.errors.iter().rev()
+        .map(|(at, message)| ListItem::new(format!("{} {}", at.format("%H:%M:%S"), message)))
+        .collect();
+    frame.render_widget(List::new(error_lines).block(Block::default().borders(Borders::ALL).title(" Recent errors ")), errors);
+
+    frame.render_widget(Paragraph::new("↑/↓ select  a approve  s skip  p pause/resume  q quit"), help);
+}
+
+/// Short age like "42s", "7m", "3h" or "2d"
+fn format_age(age: chrono::Duration) -> String {
+    let seconds = age.num_seconds().max(0);
+    match seconds {
+        0..60 => format!("{}s", seconds),
+        60..3600 => format!("{}m", seconds / 60),
+        3600..86400 => format!("{}h", seconds / 3600),
+        _ => format!("{}d", seconds / 86400),
+    }
+}
+
+/// Turn a key press into a selection change or a command for the main loop
+fn handle_key(key: KeyEvent, rows: &[FeedRow], selected: &mut Option<i32>) -> Option<DashboardCommand> {
+    let index = selected.and_then(|pr_id| rows.iter().position(|row| row.pr_id == pr_id));
+    match key.code {
+        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => Some(DashboardCommand::Quit),
+        KeyCode::Char('q') | KeyCode::Esc => Some(DashboardCommand::Quit),
+        KeyCode::Char('p') => Some(DashboardCommand::TogglePause),
+        KeyCode::Char('a') => selected.map(DashboardCommand::Approve),
+        KeyCode::Char('s') => selected.map(DashboardCommand::Skip),
+        KeyCode::Down | KeyCode::Char('j') => {
+            let next = index.map_or(0, |index| (index + 1).min(rows.len().saturating_sub(1)));
+            *selected = rows.get(next).map(|row| row.pr_id);
+            None
+        }
+        KeyCode::Up | KeyCode::Char('k') => {
+            let previous = index.map_or(0, |index| index.saturating_sub(1));
+            *selected = rows.get(previous).map(|row| row.pr_id);
+            None
+        }
+        _ => None,
+    }
+}
+
+/// The running dashboard; the terminal is restored when it is dropped
+pub struct Dashboard {
+    stop: Option<oneshot::Sender<()>>,
+    task: Option<JoinHandle<()>>,
+}
+
+impl Dashboard {
+    /// Take over the terminal and start drawing; key bindings are sent to `commands`
+    pub fn start(reviewer: Arc<ReviewerState>, errors: RecentErrors, commands: mpsc::Sender<DashboardCommand>) -> Result<Self> {
+        let terminal = ratatui::try_init()?;
+
+        let (key_tx, key_rx) = mpsc::channel(16);
+        std::thread::spawn(move || read_keys(key_tx));
+
+        let (stop_tx, stop_rx) = oneshot::channel();
+        let task = tokio::spawn(run_dashboard(terminal, reviewer, errors, commands, key_rx, stop_rx));
+
+        Ok(Self { stop: Some(stop_tx), task: Some(task) })
+    }
+
+    /// Stop drawing and give the terminal back
+    pub async fn stop(mut self) {
+        if let Some(stop) = self.stop.take() {
+            let _ = stop.send(());
+        }
+        if let Some(task) = self.task.take() {
+            let _ = task.await;
+        }
+    }
+}
+
+impl Drop for Dashboard {
+    fn drop(&mut self) {
+        if let Some(task) = self.task.take() {
+            task.abort();
+        }
+        ratatui::restore();
+    }
+}
+
+async fn run_dashboard(
+    mut terminal: DefaultTerminal,
+    reviewer: Arc<ReviewerState>,
+    errors: RecentErrors,
+    commands: mpsc::Sender<DashboardCommand>,
+    mut keys: mpsc::Receiver<KeyEvent>,
+    mut stop: oneshot::Receiver<()>,
+) {
+    let mut render_tick = time::interval(RENDER_INTERVAL);
+    let mut selected = None;
+
+    loop {
+        let mut view = DashboardView::collect(&reviewer, &errors, selected).await;
+        if selected.is_none() || view.selected_index().is_none() {
+            selected = view.rows.first().map(|row| row.pr_id);
+            view.selected = selected;
+        }
+        if terminal.draw(|frame| render(frame, &view)).is_err() {
+            break;
+        }
+
+        tokio::select! {
+            _ = render_tick.tick() => (),
+            Some(key) = keys.recv() => {
+                if let Some(command) = handle_key(key, &view.rows, &mut selected)
+                    && commands.send(command).await.is_err()
+                {
+                    break;
+                }
+            }
+            _ = &mut stop => break,
+        }
+    }
+}
+
+/// Forward key presses until the dashboard stops listening; crossterm only offers blocking reads
+fn read_keys(keys: mpsc::Sender<KeyEvent>) {
+    while !keys.is_closed() {
+        match event::poll(INPUT_POLL_TIMEOUT) {
+            Ok(true) => {
+                if let Ok(TerminalEvent::Key(key)) = event::read()
+                    && key.kind == KeyEventKind::Press
+                    && keys.blocking_send(key).is_err()
+                {
+                    break;
+                }
+            }
+            Ok(false) => (),
+            Err(_) => break,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+
+    fn screen_text(terminal: &Terminal<TestBackend>) -> String {
+        let buffer = terminal.backend().buffer();
+        buffer.content.chunks(buffer.area.width as usize)
+            .map(|line| line.iter().map(|cell| cell.symbol()).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn dashboard_renders_feed_counters_and_errors() {
+        let now = Utc::now();
+        let view = DashboardView {
+            now,
+            rows: vec![
+                FeedRow {
+                    pr_id: 42,
+                    repository: "backend".to_string(),
+                    author: "Jane Doe".to_string(),
+                    title: "Bump dependencies".to_string(),
+                    created: Some(now - chrono::Duration::minutes(7)),
+                    decision: Some("approved".to_string()),
+                },
+                FeedRow {
+                    pr_id: 41,
+                    repository: "frontend".to_string(),
+                    author: "John Roe".to_string(),
+                    title: "Fix typo".to_string(),
+                    created: None,
+                    decision: None,
+                },
+            ],
+            selected: Some(41),
+            seen: 12,
+            approved: 5,
+            skipped: 1,
+            failed: 2,
+            paused: false,
+            next_poll: Some(now + chrono::Duration::seconds(30)),
+            errors: vec![(now, "ERROR ❌ Failed to approve PR #40: 403".to_string())],
+        };
+        let mut terminal = Terminal::new(TestBackend::new(110, 20)).unwrap();
+
+        terminal.draw(|frame| render(frame, &view)).unwrap();
+        let screen = screen_text(&terminal);
+
+        assert!(screen.contains("Seen 12  Approved 5  Skipped 1  Failed 2  | next poll in 30s"), "{}", screen);
+        assert!(screen.contains("#42"));
+        assert!(screen.contains("Bump dependencies"));
+        assert!(screen.contains("7m"));
+        assert!(screen.contains("approved"));
+        assert!(screen.contains("Failed to approve PR #40: 403"));
+
+        let selected_row = terminal.backend().buffer().content.chunks(110)
+            .find(|line| line.iter().map(|cell| cell.symbol()).collect::<String>().contains("#41"))
+            .unwrap();
+        assert!(selected_row[1].modifier.contains(Modifier::REVERSED));
+
+        let paused = DashboardView { paused: true, ..view };
+        terminal.draw(|frame| render(frame, &paused)).unwrap();
+        assert!(screen_text(&terminal).contains("| paused"));
+    }
+}
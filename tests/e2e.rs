@@ -0,0 +1,490 @@
+//! End-to-end tests running the bot binary against the mock Azure DevOps server
+
+// Shared with the library tests, which use parts of it these don't
+#[allow(dead_code)]
+mod mock_ado;
+
+use axum::http::{Method, StatusCode};
+use mock_ado::{Failure, MockAdo, BOT_ID};
+use serde_json::Value;
+use std::fs::{self, File};
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::time::Duration;
+
+const WATCHED_USER: &str = "Jane Doe";
+
+/// The bot binary polling the mock server every second, killed when dropped
+struct Bot {
+    child: Child,
+    dir: PathBuf,
+}
+
+impl Bot {
+    /// Start the bot with a config watching Jane Doe plus `extra_config`, and wait for its first poll
+    async fn start(ado: &MockAdo, name: &str, extra_config: &str) -> Self {
+        Self::start_with_args(ado, name, extra_config, &[]).await
+    }
+
+    /// Like `start`, passing `extra_args` on the command line as well
+    async fn start_with_args(ado: &MockAdo, name: &str, extra_config: &str, extra_args: &[&str]) -> Self {
+        let dir = std::env::temp_dir().join(format!("fpr-e2e-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        let config = format!(r#"
+organization = {url:?}
+project = "web"
+personal_access_token = "e2e-test-pat-0123456789"
+reviewer_id = {BOT_ID:?}
+watched_users = [{WATCHED_USER:?}]
+approval_queue = {queue:?}
+decision_log = {decisions:?}
+{extra_config}
+"#,
+            url = ado.url(),
+            queue = dir.join("approval_queue.json"),
+            decisions = dir.join("decisions.jsonl"));
+        fs::write(dir.join("config.toml"), config).unwrap();
+
+        let child = Command::new(env!("CARGO_BIN_EXE_fast-pr-reviewer"))
+            .arg("--config").arg(dir.join("config.toml"))
+            .args(["--interval", "1"])
+            .args(extra_args)
+            .env("RUST_LOG", "info")
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(File::create(dir.join("bot.log")).unwrap())
+            .spawn()
+            .expect("start the bot");
+        let bot = Self { child, dir };
+
+        wait_until(&bot, "the first poll", || ado.requests().iter().any(|request| request.uri.starts_with("/web/_apis/git/pullrequests?"))).await;
+        bot
+    }
+
+    /// Decisions written to the decision log so far
+    fn decisions(&self) -> Vec<Value> {
+        fs::read_to_string(self.dir.join("decisions.jsonl")).unwrap_or_default()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    fn outcomes(&self, pr_id: i32) -> Vec<String> {
+        self.decisions().iter()
+            .filter(|decision| decision["pr_id"] == pr_id)
+            .map(|decision| decision["outcome"].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    fn log(&self) -> String {
+        fs::read_to_string(self.dir.join("bot.log")).unwrap_or_default()
+    }
+}
+
+impl Drop for Bot {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        if !std::thread::panicking() {
+            let _ = fs::remove_dir_all(&self.dir);
+        }
+    }
+}
+
+/// Wait up to 15 seconds for `condition`, failing the test with the bot's log otherwise
+async fn wait_until(bot: &Bot, what: &str, condition: impl Fn() -> bool) {
+    for _ in 0..150 {
+        if condition() {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    panic!("Timed out waiting for {}, bot log:\n{}", what, bot.log());
+}
+
+#[tokio::test]
+async fn approves_new_prs_from_watched_users_only() {
+    let ado = MockAdo::start().await;
+    let bot = Bot::start(&ado, "approve", "").await;
+
+    let watched = ado.create_pr(WATCHED_USER, "Bump serde to 1.0.219");
+    let other = ado.create_pr("John Smith", "Rewrite the build");
+
+    wait_until(&bot, "the approval", || ado.vote(watched, BOT_ID) == Some(10)).await;
+    assert_eq!(bot.outcomes(watched), ["approved"]);
+    // PRs from other users are left alone
+    assert_eq!(ado.vote(other, BOT_ID), None);
+    assert!(bot.outcomes(other).is_empty());
+}
+
+#[tokio::test]
+async fn push_during_approval_delay_reschedules_the_vote() {
+    let ado = MockAdo::start().await;
+    let bot = Bot::start(&ado, "push", "[[rules]]\nname = \"delayed\"\napprove_after = \"2s\"\n").await;
+
+    let pr_id = ado.create_pr(WATCHED_USER, "Update the changelog");
+    wait_until(&bot, "the scheduled approval", || bot.outcomes(pr_id).contains(&"scheduled".to_string())).await;
+    let head = ado.push(pr_id);
+
+    wait_until(&bot, "the approval", || ado.vote(pr_id, BOT_ID) == Some(10)).await;
+    assert_eq!(bot.outcomes(pr_id), ["scheduled", "cancelled", "scheduled", "approved"]);
+    let reviewer = ado.pr(pr_id).reviewers.into_iter().find(|reviewer| reviewer.id == BOT_ID).unwrap();
+    assert_eq!(reviewer.voted_on.as_deref(), Some(head.as_str()));
+}
+
+#[tokio::test]
+async fn required_reviewer_stays_required_after_voting() {
+    let ado = MockAdo::start().await;
+    let bot = Bot::start(&ado, "required", "[[rules]]\nname = \"required\"\n[rules.add_reviewers]\nrequired = true\n").await;
+
+    let pr_id = ado.create_pr(WATCHED_USER, "Tighten lint rules");
+    wait_until(&bot, "the approval", || ado.vote(pr_id, BOT_ID) == Some(10)).await;
+    let reviewer = ado.pr(pr_id).reviewers.into_iter().find(|reviewer| reviewer.id == BOT_ID).unwrap();
+    assert!(reviewer.is_required);
+}
+
+#[tokio::test]
+async fn throttling_and_server_errors_are_retried() {
+    let ado = MockAdo::start().await;
+    let bot = Bot::start(&ado, "retry", "").await;
+
+    ado.inject(Failure::new(500, Method::GET, "/_apis/git/pullrequests"));
+    ado.inject(Failure::new(429, Method::PUT, "/reviewers/"));
+    ado.inject(Failure::new(503, Method::PUT, "/reviewers/"));
+    let pr_id = ado.create_pr(WATCHED_USER, "Fix flaky test");
+
+    wait_until(&bot, "the approval", || ado.vote(pr_id, BOT_ID) == Some(10)).await;
+    let votes: Vec<StatusCode> = ado.requests().into_iter()
+        .filter(|request| request.method == Method::PUT)
+        .map(|request| request.status)
+        .collect();
+    assert_eq!(votes, [StatusCode::TOO_MANY_REQUESTS, StatusCode::SERVICE_UNAVAILABLE, StatusCode::OK]);
+    assert_eq!(bot.outcomes(pr_id), ["approved"]);
+}
+
+#[tokio::test]
+async fn sign_in_redirect_is_reported_instead_of_parsed() {
+    let ado = MockAdo::start().await;
+    let bot = Bot::start(&ado, "sign-in", "").await;
+
+    ado.inject(Failure::new(203, Method::GET, "/_apis/git/pullrequests").times(usize::MAX));
+    let pr_id = ado.create_pr(WATCHED_USER, "Add retry to uploads");
+    wait_until(&bot, "the sign-in error", || bot.log().contains("redirected to its sign-in page")).await;
+    assert_eq!(ado.vote(pr_id, BOT_ID), None);
+
+    // Once the PAT works again, the PR is picked up
+    ado.clear_failures();
+    wait_until(&bot, "the approval", || ado.vote(pr_id, BOT_ID) == Some(10)).await;
+    assert!(!bot.log().contains("Failed to parse"));
+}
+
+#[tokio::test]
+async fn chatops_command_approves_pr_opened_before_startup() {
+    let ado = MockAdo::start().await;
+    let pr_id = ado.create_pr(WATCHED_USER, "Tidy up imports");
+    let state_file = std::env::temp_dir().join(format!("fpr-e2e-chatops-state-{}.json", std::process::id()));
+    let _ = fs::remove_file(&state_file);
+    let chatops = format!("[chatops]\nallowed_users = [\"sam-lead\"]\nstate_file = {:?}\nscan_interval = \"1s\"\n", state_file);
+
+    let bot = Bot::start(&ado, "chatops", &chatops).await;
+    assert_eq!(ado.vote(pr_id, BOT_ID), None);
+    ado.add_comment(pr_id, "Sam Lead", "/fastreview why");
+    wait_until(&bot, "the reply", || ado.pr(pr_id).threads[0].comments.len() == 2).await;
+    drop(bot);
+
+    // A command posted while the bot is down runs once it is back, and handled ones don't run again
+    ado.add_comment(pr_id, "Sam Lead", "/fastreview approve");
+    let bot = Bot::start(&ado, "chatops-restarted", &chatops).await;
+    wait_until(&bot, "the approval", || ado.vote(pr_id, BOT_ID) == Some(10)).await;
+    wait_until(&bot, "the reply", || ado.pr(pr_id).threads[1].comments.len() == 2).await;
+    assert_eq!(bot.outcomes(pr_id), ["approved"]);
+    assert_eq!(ado.pr(pr_id).threads[0].comments.len(), 2);
+    let _ = fs::remove_file(&state_file);
+}
+
+#[tokio::test]
+async fn chatops_command_is_seen_on_an_unchanged_pr_from_anyone() {
+    let ado = MockAdo::start().await;
+    let pr_id = ado.create_pr("John Smith", "Rewrite the build");
+    let state_file = std::env::temp_dir().join(format!("fpr-e2e-chatops-unchanged-state-{}.json", std::process::id()));
+    let _ = fs::remove_file(&state_file);
+    let chatops = format!("[chatops]\nallowed_users = [\"Sam-Lead@Contoso.com\"]\nstate_file = {:?}\nscan_interval = \"1s\"\n", state_file);
+    let bot = Bot::start(&ado, "chatops-unchanged", &chatops).await;
+
+    // Let the bot see the PR a few times before the command is posted; the PR itself never changes
+    let polls = |ado: &MockAdo| ado.requests().iter().filter(|request| request.uri.contains("/_apis/git/pullrequests?")).count();
+    let seen = polls(&ado);
+    wait_until(&bot, "a few more polls", || polls(&ado) >= seen + 2).await;
+    ado.add_comment(pr_id, "Sam Lead", "/fastreview why");
+    ado.add_comment(pr_id, "Sam Lead Impostor", "/fastreview approve");
+
+    wait_until(&bot, "the replies", || ado.pr(pr_id).threads.iter().all(|thread| thread.comments.len() == 2)).await;
+    let threads = ado.pr(pr_id).threads;
+    assert!(threads[0].comments[1].content.contains("No decisions"), "{:?}", threads[0]);
+    assert!(threads[1].comments[1].content.contains("not allowed"), "{:?}", threads[1]);
+    assert_eq!(ado.vote(pr_id, BOT_ID), None);
+    let _ = fs::remove_file(&state_file);
+}
+
+#[tokio::test]
+async fn chatops_reads_comments_once_per_scan_interval() {
+    let ado = MockAdo::start().await;
+    let pr_id = ado.create_pr("John Smith", "Rewrite the build");
+    let state_file = std::env::temp_dir().join(format!("fpr-e2e-chatops-interval-state-{}.json", std::process::id()));
+    let _ = fs::remove_file(&state_file);
+    let chatops = format!("[chatops]\nallowed_users = [\"sam-lead\"]\nstate_file = {:?}\nscan_interval = \"1h\"\n", state_file);
+    let bot = Bot::start(&ado, "chatops-interval", &chatops).await;
+
+    let polls = |ado: &MockAdo| ado.requests().iter().filter(|request| request.uri.contains("/_apis/git/pullrequests?")).count();
+    let seen = polls(&ado);
+    wait_until(&bot, "a few more polls", || polls(&ado) >= seen + 3).await;
+    let thread_reads = ado.requests().iter()
+        .filter(|request| request.method == Method::GET && request.uri.contains(&format!("/pullRequests/{}/threads", pr_id)))
+        .count();
+    assert_eq!(thread_reads, 1);
+    let _ = fs::remove_file(&state_file);
+}
+
+#[tokio::test]
+async fn decision_hook_blocks_prs_until_it_approves() {
+    let ado = MockAdo::start().await;
+    let hook = r#"
+[[rules]]
+name = "hooked"
+[rules.hook]
+command = ["sh", "-c", "if grep -q /migrations/; then echo '{\"vote\":\"wait_for_author\",\"comment\":\"Migrations need a human\",\"reason\":\"touches migrations\"}'; else echo '{\"vote\":\"approve\"}'; fi"]
+timeout = "10s"
+"#;
+    let bot = Bot::start(&ado, "hook", hook).await;
+
+    let pr_id = ado.create_pr(WATCHED_USER, "Add the orders table");
+    ado.update_pr(pr_id, |pr| pr.files = vec!["/db/migrations/001_orders.sql".to_string()]);
+    wait_until(&bot, "the wait-for-author vote", || ado.vote(pr_id, BOT_ID) == Some(-5)).await;
+    assert_eq!(ado.pr(pr_id).threads[0].comments[0].content, "Migrations need a human");
+
+    // Once the migration is gone the hook approves, after voting and commenting only once
+    ado.update_pr(pr_id, |pr| pr.files = vec!["/src/orders.rs".to_string()]);
+    ado.push(pr_id);
+    wait_until(&bot, "the approval", || ado.vote(pr_id, BOT_ID) == Some(10)).await;
+    assert_eq!(bot.outcomes(pr_id), ["blocked", "approved"]);
+    assert_eq!(ado.pr(pr_id).threads.len(), 1);
+}
+
+#[tokio::test]
+async fn decision_hook_runs_while_conditions_are_checked() {
+    let ado = MockAdo::start().await;
+    let started = std::env::temp_dir().join(format!("fpr-e2e-hook-started-{}", std::process::id()));
+    let _ = fs::remove_file(&started);
+    // The mock never links work items, so the conditions fail while the hook is still running
+    let hook = format!(r#"
+[[rules]]
+name = "hooked"
+[rules.conditions.work_items]
+[rules.hook]
+command = ["sh", "-c", "echo started >> {}; sleep 60; echo '{{\"vote\":\"approve\"}}'"]
+timeout = "120s"
+"#, started.display());
+    let bot = Bot::start(&ado, "hook-alongside", &hook).await;
+
+    let pr_id = ado.create_pr(WATCHED_USER, "Add the orders table");
+    wait_until(&bot, "the conditions' wait-for-author vote", || ado.vote(pr_id, BOT_ID) == Some(-5)).await;
+    wait_until(&bot, "the hook to start alongside the conditions", || started.exists()).await;
+    let _ = fs::remove_file(&started);
+}
+
+#[tokio::test]
+async fn blocking_decision_hook_runs_again_only_after_a_push() {
+    let ado = MockAdo::start().await;
+    let runs = std::env::temp_dir().join(format!("fpr-e2e-hook-blocking-runs-{}", std::process::id()));
+    let _ = fs::remove_file(&runs);
+    let hook = format!(r#"
+[[rules]]
+name = "hooked"
+[rules.hook]
+command = ["sh", "-c", "echo run >> {}; echo '{{\"vote\":\"wait_for_author\",\"reason\":\"needs a human\"}}'"]
+timeout = "10s"
+"#, runs.display());
+    let bot = Bot::start(&ado, "hook-blocking", &hook).await;
+    let run_count = || fs::read_to_string(&runs).map(|runs| runs.lines().count()).unwrap_or(0);
+
+    let pr_id = ado.create_pr(WATCHED_USER, "Add the orders table");
+    wait_until(&bot, "the wait-for-author vote", || ado.vote(pr_id, BOT_ID) == Some(-5)).await;
+
+    // Still blocked on later polls, without asking the hook again
+    let polls = |ado: &MockAdo| ado.requests().iter().filter(|request| request.uri.contains("/_apis/git/pullrequests?")).count();
+    let seen = polls(&ado);
+    wait_until(&bot, "a few more polls", || polls(&ado) >= seen + 2).await;
+    assert_eq!(run_count(), 1);
+    assert_eq!(bot.outcomes(pr_id), ["blocked"]);
+
+    ado.push(pr_id);
+    wait_until(&bot, "the hook to run for the new commit", || run_count() == 2).await;
+    let _ = fs::remove_file(&runs);
+}
+
+#[tokio::test]
+async fn failing_decision_hook_skips_the_pr() {
+    let ado = MockAdo::start().await;
+    let runs = std::env::temp_dir().join(format!("fpr-e2e-hook-runs-{}", std::process::id()));
+    let _ = fs::remove_file(&runs);
+    let hook = format!(r#"
+[[rules]]
+name = "hooked"
+[rules.hook]
+command = ["sh", "-c", "echo run >> {}; exit 1"]
+timeout = "10s"
+"#, runs.display());
+    let bot = Bot::start(&ado, "hook-failing", &hook).await;
+
+    let pr_id = ado.create_pr(WATCHED_USER, "Add the orders table");
+    wait_until(&bot, "the skip", || bot.outcomes(pr_id) == ["skipped"]).await;
+    assert!(bot.log().contains("hook failed for PR"), "{}", bot.log());
+
+    // Skipped for good: later polls neither run the hook again nor vote
+    let polls = |ado: &MockAdo| ado.requests().iter().filter(|request| request.uri.contains("/_apis/git/pullrequests?")).count();
+    let seen = polls(&ado);
+    wait_until(&bot, "a few more polls", || polls(&ado) >= seen + 2).await;
+    assert_eq!(fs::read_to_string(&runs).unwrap().lines().count(), 1);
+    assert_eq!(ado.vote(pr_id, BOT_ID), None);
+    let _ = fs::remove_file(&runs);
+}
+
+#[tokio::test]
+async fn config_edits_are_picked_up_and_bad_ones_keep_the_previous_config() {
+    let ado = MockAdo::start().await;
+    let bot = Bot::start(&ado, "reload", "").await;
+    let config_path = bot.dir.join("config.toml");
+    let original = fs::read_to_string(&config_path).unwrap();
+
+    let watch_both = original.replace(&format!("[{:?}]", WATCHED_USER), &format!("[{:?}, \"John Smith\"]", WATCHED_USER));
+    fs::write(&config_path, &watch_both).unwrap();
+    wait_until(&bot, "the reload", || bot.log().contains("Now watching 2 users (added: [John Smith], removed: [])")).await;
+    let added = ado.create_pr("John Smith", "Rewrite the build");
+    wait_until(&bot, "the approval", || ado.vote(added, BOT_ID) == Some(10)).await;
+
+    // An edit that doesn't parse leaves the bot running on what it had
+    fs::write(&config_path, watch_both.replace("watched_users = [", "watched_users = [[")).unwrap();
+    wait_until(&bot, "the rejected reload", || bot.log().contains("keeping the previous one")).await;
+    let after = ado.create_pr("John Smith", "Rewrite the build again");
+    wait_until(&bot, "the approval", || ado.vote(after, BOT_ID) == Some(10)).await;
+    assert_eq!(bot.outcomes(after), ["approved"]);
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn sighup_reload_is_not_repeated_by_the_file_watcher() {
+    let ado = MockAdo::start().await;
+    let bot = Bot::start(&ado, "sighup", "").await;
+    let config_path = bot.dir.join("config.toml");
+    let original = fs::read_to_string(&config_path).unwrap();
+
+    fs::write(&config_path, original.replace(&format!("[{:?}]", WATCHED_USER), &format!("[{:?}, \"John Smith\"]", WATCHED_USER))).unwrap();
+    let status = Command::new("kill").args(["-HUP", &bot.child.id().to_string()]).status().unwrap();
+    assert!(status.success());
+    wait_until(&bot, "the reload", || bot.log().contains("Now watching 2 users")).await;
+
+    // The file watcher checks every 2 seconds; give it the chance to pick up the edit again
+    tokio::time::sleep(Duration::from_secs(3)).await;
+    let log = bot.log();
+    assert!(log.contains("Received SIGHUP"), "{}", log);
+    assert!(!log.contains("changed, reloading it"), "{}", log);
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn stopping_during_a_stuck_poll_exits_with_the_timeout_exit_code() {
+    let ado = MockAdo::start().await;
+    let started = std::env::temp_dir().join(format!("fpr-e2e-hook-started-{}", std::process::id()));
+    let _ = fs::remove_file(&started);
+    let config = format!(r#"
+[shutdown]
+timeout = "1s"
+timeout_exit_code = 3
+[[rules]]
+name = "slow"
+[rules.hook]
+command = ["sh", "-c", "touch {}; sleep 20"]
+timeout = "30s"
+"#, started.display());
+    let mut bot = Bot::start(&ado, "drain-timeout", &config).await;
+
+    ado.create_pr(WATCHED_USER, "Add the orders table");
+    wait_until(&bot, "the hook", || started.exists()).await;
+    let status = Command::new("kill").args(["-TERM", &bot.child.id().to_string()]).status().unwrap();
+    assert!(status.success());
+    wait_until(&bot, "the bot to stop", || bot.log().contains("did not finish within 1s")).await;
+    let mut exited = None;
+    for _ in 0..50 {
+        exited = bot.child.try_wait().unwrap();
+        if exited.is_some() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    assert_eq!(exited.and_then(|status| status.code()), Some(3), "{}", bot.log());
+    let _ = fs::remove_file(&started);
+}
+
+#[tokio::test]
+async fn json_logs_carry_fields_and_the_poll_and_pr_spans() {
+    let ado = MockAdo::start().await;
+    let bot = Bot::start_with_args(&ado, "json-log", "", &["--log-format", "json"]).await;
+
+    let pr_id = ado.create_pr(WATCHED_USER, "Bump serde to 1.0.219");
+    wait_until(&bot, "the approval", || ado.vote(pr_id, BOT_ID) == Some(10)).await;
+    wait_until(&bot, "the decision", || !bot.outcomes(pr_id).is_empty()).await;
+
+    let lines: Vec<Value> = bot.log().lines()
+        .map(|line| serde_json::from_str(line).unwrap_or_else(|e| panic!("{} is not JSON: {}", line, e)))
+        .collect();
+    let approved = lines.iter()
+        .find(|line| line["fields"]["message"].as_str().is_some_and(|message| message.starts_with("Successfully approved PR")))
+        .unwrap_or_else(|| panic!("No approval in {:#?}", lines));
+    assert!(approved["timestamp"].is_string());
+    assert_eq!(approved["level"], "INFO");
+    assert_eq!(approved["fields"]["pr_id"], pr_id);
+    assert_eq!(approved["fields"]["vote"], 10);
+    assert_eq!(approved["span"]["name"], "pr");
+    assert_eq!(approved["span"]["pr_id"], pr_id);
+    assert_eq!(approved["span"]["repo"], "backend");
+    assert_eq!(approved["span"]["author"], WATCHED_USER);
+    let spans: Vec<&str> = approved["spans"].as_array().unwrap().iter().map(|span| span["name"].as_str().unwrap()).collect();
+    assert_eq!(spans, ["poll", "pr"]);
+
+    let decision = lines.iter()
+        .find(|line| line["fields"]["pr_id"] == pr_id && line["fields"]["outcome"] == "approved")
+        .unwrap_or_else(|| panic!("No decision in {:#?}", lines));
+    assert_eq!(decision["fields"]["operation"], bot.decisions()[0]["action"]);
+    assert_eq!(decision["fields"]["repo"], "backend");
+    assert_eq!(decision["fields"]["author"], WATCHED_USER);
+}
+
+#[tokio::test]
+async fn mock_lists_prs_in_pages_and_resets_votes_on_push() {
+    let ado = MockAdo::start().await;
+    let ids: Vec<i32> = (1..=5).map(|n| ado.create_pr(WATCHED_USER, &format!("Change {}", n))).collect();
+    ado.update_pr(ids[0], |pr| pr.status = "abandoned".to_string());
+
+    let client = reqwest::Client::new();
+    let page = |skip: usize| {
+        let request = client.get(format!("{}/web/_apis/git/pullrequests?status=active&$top=2&$skip={}", ado.url(), skip))
+            .basic_auth("", Some("pat"));
+        async move { request.send().await.unwrap().json::<Value>().await.unwrap() }
+    };
+    let listed: Vec<Value> = [page(0).await, page(2).await, page(4).await].iter()
+        .flat_map(|page| page["value"].as_array().unwrap().iter().map(|pr| pr["pullRequestId"].clone()))
+        .collect();
+    assert_eq!(listed, [ids[4], ids[3], ids[2], ids[1]]);
+
+    let vote_url = format!("{}/web/_apis/git/repositories/backend/pullRequests/{}/reviewers/{}", ado.url(), ids[1], BOT_ID);
+    let response = client.put(&vote_url).basic_auth("", Some("pat")).json(&serde_json::json!({ "vote": 10 })).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    ado.push(ids[1]);
+    assert_eq!(ado.vote(ids[1], BOT_ID), Some(0));
+    assert_eq!(ado.pr(ids[1]).iterations.len(), 2);
+
+    // Without credentials the mock answers like Azure DevOps does
+    assert_eq!(client.get(&vote_url).send().await.unwrap().status(), StatusCode::UNAUTHORIZED);
+}
@@ -0,0 +1,120 @@
+//! Tests of the library API against the mock Azure DevOps server, in a single process
+
+// Shared with the end-to-end tests, which use more of it
+#[allow(dead_code)]
+mod mock_ado;
+
+use fast_pr_reviewer::{AppConfig, AzureDevOpsClient, Reviewer};
+use mock_ado::{MockAdo, BOT_ID};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[tokio::test]
+async fn reviewer_approves_skips_and_reports_decisions() {
+    let ado = MockAdo::start().await;
+    let queue_path = std::env::temp_dir().join(format!("fpr-library-test-queue-{}.json", std::process::id()));
+    let config: AppConfig = toml::from_str(&format!(r#"
+        organization = {:?}
+        project = "web"
+        reviewer_id = {:?}
+        watched_users = ["Jane Doe"]
+        approval_queue = {:?}
+    "#, ado.url(), BOT_ID, queue_path)).unwrap();
+    let client = AzureDevOpsClient::new(&config.organization, &config.project, "library-test-pat-0123");
+    // Only PRs opened after the reviewer was created are approved, even when it first polls later
+    let before_start = ado.create_pr("Jane Doe", "Opened before the reviewer");
+    let mut reviewer = Reviewer::new(client, config).unwrap();
+    let decisions = Arc::new(Mutex::new(Vec::new()));
+    let recorded = decisions.clone();
+    reviewer.on_decision(move |decision| recorded.lock().unwrap().push((decision.pr_id, decision.outcome.clone())));
+
+    let watched = ado.create_pr("Jane Doe", "Bump serde to 1.0.219");
+    let other = ado.create_pr("John Smith", "Rewrite the build");
+    assert_eq!(reviewer.run_once().await.unwrap(), 3);
+    assert_eq!(ado.vote(watched, BOT_ID), Some(10));
+    assert_eq!(ado.vote(other, BOT_ID), None);
+    assert_eq!(ado.vote(before_start, BOT_ID), None);
+
+    // Skipped PRs are left alone, even by later polls
+    let skipped = ado.create_pr("Jane Doe", "Experimental refactoring");
+    reviewer.skip(skipped, "a test").await.unwrap();
+    assert_eq!(reviewer.run_once().await.unwrap(), 0);
+    assert_eq!(ado.vote(skipped, BOT_ID), None);
+
+    // PRs of other users can still be approved on request
+    reviewer.approve(other, "a test").await.unwrap();
+    assert_eq!(ado.vote(other, BOT_ID), Some(10));
+
+    // run keeps polling until told to stop
+    let polled_before = ado.requests().len();
+    reviewer.run(Duration::from_millis(50), tokio::time::sleep(Duration::from_millis(300))).await.unwrap();
+    assert!(ado.requests().len() > polled_before + 1);
+
+    assert_eq!(*decisions.lock().unwrap(), [
+        (watched, "approved".to_string()),
+        (skipped, "skipped".to_string()),
+        (other, "approved".to_string()),
+    ]);
+    let _ = std::fs::remove_file(queue_path);
+}
+
+#[tokio::test]
+async fn reviewers_in_one_process_keep_their_own_state() {
+    let ado = MockAdo::start().await;
+    let reviewer = |name: &str| {
+        let queue_path = std::env::temp_dir().join(format!("fpr-library-test-{}-queue-{}.json", name, std::process::id()));
+        let config: AppConfig = toml::from_str(&format!(r#"
+            organization = {:?}
+            project = "web"
+            reviewer_id = {:?}
+            watched_users = ["Jane Doe"]
+            approval_queue = {:?}
+        "#, ado.url(), BOT_ID, queue_path)).unwrap();
+        let client = AzureDevOpsClient::new(&config.organization, &config.project, "library-test-pat-0123");
+        (Reviewer::new(client, config).unwrap(), queue_path)
+    };
+
+    let (first, first_queue) = reviewer("first");
+    let pr_id = ado.create_pr("Jane Doe", "Bump serde to 1.0.219");
+    // Each reviewer only approves PRs created after it started
+    let (second, second_queue) = reviewer("second");
+    second.set_paused(true).await;
+
+    assert_eq!(second.run_once().await.unwrap(), 1);
+    assert_eq!(ado.vote(pr_id, BOT_ID), None);
+    assert!(!first.is_paused().await);
+    assert!(!first.state().is_seen(pr_id).await);
+
+    assert_eq!(first.run_once().await.unwrap(), 1);
+    assert_eq!(ado.vote(pr_id, BOT_ID), Some(10));
+    assert_eq!(first.state().tracked_prs().await.len(), 1);
+    assert!(second.state().tracked_prs().await.is_empty());
+    let _ = std::fs::remove_file(first_queue);
+    let _ = std::fs::remove_file(second_queue);
+}
+
+#[tokio::test]
+async fn watched_prs_are_reviewed_at_most_max_concurrency_at_a_time() {
+    let ado = MockAdo::start().await;
+    let queue_path = std::env::temp_dir().join(format!("fpr-library-test-concurrency-queue-{}.json", std::process::id()));
+    let config: AppConfig = toml::from_str(&format!(r#"
+        organization = {:?}
+        project = "web"
+        reviewer_id = {:?}
+        watched_users = ["Jane Doe"]
+        approval_queue = {:?}
+        max_concurrency = 2
+    "#, ado.url(), BOT_ID, queue_path)).unwrap();
+    let client = AzureDevOpsClient::new(&config.organization, &config.project, "library-test-pat-0123")
+        .with_rate_limit(100, 100.0);
+    let reviewer = Reviewer::new(client, config).unwrap();
+    let prs: Vec<i32> = (1..=6).map(|n| ado.create_pr("Jane Doe", &format!("Bump dependency {}", n))).collect();
+    ado.set_latency(Duration::from_millis(50));
+
+    assert_eq!(reviewer.run_once().await.unwrap(), prs.len());
+    for pr_id in prs {
+        assert_eq!(ado.vote(pr_id, BOT_ID), Some(10));
+    }
+    assert_eq!(ado.max_in_flight(), 2);
+    let _ = std::fs::remove_file(queue_path);
+}
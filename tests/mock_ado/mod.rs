@@ -0,0 +1,622 @@
+//! In-memory Azure DevOps server for end-to-end tests.
+//!
+//! Emulates the part of the REST API the bot uses: pull request listing with paging, reviewers and votes,
+//! comment threads, statuses, iterations and their changed files, policy evaluations and work items. Tests
+//! drive it through [`MockAdo`]: create and update PRs, push commits, and inject failures such as throttling,
+//! server errors or the sign-in page Azure DevOps serves for a bad PAT.
+
+use axum::extract::{Path, Query, Request, State};
+use axum::http::{header, Method, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post, put};
+use axum::{Json, Router};
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Identity the mock treats as the caller, e.g. as the author of comments posted through the API
+pub const BOT_ID: &str = "bot-id";
+const BOT_NAME: &str = "Review Bot";
+
+/// The one project the mock serves; project-scoped APIs are only found under it
+const PROJECT: &str = "web";
+
+// Repository new PRs are opened in unless a test moves them
+const DEFAULT_REPOSITORY: (&str, &str) = ("repo-1", "backend");
+
+/// A pull request as the mock keeps it
+#[derive(Debug, Clone)]
+pub struct MockPr {
+    pub id: i32,
+    pub title: String,
+    pub author: String,
+    pub repository_id: String,
+    pub repository_name: String,
+    pub source_branch: String,
+    pub target_branch: String,
+    /// active, completed or abandoned
+    pub status: String,
+    pub merge_status: String,
+    pub created: DateTime<Utc>,
+    pub reviewers: Vec<MockReviewer>,
+    /// Source commit of every push, oldest first; the last one is the PR's head
+    pub iterations: Vec<String>,
+    /// Paths of the files the PR changes, reported as edits
+    pub files: Vec<String>,
+    pub threads: Vec<MockThread>,
+    pub statuses: Vec<Value>,
+    pub auto_complete_set_by: Option<String>,
+}
+
+impl MockPr {
+    /// Current source commit
+    pub fn head(&self) -> &str {
+        self.iterations.last().map(String::as_str).unwrap_or_default()
+    }
+
+    fn to_json(&self) -> Value {
+        let mut pr = json!({
+            "pullRequestId": self.id,
+            "title": self.title,
+            "createdBy": { "id": identity_id(&self.author), "displayName": self.author },
+            "creationDate": self.created.to_rfc3339_opts(SecondsFormat::AutoSi, true),
+            "sourceRefName": self.source_branch,
+            "targetRefName": self.target_branch,
+            "repository": { "id": self.repository_id, "name": self.repository_name, "project": { "id": "project-1" } },
+            "status": self.status,
+            "mergeStatus": self.merge_status,
+            "lastMergeSourceCommit": { "commitId": self.head() },
+            "reviewers": self.reviewers.iter().map(MockReviewer::to_json).collect::<Vec<_>>(),
+        });
+        if let Some(id) = &self.auto_complete_set_by {
+            pr["autoCompleteSetBy"] = json!({ "id": id });
+        }
+        pr
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MockReviewer {
+    pub id: String,
+    pub display_name: String,
+    pub vote: i32,
+    pub is_required: bool,
+    /// Source commit the reviewer last voted on
+    pub voted_on: Option<String>,
+}
+
+impl MockReviewer {
+    fn to_json(&self) -> Value {
+        json!({ "id": self.id, "displayName": self.display_name, "vote": self.vote, "isRequired": self.is_required })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MockThread {
+    pub id: i32,
+    pub status: String,
+    pub comments: Vec<MockComment>,
+}
+
+#[derive(Debug, Clone)]
+pub struct MockComment {
+    pub id: i32,
+    pub author: String,
+    pub content: String,
+    pub published: DateTime<Utc>,
+}
+
+/// A failure served instead of the real response to matching requests
+#[derive(Debug, Clone)]
+pub struct Failure {
+    status: u16,
+    method: Method,
+    path: String,
+    remaining: usize,
+}
+
+impl Failure {
+    /// Answer the next request with this method whose path contains `path` with `status`;
+    /// 429 comes with a Retry-After and 203 with the sign-in page
+    pub fn new(status: u16, method: Method, path: &str) -> Self {
+        Self { status, method, path: path.to_string(), remaining: 1 }
+    }
+
+    /// Fail this many matching requests instead of one
+    pub fn times(mut self, times: usize) -> Self {
+        self.remaining = times;
+        self
+    }
+
+    fn into_response(self) -> Response {
+        let status = StatusCode::from_u16(self.status).expect("valid status code");
+        match status {
+            StatusCode::NON_AUTHORITATIVE_INFORMATION => (
+                status,
+                [(header::CONTENT_TYPE, "text/html; charset=utf-8")],
+                "<html><head><title>Azure DevOps Services | Sign In</title></head><body>Sign in to your account</body></html>",
+            ).into_response(),
+            StatusCode::TOO_MANY_REQUESTS => (
+                status,
+                [(header::RETRY_AFTER, "1")],
+                Json(json!({ "message": "Request was blocked due to exceeding usage of resource 'Core' in namespace 'User'." })),
+            ).into_response(),
+            _ => (status, Json(json!({ "message": format!("Injected failure with status {}", self.status) }))).into_response(),
+        }
+    }
+}
+
+/// A request the mock received, with the status it answered
+#[derive(Debug, Clone)]
+pub struct LoggedRequest {
+    pub method: Method,
+    pub uri: String,
+    pub status: StatusCode,
+}
+
+#[derive(Default)]
+struct MockState {
+    prs: Vec<MockPr>,
+    failures: Vec<Failure>,
+    requests: Vec<LoggedRequest>,
+    next_pr_id: i32,
+    next_commit: u64,
+    next_thread_id: i32,
+    latency: Duration,
+    in_flight: usize,
+    max_in_flight: usize,
+}
+
+impl MockState {
+    fn new_commit(&mut self) -> String {
+        self.next_commit += 1;
+        format!("{:040x}", self.next_commit)
+    }
+
+    fn pr(&mut self, id: i32) -> Option<&mut MockPr> {
+        self.prs.iter_mut().find(|pr| pr.id == id)
+    }
+
+    /// A PR looked up through a repository-scoped URL, which may name the repository by ID or name
+    fn repository_pr(&mut self, repository: &str, id: i32) -> Option<&mut MockPr> {
+        self.prs.iter_mut()
+            .find(|pr| pr.id == id && (pr.repository_id == repository || pr.repository_name.eq_ignore_ascii_case(repository)))
+    }
+}
+
+type SharedState = Arc<Mutex<MockState>>;
+
+/// A running mock server; it stops with the test's runtime
+pub struct MockAdo {
+    url: String,
+    state: SharedState,
+}
+
+impl MockAdo {
+    pub async fn start() -> Self {
+        let state = SharedState::default();
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.expect("bind mock server");
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let router = router(state.clone());
+        tokio::spawn(async move { axum::serve(listener, router).await });
+        Self { url, state }
+    }
+
+    /// Base URL to use as the organization
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Open a PR from `author` right now, with one commit and no reviewers; returns its ID
+    pub fn create_pr(&self, author: &str, title: &str) -> i32 {
+        let mut state = self.state.lock().unwrap();
+        state.next_pr_id += 1;
+        let id = state.next_pr_id;
+        let commit = state.new_commit();
+        state.prs.push(MockPr {
+            id,
+            title: title.to_string(),
+            author: author.to_string(),
+            repository_id: DEFAULT_REPOSITORY.0.to_string(),
+            repository_name: DEFAULT_REPOSITORY.1.to_string(),
+            source_branch: format!("refs/heads/topic-{}", id),
+            target_branch: "refs/heads/main".to_string(),
+            status: "active".to_string(),
+            merge_status: "succeeded".to_string(),
+            created: Utc::now(),
+            reviewers: Vec::new(),
+            iterations: vec![commit],
+            files: vec!["/README.md".to_string()],
+            threads: Vec::new(),
+            statuses: Vec::new(),
+            auto_complete_set_by: None,
+        });
+        id
+    }
+
+    /// Change a PR in place, e.g. to abandon it or rename it
+    pub fn update_pr(&self, id: i32, update: impl FnOnce(&mut MockPr)) {
+        let mut state = self.state.lock().unwrap();
+        update(state.pr(id).expect("unknown PR"));
+    }
+
+    /// Push a new commit to a PR, which resets every vote like a "reset votes on push" branch policy
+    pub fn push(&self, id: i32) -> String {
+        let mut state = self.state.lock().unwrap();
+        let commit = state.new_commit();
+        let pr = state.pr(id).expect("unknown PR");
+        pr.iterations.push(commit.clone());
+        for reviewer in &mut pr.reviewers {
+            reviewer.vote = 0;
+        }
+        commit
+    }
+
+    /// Post a comment from someone else in a new thread, e.g. a chat-ops command
+    pub fn add_comment(&self, id: i32, author: &str, content: &str) {
+        let mut state = self.state.lock().unwrap();
+        state.next_thread_id += 1;
+        let thread_id = state.next_thread_id;
+        state.pr(id).expect("unknown PR").threads.push(MockThread {
+            id: thread_id,
+            status: "active".to_string(),
+            comments: vec![MockComment { id: 1, author: author.to_string(), content: content.to_string(), published: Utc::now() }],
+        });
+    }
+
+    pub fn pr(&self, id: i32) -> MockPr {
+        self.state.lock().unwrap().pr(id).expect("unknown PR").clone()
+    }
+
+    /// Vote of a reviewer on a PR, if they are a reviewer
+    pub fn vote(&self, id: i32, reviewer_id: &str) -> Option<i32> {
+        self.pr(id).reviewers.iter().find(|reviewer| reviewer.id == reviewer_id).map(|reviewer| reviewer.vote)
+    }
+
+    /// Serve failures to matching requests, before any failure injected earlier runs out
+    pub fn inject(&self, failure: Failure) {
+        self.state.lock().unwrap().failures.push(failure);
+    }
+
+    pub fn clear_failures(&self) {
+        self.state.lock().unwrap().failures.clear();
+    }
+
+    /// Every request so far, oldest first
+    pub fn requests(&self) -> Vec<LoggedRequest> {
+        self.state.lock().unwrap().requests.clone()
+    }
+
+    /// Hold every request for `latency` before answering it
+    pub fn set_latency(&self, latency: Duration) {
+        self.state.lock().unwrap().latency = latency;
+    }
+
+    /// Most requests the mock has been answering at the same time
+    pub fn max_in_flight(&self) -> usize {
+        self.state.lock().unwrap().max_in_flight
+    }
+}
+
+fn router(state: SharedState) -> Router {
+    let pull_request = "/_apis/git/repositories/{repository}/pullRequests/{id}";
+    let project = Router::new()
+        .route("/_apis/git/repositories", get(repositories))
+        .route("/_apis/git/pullrequests", get(list_prs))
+        .route("/_apis/git/pullrequests/{id}", get(get_pr))
+        .route(pull_request, get(get_repository_pr).patch(update_pr))
+        .route(&format!("{}/reviewers", pull_request), get(reviewers))
+        .route(&format!("{}/reviewers/{{reviewer}}", pull_request), put(set_reviewer).get(get_reviewer).delete(remove_reviewer))
+        .route(&format!("{}/threads", pull_request), get(threads).post(create_thread))
+        .route(&format!("{}/threads/{{thread}}/comments", pull_request), post(reply))
+        .route(&format!("{}/statuses", pull_request), get(statuses).post(create_status))
+        .route(&format!("{}/iterations", pull_request), get(iterations))
+        .route(&format!("{}/iterations/{{iteration}}/changes", pull_request), get(iteration_changes))
+        .route(&format!("{}/workitems", pull_request), get(|| async { Json(json!({ "value": [], "count": 0 })) }))
+        .route("/_apis/policy/evaluations", get(|| async { Json(json!({ "value": [], "count": 0 })) }));
+    Router::new()
+        .route("/_apis/connectionData", get(connection_data))
+        .nest(&format!("/{}", PROJECT), project)
+        .layer(middleware::from_fn_with_state(state.clone(), authenticate_and_inject))
+        .with_state(state)
+}
+
+/// Reject anonymous requests, serve injected failures and log every request
+async fn authenticate_and_inject(State(state): State<SharedState>, request: Request, next: Next) -> Response {
+    let method = request.method().clone();
+    let uri = request.uri().to_string();
+    let authenticated = request.headers().get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("Basic "));
+
+    let (injected, latency) = {
+        let mut state = state.lock().unwrap();
+        state.in_flight += 1;
+        state.max_in_flight = state.max_in_flight.max(state.in_flight);
+        let injected = state.failures.iter_mut()
+            .find(|failure| failure.remaining > 0 && failure.method == method && request.uri().path().contains(&failure.path))
+            .map(|failure| {
+                failure.remaining -= 1;
+                failure.clone()
+            });
+        (injected, state.latency)
+    };
+    tokio::time::sleep(latency).await;
+
+    let response = match injected {
+        _ if !authenticated => StatusCode::UNAUTHORIZED.into_response(),
+        Some(failure) => failure.into_response(),
+        None => next.run(request).await,
+    };
+
+    let mut state = state.lock().unwrap();
+    state.in_flight -= 1;
+    state.requests.push(LoggedRequest { method, uri, status: response.status() });
+    response
+}
+
+fn not_found(what: &str) -> Response {
+    (StatusCode::NOT_FOUND, Json(json!({ "message": format!("{} does not exist.", what) }))).into_response()
+}
+
+fn identity_id(display_name: &str) -> String {
+    if display_name == BOT_NAME {
+        return BOT_ID.to_string();
+    }
+    display_name.to_lowercase().replace(' ', "-")
+}
+
+async fn connection_data() -> Json<Value> {
+    Json(json!({ "authenticatedUser": { "id": BOT_ID, "providerDisplayName": BOT_NAME } }))
+}
+
+async fn repositories(State(state): State<SharedState>) -> Json<Value> {
+    let state = state.lock().unwrap();
+    let mut repositories: Vec<(String, String)> = vec![(DEFAULT_REPOSITORY.0.to_string(), DEFAULT_REPOSITORY.1.to_string())];
+    for pr in &state.prs {
+        if !repositories.iter().any(|(id, _)| *id == pr.repository_id) {
+            repositories.push((pr.repository_id.clone(), pr.repository_name.clone()));
+        }
+    }
+    let value: Vec<Value> = repositories.into_iter().map(|(id, name)| json!({ "id": id, "name": name })).collect();
+    Json(json!({ "count": value.len(), "value": value }))
+}
+
+#[derive(Deserialize)]
+struct ListQuery {
+    #[serde(rename = "status")]
+    status: Option<String>,
+    #[serde(rename = "$top")]
+    top: Option<usize>,
+    #[serde(rename = "$skip")]
+    skip: Option<usize>,
+}
+
+/// Newest PRs first, filtered by status (active unless asked for another, or "all") and paged with $top/$skip
+async fn list_prs(State(state): State<SharedState>, Query(query): Query<ListQuery>) -> Json<Value> {
+    let state = state.lock().unwrap();
+    let status = query.status.unwrap_or_else(|| "active".to_string());
+    let mut prs: Vec<&MockPr> = state.prs.iter().filter(|pr| status == "all" || pr.status == status).collect();
+    prs.sort_by(|a, b| b.created.cmp(&a.created).then(b.id.cmp(&a.id)));
+
+    let value: Vec<Value> = prs.into_iter()
+        .skip(query.skip.unwrap_or(0))
+        .take(query.top.unwrap_or(100))
+        .map(MockPr::to_json)
+        .collect();
+    Json(json!({ "count": value.len(), "value": value }))
+}
+
+async fn get_pr(State(state): State<SharedState>, Path(id): Path<i32>) -> Response {
+    match state.lock().unwrap().pr(id) {
+        Some(pr) => Json(pr.to_json()).into_response(),
+        None => not_found(&format!("Pull request {}", id)),
+    }
+}
+
+async fn get_repository_pr(State(state): State<SharedState>, Path((repository, id)): Path<(String, i32)>) -> Response {
+    match state.lock().unwrap().repository_pr(&repository, id) {
+        Some(pr) => Json(pr.to_json()).into_response(),
+        None => not_found(&format!("Pull request {}", id)),
+    }
+}
+
+/// Abandon, complete or set auto-complete; completing needs the PR's current head as lastMergeSourceCommit
+async fn update_pr(State(state): State<SharedState>, Path((repository, id)): Path<(String, i32)>, Json(update): Json<Value>) -> Response {
+    let mut state = state.lock().unwrap();
+    let Some(pr) = state.repository_pr(&repository, id) else {
+        return not_found(&format!("Pull request {}", id));
+    };
+
+    if let Some(id) = update["autoCompleteSetBy"]["id"].as_str() {
+        pr.auto_complete_set_by = Some(id.to_string());
+    }
+    if let Some(status) = update["status"].as_str() {
+        let expected_head = update["lastMergeSourceCommit"]["commitId"].as_str();
+        if status == "completed" && expected_head.is_some_and(|head| head != pr.head()) {
+            return (StatusCode::CONFLICT, Json(json!({ "message": "The pull request has been updated." }))).into_response();
+        }
+        pr.status = status.to_string();
+    }
+    Json(pr.to_json()).into_response()
+}
+
+async fn reviewers(State(state): State<SharedState>, Path((repository, id)): Path<(String, i32)>) -> Response {
+    match state.lock().unwrap().repository_pr(&repository, id) {
+        Some(pr) => {
+            let value: Vec<Value> = pr.reviewers.iter().map(MockReviewer::to_json).collect();
+            Json(json!({ "count": value.len(), "value": value })).into_response()
+        }
+        None => not_found(&format!("Pull request {}", id)),
+    }
+}
+
+async fn get_reviewer(State(state): State<SharedState>, Path((repository, id, reviewer)): Path<(String, i32, String)>) -> Response {
+    let mut state = state.lock().unwrap();
+    let Some(pr) = state.repository_pr(&repository, id) else {
+        return not_found(&format!("Pull request {}", id));
+    };
+    match pr.reviewers.iter().find(|r| r.id == reviewer) {
+        Some(reviewer) => Json(reviewer.to_json()).into_response(),
+        None => not_found(&format!("Reviewer {}", reviewer)),
+    }
+}
+
+/// Add a reviewer or change their vote
+async fn set_reviewer(
+    State(state): State<SharedState>,
+    Path((repository, id, reviewer)): Path<(String, i32, String)>,
+    Json(body): Json<Value>,
+) -> Response {
+    let mut state = state.lock().unwrap();
+    let Some(pr) = state.repository_pr(&repository, id) else {
+        return not_found(&format!("Pull request {}", id));
+    };
+    let vote = body["vote"].as_i64().unwrap_or(0) as i32;
+    let head = pr.head().to_string();
+
+    let index = match pr.reviewers.iter().position(|r| r.id == reviewer) {
+        Some(index) => index,
+        None => {
+            let display_name = if reviewer == BOT_ID { BOT_NAME.to_string() } else { reviewer.clone() };
+            pr.reviewers.push(MockReviewer { id: reviewer, display_name, vote: 0, is_required: false, voted_on: None });
+            pr.reviewers.len() - 1
+        }
+    };
+    let entry = &mut pr.reviewers[index];
+    entry.vote = vote;
+    if vote != 0 {
+        entry.voted_on = Some(head);
+    }
+    // Like Azure DevOps, a PUT without isRequired leaves the reviewer optional
+    entry.is_required = body["isRequired"].as_bool().unwrap_or(false);
+    Json(entry.to_json()).into_response()
+}
+
+async fn remove_reviewer(State(state): State<SharedState>, Path((repository, id, reviewer)): Path<(String, i32, String)>) -> Response {
+    let mut state = state.lock().unwrap();
+    let Some(pr) = state.repository_pr(&repository, id) else {
+        return not_found(&format!("Pull request {}", id));
+    };
+    pr.reviewers.retain(|r| r.id != reviewer);
+    StatusCode::NO_CONTENT.into_response()
+}
+
+fn thread_json(thread: &MockThread) -> Value {
+    let comments: Vec<Value> = thread.comments.iter()
+        .map(|comment| json!({
+            "id": comment.id,
+            "author": {
+                "id": identity_id(&comment.author),
+                "displayName": comment.author,
+                "uniqueName": format!("{}@contoso.com", identity_id(&comment.author)),
+            },
+            "content": comment.content,
+            "publishedDate": comment.published.to_rfc3339_opts(SecondsFormat::AutoSi, true),
+            "commentType": "text",
+        }))
+        .collect();
+    json!({ "id": thread.id, "status": thread.status, "comments": comments, "isDeleted": false })
+}
+
+async fn threads(State(state): State<SharedState>, Path((repository, id)): Path<(String, i32)>) -> Response {
+    match state.lock().unwrap().repository_pr(&repository, id) {
+        Some(pr) => {
+            let value: Vec<Value> = pr.threads.iter().map(thread_json).collect();
+            Json(json!({ "count": value.len(), "value": value })).into_response()
+        }
+        None => not_found(&format!("Pull request {}", id)),
+    }
+}
+
+/// Start a thread with the caller as the author of its comments
+async fn create_thread(State(state): State<SharedState>, Path((repository, id)): Path<(String, i32)>, Json(body): Json<Value>) -> Response {
+    let mut state = state.lock().unwrap();
+    state.next_thread_id += 1;
+    let thread_id = state.next_thread_id;
+    let Some(pr) = state.repository_pr(&repository, id) else {
+        return not_found(&format!("Pull request {}", id));
+    };
+
+    let comments = body["comments"].as_array().cloned().unwrap_or_default().iter()
+        .zip(1..)
+        .map(|(comment, comment_id)| MockComment {
+            id: comment_id,
+            author: BOT_NAME.to_string(),
+            content: comment["content"].as_str().unwrap_or_default().to_string(),
+            published: Utc::now(),
+        })
+        .collect();
+    let thread = MockThread { id: thread_id, status: body["status"].as_str().unwrap_or("active").to_string(), comments };
+    let response = thread_json(&thread);
+    pr.threads.push(thread);
+    Json(response).into_response()
+}
+
+async fn reply(
+    State(state): State<SharedState>,
+    Path((repository, id, thread_id)): Path<(String, i32, i32)>,
+    Json(body): Json<Value>,
+) -> Response {
+    let mut state = state.lock().unwrap();
+    let Some(pr) = state.repository_pr(&repository, id) else {
+        return not_found(&format!("Pull request {}", id));
+    };
+    let Some(thread) = pr.threads.iter_mut().find(|thread| thread.id == thread_id) else {
+        return not_found(&format!("Thread {}", thread_id));
+    };
+
+    let comment = MockComment {
+        id: thread.comments.len() as i32 + 1,
+        author: BOT_NAME.to_string(),
+        content: body["content"].as_str().unwrap_or_default().to_string(),
+        published: Utc::now(),
+    };
+    let response = json!({ "id": comment.id, "content": comment.content });
+    thread.comments.push(comment);
+    Json(response).into_response()
+}
+
+async fn statuses(State(state): State<SharedState>, Path((repository, id)): Path<(String, i32)>) -> Response {
+    match state.lock().unwrap().repository_pr(&repository, id) {
+        Some(pr) => Json(json!({ "count": pr.statuses.len(), "value": pr.statuses })).into_response(),
+        None => not_found(&format!("Pull request {}", id)),
+    }
+}
+
+async fn create_status(State(state): State<SharedState>, Path((repository, id)): Path<(String, i32)>, Json(mut status): Json<Value>) -> Response {
+    let mut state = state.lock().unwrap();
+    let Some(pr) = state.repository_pr(&repository, id) else {
+        return not_found(&format!("Pull request {}", id));
+    };
+    status["id"] = json!(pr.statuses.len() + 1);
+    pr.statuses.push(status.clone());
+    Json(status).into_response()
+}
+
+async fn iteration_changes(State(state): State<SharedState>, Path((repository, id, _iteration)): Path<(String, i32, i32)>) -> Response {
+    match state.lock().unwrap().repository_pr(&repository, id) {
+        Some(pr) => {
+            let entries: Vec<Value> = pr.files.iter().zip(1..)
+                .map(|(path, change_id)| json!({ "changeId": change_id, "item": { "path": path }, "changeType": "edit" }))
+                .collect();
+            Json(json!({ "changeEntries": entries })).into_response()
+        }
+        None => not_found(&format!("Pull request {}", id)),
+    }
+}
+
+async fn iterations(State(state): State<SharedState>, Path((repository, id)): Path<(String, i32)>) -> Response {
+    match state.lock().unwrap().repository_pr(&repository, id) {
+        Some(pr) => {
+            let value: Vec<Value> = pr.iterations.iter().zip(1..)
+                .map(|(commit, iteration)| json!({ "id": iteration, "sourceRefCommit": { "commitId": commit } }))
+                .collect();
+            Json(json!({ "count": value.len(), "value": value })).into_response()
+        }
+        None => not_found(&format!("Pull request {}", id)),
+    }
+}